@@ -0,0 +1,100 @@
+//! Compare the load times of a 500 bindings configuration, with the
+//! visitor based deserialization of crokey and with an intermediate
+//! String per key.
+//!
+//! Run with `cargo bench --bench config_load`
+
+use {
+    crokey::*,
+    serde::{
+        Deserialize,
+        Deserializer,
+    },
+    std::{
+        collections::HashMap,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+const ROUNDS: u32 = 200;
+
+/// A key deserialized through an allocated String
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct StringKey(KeyCombination);
+
+impl<'de> Deserialize<'de> for StringKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map(StringKey).map_err(serde::de::Error::custom)
+    }
+}
+
+fn config() -> String {
+    let modifiers = [
+        "",
+        "ctrl-",
+        "alt-",
+        "shift-",
+        "ctrl-alt-",
+        "ctrl-shift-",
+        "alt-shift-",
+        "ctrl-alt-shift-",
+    ];
+    let keys = ('a'..='z')
+        .map(|c| c.to_string())
+        .chain((1..=12).map(|n| format!("f{n}")))
+        .chain(["enter", "tab", "home", "end", "pageup", "pagedown"].map(String::from))
+        .chain(('0'..='9').map(|c| c.to_string()))
+        .chain(["up", "down", "left", "right", "insert", "delete"].map(String::from))
+        .chain([",", ".", "/", ";", "[", "]"].map(String::from));
+    let mut config = String::from("{\n");
+    let mut count = 0;
+    for key in keys {
+        for modifier in modifiers {
+            if count < 500 {
+                config.push_str(&format!("\"{modifier}{key}\": action_{count}\n"));
+                count += 1;
+            }
+        }
+    }
+    config.push('}');
+    assert_eq!(count, 500);
+    config
+}
+
+/// Measure the mean time of a load, `load` returning the number of bindings
+/// so that the work can't be optimized away
+fn measure(name: &str, config: &str, load: impl Fn(&str) -> usize) -> Duration {
+    let mut count = 0;
+    for _ in 0..ROUNDS / 10 {
+        count += load(config); // warm up
+    }
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        count += load(config);
+    }
+    let duration = start.elapsed() / ROUNDS;
+    assert_eq!(count, 500 * (ROUNDS + ROUNDS / 10) as usize);
+    println!("{name:>12}: {duration:?} per load");
+    duration
+}
+
+fn main() {
+    let config = config();
+    let with_visitor = measure("visitor", &config, |config| {
+        deser_hjson::from_str::<HashMap<KeyCombination, String>>(config).unwrap().len()
+    });
+    let with_string = measure("string", &config, |config| {
+        deser_hjson::from_str::<HashMap<StringKey, String>>(config).unwrap().len()
+    });
+    println!(
+        "the visitor takes {:.0}% of the time of the string",
+        100.0 * with_visitor.as_secs_f64() / with_string.as_secs_f64(),
+    );
+}
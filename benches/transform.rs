@@ -0,0 +1,118 @@
+//! Measure the time of the transformation of a key event by the combiner,
+//! and check it doesn't allocate.
+//!
+//! Run with `cargo bench --bench transform`
+
+use {
+    crokey::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        KeyEventState,
+        KeyModifiers,
+        ModifierKeyCode,
+    },
+    std::{
+        alloc::{
+            GlobalAlloc,
+            Layout,
+            System,
+        },
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        time::Instant,
+    },
+};
+
+const ROUNDS: usize = 100_000;
+
+/// The system allocator, counting the allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn event(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+    KeyEvent {
+        code,
+        modifiers,
+        kind,
+        state: KeyEventState::empty(),
+    }
+}
+
+/// The events of some typing and of a few combinations, in kitty mode
+fn events() -> Vec<KeyEvent> {
+    use KeyEventKind::*;
+    let ctrl = KeyModifiers::CONTROL;
+    let none = KeyModifiers::NONE;
+    let shift = KeyCode::Modifier(ModifierKeyCode::LeftShift);
+    vec![
+        event(KeyCode::Char('a'), none, Press),
+        event(KeyCode::Char('a'), none, Release),
+        event(KeyCode::Char('b'), none, Press),
+        event(KeyCode::Char('b'), none, Repeat),
+        event(KeyCode::Char('b'), none, Release),
+        event(shift, KeyModifiers::SHIFT, Press),
+        event(KeyCode::Char('C'), KeyModifiers::SHIFT, Press),
+        event(KeyCode::Char('C'), KeyModifiers::SHIFT, Release),
+        event(shift, KeyModifiers::SHIFT, Release),
+        event(KeyCode::Char('x'), ctrl, Press),
+        event(KeyCode::Char('y'), ctrl, Press),
+        event(KeyCode::Char('y'), ctrl, Release),
+        event(KeyCode::Char('x'), ctrl, Release),
+        event(KeyCode::Char(' '), none, Press),
+        event(KeyCode::Char('j'), none, Press),
+        event(KeyCode::Char('k'), none, Press),
+        event(KeyCode::Char('k'), none, Release),
+        event(KeyCode::Null, none, Press),
+    ]
+}
+
+/// Transform the events `ROUNDS` times, returning the number of
+/// combinations so that the work can't be optimized away
+fn run(core: &mut CombinerCore, events: &[KeyEvent]) -> usize {
+    let mut count = 0;
+    for _ in 0..ROUNDS {
+        for event in events {
+            if core.transform(*event).is_some() {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+fn main() {
+    let events = events();
+    for combining in [false, true] {
+        let mut core = CombinerCore::default();
+        core.set_combining(combining);
+        run(&mut core, &events); // warm up
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let count = run(&mut core, &events);
+        let duration = start.elapsed() / (ROUNDS * events.len()) as u32;
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        let mode = if combining { "combining" } else { "ansi" };
+        println!(
+            "{mode:>10}: {duration:?} per event, {count} combinations, {allocations} allocations",
+        );
+        assert_eq!(allocations, 0, "transform allocated");
+    }
+}
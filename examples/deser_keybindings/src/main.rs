@@ -9,14 +9,13 @@ use {
         },
     },
     serde::Deserialize,
-    std::collections::HashMap,
 };
 
 /// This is an example of a configuration structure which contains
-/// a map from KeyEvent to String.
+/// a map from KeyCombination to String.
 #[derive(Deserialize)]
 struct Config {
-    keybindings: HashMap<KeyCombination, String>,
+    keybindings: NormalizedKeyMap<String>,
 }
 
 /// An example of what could be a configuration file
@@ -47,7 +46,7 @@ pub fn main() {
                 println!("bye!");
                 break;
             }
-            if let Some(word) = config.keybindings.get(&key) {
+            if let Some(word) = config.keybindings.get(key) {
                 println!(
                     "You hit {} which is mapped to {}",
                     fmt.to_string(key).green(),
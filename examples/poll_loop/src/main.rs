@@ -0,0 +1,43 @@
+//! To run this example, cd to the poll_loop repository then do `cargo run`
+use {
+    crokey::*,
+    crossterm::{
+        style::Stylize,
+        terminal,
+    },
+    std::time::Duration,
+};
+
+pub fn main() {
+    let fmt = KeyCombinationFormat::default();
+    let mut combiner = Combiner::default();
+    let support = combiner.enable_combining_with_report();
+    if let Some(unavailability) = support.unavailability {
+        println!("No key combination: {}", unavailability);
+    }
+    println!("Type any key combination, or ctrl-q to quit");
+    terminal::enable_raw_mode().unwrap();
+    let mut ticks = 0;
+    loop {
+        match combiner.poll_transform(Duration::from_secs(1)) {
+            Ok(Some(key!(ctrl-q))) => {
+                break;
+            }
+            Ok(Some(key_combination)) => {
+                print!("You typed {}\r\n", fmt.to_string(key_combination).blue());
+            }
+            Ok(None) => {
+                // the timeout is the place for background work
+                ticks += 1;
+                print!("{}\r\n", format!("{} s without key", ticks).dim());
+                continue;
+            }
+            Err(e) => {
+                print!("Error: {}\r\n", e);
+                break;
+            }
+        }
+        ticks = 0;
+    }
+    terminal::disable_raw_mode().unwrap();
+}
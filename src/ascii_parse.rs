@@ -0,0 +1,243 @@
+//! A `const fn` parser of the key combinations written with ASCII chars,
+//! to define keys in constants and statics without macro.
+
+use {
+    crate::{
+        parse::{
+            KEY_NAMES,
+            MODIFIER_NAMES,
+            PLATFORM_MODIFIER_NAMES,
+        },
+        *,
+    },
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+    std::fmt,
+};
+
+/// The reason why [parse_ascii] failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseAsciiError {
+    /// The string, or the char it gives as code point, isn't ASCII
+    NotAscii,
+    /// The key isn't a known key name nor a printable ASCII char
+    UnknownKey,
+    /// The string contains several key codes, like `ctrl-a-b`
+    MultipleCodes,
+}
+
+impl ParseAsciiError {
+    const fn message(self) -> &'static str {
+        match self {
+            Self::NotAscii => "the key isn't ASCII",
+            Self::UnknownKey => "unknown key",
+            Self::MultipleCodes => "several key codes can't be parsed in const context",
+        }
+    }
+}
+
+impl fmt::Display for ParseAsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ParseAsciiError {}
+
+/// Parse a key combination in a const context.
+///
+/// This understands the syntax of [parse] for the combinations of one key
+/// code written in ASCII: the key names, the printable ASCII chars, and
+/// their code points (e.g. `0x22` for `"`). The result is the same as
+/// the one of [parse], i.e. [normalized](KeyCombination::normalized).
+///
+/// ```
+/// use crokey::*;
+/// const SAVE: KeyCombination = match parse_ascii("ctrl-s") {
+///     Ok(key) => key,
+///     Err(_) => panic!("invalid key"),
+/// };
+/// assert_eq!(SAVE, key!(ctrl-s));
+/// assert_eq!(parse_ascii("command-c"), Ok(key!(super-c)));
+/// assert_eq!(parse_ascii("ctrl-a-b"), Err(ParseAsciiError::MultipleCodes));
+/// assert_eq!(parse_ascii("alt-é"), Err(ParseAsciiError::NotAscii));
+/// ```
+pub const fn parse_ascii(raw: &str) -> Result<KeyCombination, ParseAsciiError> {
+    let bytes = raw.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii() {
+            return Err(ParseAsciiError::NotAscii);
+        }
+        i += 1;
+    }
+    let mut modifiers = KeyModifiers::NONE;
+    let mut start = 0;
+    while let Some((modifier, len)) = modifier_at(bytes, start) {
+        modifiers = modifiers.union(modifier);
+        start += len;
+    }
+    // a hyphen alone is the hyphen key, other ones separate key codes
+    if bytes.len() - start > 1 {
+        let mut i = start;
+        while i < bytes.len() {
+            if bytes[i] == b'-' {
+                return Err(ParseAsciiError::MultipleCodes);
+            }
+            i += 1;
+        }
+    }
+    let code = match key_code(bytes, start) {
+        Ok(code) => code,
+        Err(e) => return Err(e),
+    };
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+    let code = match code {
+        Char(c) if shift => Char(c.to_ascii_uppercase()),
+        Char(c) if c.is_ascii_uppercase() => {
+            modifiers = modifiers.union(KeyModifiers::SHIFT);
+            code
+        }
+        code => code,
+    };
+    if matches!(code, BackTab) {
+        modifiers = modifiers.union(KeyModifiers::SHIFT);
+    }
+    Ok(KeyCombination {
+        codes: OneToThree::One(code),
+        modifiers,
+    })
+}
+
+/// Parse a key combination in a const context, panicking (at compile time
+/// in a const or a static) when it's not valid.
+///
+/// See [parse_ascii] for the accepted syntax.
+///
+/// ```
+/// use crokey::*;
+/// static QUIT: KeyCombination = parse_ascii_ok("ctrl-q");
+/// assert_eq!(QUIT, key!(ctrl-q));
+/// ```
+pub const fn parse_ascii_ok(raw: &str) -> KeyCombination {
+    match parse_ascii(raw) {
+        Ok(key) => key,
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+/// Tell whether the bytes contain the lowercase prefix at `start`,
+/// ignoring the ASCII case
+const fn starts_with_at(bytes: &[u8], start: usize, prefix: &[u8]) -> bool {
+    if bytes.len() - start < prefix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[start + i].to_ascii_lowercase() != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Return the modifier whose name, in [MODIFIER_NAMES], [MODIFIER_SYNONYMS]
+/// or the platform modifiers, is at `start`, and the length of this name
+const fn modifier_at(bytes: &[u8], start: usize) -> Option<(KeyModifiers, usize)> {
+    let tables = [MODIFIER_NAMES, MODIFIER_SYNONYMS];
+    let mut t = 0;
+    while t < tables.len() {
+        let mut i = 0;
+        while i < tables[t].len() {
+            let (name, modifier) = tables[t][i];
+            if starts_with_at(bytes, start, name.as_bytes()) {
+                return Some((modifier, name.len()));
+            }
+            i += 1;
+        }
+        t += 1;
+    }
+    let platform_modifiers = PlatformModifiers::current();
+    let [primary, secondary] = PLATFORM_MODIFIER_NAMES;
+    if starts_with_at(bytes, start, primary.as_bytes()) {
+        return Some((platform_modifiers.primary, primary.len()));
+    }
+    if starts_with_at(bytes, start, secondary.as_bytes()) {
+        return Some((platform_modifiers.secondary, secondary.len()));
+    }
+    None
+}
+
+/// Parse the key code written from `start` to the end of the bytes
+const fn key_code(bytes: &[u8], start: usize) -> Result<KeyCode, ParseAsciiError> {
+    let len = bytes.len() - start;
+    if len == 1 {
+        return match bytes[start] {
+            b'\r' | b'\n' => Ok(Enter),
+            b if b.is_ascii_graphic() || b == b' ' => Ok(Char(b.to_ascii_lowercase() as char)),
+            _ => Err(ParseAsciiError::UnknownKey),
+        };
+    }
+    let mut i = 0;
+    while i < KEY_NAMES.len() {
+        let (name, code) = KEY_NAMES[i];
+        if name.len() == len && starts_with_at(bytes, start, name.as_bytes()) {
+            return Ok(code);
+        }
+        i += 1;
+    }
+    if len > 2 && starts_with_at(bytes, start, b"0x") {
+        let mut value: u32 = 0;
+        let mut i = start + 2;
+        while i < bytes.len() {
+            let digit = match bytes[i].to_ascii_lowercase() {
+                b @ b'0'..=b'9' => b - b'0',
+                b @ b'a'..=b'f' => b - b'a' + 10,
+                _ => return Err(ParseAsciiError::UnknownKey),
+            };
+            if value > 0xff {
+                return Err(ParseAsciiError::NotAscii);
+            }
+            value = value * 16 + digit as u32;
+            i += 1;
+        }
+        if value > 0x7f {
+            return Err(ParseAsciiError::NotAscii);
+        }
+        let b = value as u8;
+        if b.is_ascii_graphic() || b == b' ' {
+            return Ok(Char(b as char));
+        }
+    }
+    Err(ParseAsciiError::UnknownKey)
+}
+
+#[test]
+fn check_ascii_parsing_agrees_with_parse() {
+    for raw in [
+        "a", "A", "shift-a", "Ctrl-Alt-Shift-X", "ctrl-'", "alt--", "-", "shift-tab",
+        "backtab", "ctrl-backtab", "space", "shift-space", "enter", "F12", "ctrl-pgdn",
+        "alt-kp1", "shift-kpplus", "0x22", "ctrl-0x41", "shift-0x61", "0x2D", "unknown",
+        "ctrl-1", "shift-1", "\r", "shift-!", "ctrl-shift-?", "super-s", "Control-c",
+        "command-shift-z", "option-x", "windows-e", "primary-s", "secondary-alt-x",
+    ] {
+        assert_eq!(parse_ascii(raw).ok(), parse(raw).ok(), "{:?}", raw);
+    }
+    for (name, _) in KEY_NAMES {
+        assert_eq!(parse_ascii(name).unwrap(), parse(name).unwrap(), "{:?}", name);
+    }
+    for (prefix, _) in MODIFIER_NAMES.iter().chain(MODIFIER_SYNONYMS) {
+        let raw = format!("{}a", prefix);
+        assert_eq!(parse_ascii(&raw).unwrap(), parse(&raw).unwrap(), "{:?}", raw);
+    }
+    assert_eq!(parse_ascii(""), Err(ParseAsciiError::UnknownKey));
+    assert_eq!(parse_ascii("ctrl-"), Err(ParseAsciiError::UnknownKey));
+    assert_eq!(parse_ascii("ctrl-nothing"), Err(ParseAsciiError::UnknownKey));
+    assert_eq!(parse_ascii("0x1d"), Err(ParseAsciiError::UnknownKey));
+    assert_eq!(parse_ascii("0xe9"), Err(ParseAsciiError::NotAscii));
+    assert_eq!(parse_ascii("0x100000041"), Err(ParseAsciiError::NotAscii));
+    assert_eq!(parse_ascii("a-b"), Err(ParseAsciiError::MultipleCodes));
+}
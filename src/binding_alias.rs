@@ -0,0 +1,240 @@
+use {
+    crate::*,
+    std::{
+        fmt,
+        str::FromStr,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+/// The value of a binding in a configuration: either an action, or
+/// an alias, written `@keys`, meaning "same as the binding of these keys".
+///
+/// A value starting with `@@` is an action starting with `@`.
+///
+/// ```
+/// use crokey::*;
+/// # #[cfg(feature = "serde")] {
+/// let map: KeyBindingMap<BindingValue<String>> = deser_hjson::from_str(r#"
+/// {
+///     enter: validate
+///     ctrl-j: @enter
+///     ctrl-m: @ctrl-j
+///     ctrl-a: @@home
+/// }
+/// "#).unwrap();
+/// let map = map.resolve_aliases().unwrap();
+/// assert_eq!(map.get(key!(ctrl-m)).unwrap(), "validate");
+/// assert_eq!(map.get(key!(ctrl-a)).unwrap(), "@home");
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingValue<A> {
+    Action(A),
+    Alias(KeySequence),
+}
+
+impl<A: FromStr> FromStr for BindingValue<A> {
+    type Err = BindingValueError<A::Err>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('@') {
+            Some(rest) if !rest.starts_with('@') => rest
+                .parse()
+                .map(Self::Alias)
+                .map_err(BindingValueError::Alias),
+            Some(rest) => rest
+                .parse()
+                .map(Self::Action)
+                .map_err(BindingValueError::Action),
+            None => s
+                .parse()
+                .map(Self::Action)
+                .map_err(BindingValueError::Action),
+        }
+    }
+}
+
+impl<A: fmt::Display> fmt::Display for BindingValue<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Action(action) => {
+                let action = action.to_string();
+                if action.starts_with('@') {
+                    write!(f, "@")?;
+                }
+                write!(f, "{}", action)
+            }
+            Self::Alias(keys) => write!(f, "@{}", keys),
+        }
+    }
+}
+
+/// Error returned when a binding value can't be parsed
+#[derive(Debug)]
+pub enum BindingValueError<E> {
+    Action(E),
+    Alias(ParseKeyError),
+}
+
+impl<E: fmt::Display> fmt::Display for BindingValueError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Action(e) => write!(f, "invalid action: {}", e),
+            Self::Alias(e) => write!(f, "invalid alias: {}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for BindingValueError<E> {}
+
+/// Error returned when the aliases of a binding map can't be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasError {
+    /// The binding with the given keys is an alias of unbound keys
+    UnknownAlias {
+        keys: BindingKeys,
+        target: KeySequence,
+    },
+    /// The aliases refer to each other in a loop
+    AliasCycle(Vec<KeySequence>),
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownAlias { keys, target } => {
+                write!(f, "{} is an alias of {} which isn't bound", keys, target)
+            }
+            Self::AliasCycle(chain) => {
+                write!(f, "key aliases loop:")?;
+                for (i, keys) in chain.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { " -> " } else { " " }, keys)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
+
+impl<A: Clone> KeyBindingMap<BindingValue<A>> {
+    /// Replace every alias with the action of the binding it refers to,
+    /// following chained aliases.
+    ///
+    /// The values of the [pattern](KeyBindingMap::insert_pattern) bindings
+    /// may be aliases too.
+    pub fn resolve_aliases(&self) -> Result<KeyBindingMap<A>, AliasError> {
+        let mut resolved = KeyBindingMap::new();
+        for (keys, value) in self.iter() {
+            let action = self.resolve_value(BindingKeys::Sequence(keys.clone()), value)?;
+            let _ = match self.meta(keys) {
+                Some(meta) => resolved.insert_with_meta(keys.clone(), action.clone(), meta.clone()),
+                None => resolved.insert(keys.clone(), action.clone()),
+            };
+            if !self.is_enabled(keys) {
+                resolved.set_enabled(keys, false);
+            }
+        }
+        for (pattern, value) in self.patterns() {
+            let action = self.resolve_value(BindingKeys::Pattern(*pattern), value)?;
+            let _ = resolved.insert_pattern(*pattern, action.clone());
+        }
+        Ok(resolved)
+    }
+    /// Return the action of the value bound to the keys, following aliases
+    fn resolve_value<'m>(
+        &'m self,
+        keys: BindingKeys,
+        mut value: &'m BindingValue<A>,
+    ) -> Result<&'m A, AliasError> {
+        // patterns can't be alias targets, so they're not part of cycles
+        let mut chain = match &keys {
+            BindingKeys::Sequence(keys) => vec![keys.clone()],
+            BindingKeys::Pattern(_) => Vec::new(),
+        };
+        loop {
+            let target = match value {
+                BindingValue::Action(action) => return Ok(action),
+                BindingValue::Alias(target) => target,
+            };
+            if chain.contains(target) {
+                chain.push(target.clone());
+                return Err(AliasError::AliasCycle(chain));
+            }
+            value = self.get_sequence_ignoring_state(target).ok_or_else(|| AliasError::UnknownAlias {
+                keys: chain.last().cloned().map_or(keys.clone(), BindingKeys::Sequence),
+                target: target.clone(),
+            })?;
+            chain.push(target.clone());
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, A> Deserialize<'de> for BindingValue<A>
+where
+    A: FromStr,
+    A::Err: fmt::Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<A: fmt::Display> Serialize for BindingValue<A> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[test]
+fn check_alias_errors() {
+    let mut map: KeyBindingMap<BindingValue<String>> = KeyBindingMap::new();
+    map.insert("a", "@b".parse().unwrap()).unwrap();
+    map.insert("b", "@a".parse().unwrap()).unwrap();
+    assert!(matches!(map.resolve_aliases(), Err(AliasError::AliasCycle(_))));
+    let mut map: KeyBindingMap<BindingValue<String>> = KeyBindingMap::new();
+    map.insert("a", "@b".parse().unwrap()).unwrap();
+    assert_eq!(
+        map.resolve_aliases().unwrap_err(),
+        AliasError::UnknownAlias { keys: BindingKeys::Sequence(key!(a).into()), target: key!(b).into() },
+    );
+}
+
+#[test]
+fn check_alias_patterns() {
+    let mut map: KeyBindingMap<BindingValue<String>> = KeyBindingMap::new();
+    map.insert("enter", "@ctrl-m".parse().unwrap()).unwrap();
+    map.insert("ctrl-m", "validate".parse().unwrap()).unwrap();
+    map.insert_pattern("*-enter", "@enter".parse().unwrap()).unwrap();
+    map.insert_pattern("none-x", "cut".parse().unwrap()).unwrap();
+    let resolved = map.resolve_aliases().unwrap();
+    assert_eq!(resolved.len(), 4);
+    assert_eq!(resolved.get_pattern(key!(alt-enter)).unwrap(), "validate");
+    assert_eq!(resolved.get_pattern(key!(x)).unwrap(), "cut");
+    map.insert_pattern("*-tab", "@f1".parse().unwrap()).unwrap();
+    assert_eq!(
+        map.resolve_aliases().unwrap_err(),
+        AliasError::UnknownAlias {
+            keys: BindingKeys::Pattern("*-tab".parse().unwrap()),
+            target: key!(f1).into(),
+        },
+    );
+}
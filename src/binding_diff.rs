@@ -0,0 +1,184 @@
+use crate::*;
+
+/// A binding which differs between two binding maps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingDiffEntry<A> {
+    pub keys: BindingKeys,
+    /// the keys, formatted with the standard format
+    pub formatted_keys: String,
+    /// the action in the old map, if any
+    pub old: Option<A>,
+    /// the action in the new map, if any
+    pub new: Option<A>,
+}
+
+impl<A> BindingDiffEntry<A> {
+    fn new<K: Into<BindingKeys>>(keys: K, old: Option<A>, new: Option<A>) -> Self {
+        let keys = keys.into();
+        Self {
+            formatted_keys: keys.to_string(),
+            keys,
+            old,
+            new,
+        }
+    }
+}
+
+/// The differences between two binding maps, each list sorted
+/// by formatted keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingsDiff<A> {
+    /// bindings only in the new map
+    pub added: Vec<BindingDiffEntry<A>>,
+    /// bindings only in the old map
+    pub removed: Vec<BindingDiffEntry<A>>,
+    /// keys bound in both maps, to different actions
+    pub rebound: Vec<BindingDiffEntry<A>>,
+}
+
+impl<A> BindingsDiff<A> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.rebound.is_empty()
+    }
+}
+
+/// Compare two binding maps, for example the default bindings of an
+/// application and the ones of the user.
+///
+/// The [pattern](KeyBindingMap::insert_pattern) bindings are compared too.
+///
+/// ```
+/// use crokey::*;
+/// let mut defaults = KeyBindingMap::new();
+/// defaults.insert("ctrl-s", "save").unwrap();
+/// defaults.insert("ctrl-q", "quit").unwrap();
+/// let mut user = defaults.clone();
+/// user.insert("ctrl-s", "save_all").unwrap();
+/// user.insert("ctrl-x ctrl-c", "quit").unwrap();
+/// let diff = diff_bindings(&defaults, &user);
+/// assert_eq!(diff.added[0].formatted_keys, "Ctrl-x Ctrl-c");
+/// assert!(diff.removed.is_empty());
+/// assert_eq!(diff.rebound[0].old, Some("save"));
+/// assert_eq!(diff.rebound[0].new, Some("save_all"));
+/// user.insert_pattern("*-enter", "confirm").unwrap();
+/// assert_eq!(diff_bindings(&defaults, &user).added[0].formatted_keys, "*-Enter");
+/// ```
+pub fn diff_bindings<A: Clone + PartialEq>(
+    old: &KeyBindingMap<A>,
+    new: &KeyBindingMap<A>,
+) -> BindingsDiff<A> {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut rebound = Vec::new();
+    let new_bindings = new
+        .iter()
+        .map(|(keys, action)| (BindingKeys::from(keys.clone()), action, old.get_sequence(keys)))
+        .chain(
+            new.patterns()
+                .map(|(pattern, action)| (BindingKeys::from(*pattern), action, old.pattern_action(*pattern))),
+        );
+    for (keys, new_action, old_action) in new_bindings {
+        match old_action {
+            None => {
+                added.push(BindingDiffEntry::new(keys, None, Some(new_action.clone())));
+            }
+            Some(old_action) if old_action != new_action => {
+                rebound.push(BindingDiffEntry::new(
+                    keys,
+                    Some(old_action.clone()),
+                    Some(new_action.clone()),
+                ));
+            }
+            _ => {}
+        }
+    }
+    for (keys, old_action) in old.iter() {
+        if new.get_sequence(keys).is_none() {
+            removed.push(BindingDiffEntry::new(keys.clone(), Some(old_action.clone()), None));
+        }
+    }
+    for (pattern, old_action) in old.patterns() {
+        if new.pattern_action(*pattern).is_none() {
+            removed.push(BindingDiffEntry::new(*pattern, Some(old_action.clone()), None));
+        }
+    }
+    for list in [&mut added, &mut removed, &mut rebound] {
+        list.sort_by(|a, b| a.formatted_keys.cmp(&b.formatted_keys));
+    }
+    BindingsDiff { added, removed, rebound }
+}
+
+/// Apply user bindings over default ones, returning the merged map
+/// and the default bindings which were changed by the user, sorted
+/// by formatted keys.
+///
+/// The user's [pattern](KeyBindingMap::insert_pattern) bindings replace
+/// the default ones with the same pattern, and are added after the others.
+///
+/// ```
+/// use crokey::*;
+/// let mut defaults = KeyBindingMap::new();
+/// defaults.insert("ctrl-s", "save").unwrap();
+/// defaults.insert("ctrl-q", "quit").unwrap();
+/// let mut user = KeyBindingMap::new();
+/// user.insert("ctrl-s", "save_all").unwrap();
+/// user.insert("ctrl-o", "open").unwrap();
+/// let (merged, overridden) = merge_with_overrides(&defaults, &user);
+/// assert_eq!(merged.len(), 3);
+/// assert_eq!(merged.get(key!(ctrl-s)), Some(&"save_all"));
+/// assert_eq!(overridden.len(), 1);
+/// assert_eq!(overridden[0].old, Some("save"));
+/// ```
+pub fn merge_with_overrides<A: Clone + PartialEq>(
+    defaults: &KeyBindingMap<A>,
+    overrides: &KeyBindingMap<A>,
+) -> (KeyBindingMap<A>, Vec<BindingDiffEntry<A>>) {
+    let mut merged = defaults.clone();
+    let mut overridden = Vec::new();
+    for (keys, action) in overrides.iter() {
+        if let Ok(Some(default_action)) = merged.insert(keys.clone(), action.clone()) {
+            if default_action != *action {
+                overridden.push(BindingDiffEntry::new(
+                    keys.clone(),
+                    Some(default_action),
+                    Some(action.clone()),
+                ));
+            }
+        }
+    }
+    for (pattern, action) in overrides.patterns() {
+        if let Ok(Some(default_action)) = merged.insert_pattern(*pattern, action.clone()) {
+            if default_action != *action {
+                overridden.push(BindingDiffEntry::new(*pattern, Some(default_action), Some(action.clone())));
+            }
+        }
+    }
+    overridden.sort_by(|a, b| a.formatted_keys.cmp(&b.formatted_keys));
+    (merged, overridden)
+}
+
+#[test]
+fn check_pattern_diff_and_merge() {
+    let mut defaults = KeyBindingMap::new();
+    defaults.insert("ctrl-q", "quit").unwrap();
+    defaults.insert_pattern("*-enter", "confirm").unwrap();
+    defaults.insert_pattern("none-x", "cut").unwrap();
+    let mut user = KeyBindingMap::new();
+    user.insert("ctrl-q", "quit").unwrap();
+    user.insert_pattern("*-enter", "validate").unwrap();
+    user.insert_pattern("*-tab", "complete").unwrap();
+    let diff = diff_bindings(&defaults, &user);
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].keys, BindingKeys::Pattern("*-tab".parse().unwrap()));
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].formatted_keys, "none-x");
+    assert_eq!(diff.rebound.len(), 1);
+    assert_eq!(diff.rebound[0].new, Some("validate"));
+    let (merged, overridden) = merge_with_overrides(&defaults, &user);
+    assert_eq!(merged.len(), 4);
+    assert_eq!(merged.get_pattern(key!(alt-enter)), Some(&"validate"));
+    assert_eq!(merged.get_pattern(key!(x)), Some(&"cut"));
+    assert_eq!(merged.get_pattern(key!(tab)), Some(&"complete"));
+    assert_eq!(overridden.len(), 1);
+    assert_eq!(overridden[0].formatted_keys, "*-Enter");
+}
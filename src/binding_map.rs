@@ -0,0 +1,624 @@
+use {
+    crate::*,
+    std::{
+        collections::{
+            hash_map,
+            HashMap,
+            HashSet,
+        },
+        fmt::{
+            self,
+            Write,
+        },
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{
+    de,
+    ser::SerializeMap,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+/// A map from keys to actions.
+///
+/// The keys are key sequences. Most bindings are made of a single
+/// key combination, which is a sequence of length one.
+///
+/// Keys are stored and looked for in [canonical](KeyCombination::canonical)
+/// form, so that `A`, `shift-a` and `shift-A` are the same key.
+///
+/// ```
+/// use crokey::*;
+/// #[derive(Debug, PartialEq)]
+/// enum Action { Save, Top }
+/// let mut map = KeyBindingMap::new();
+/// map.insert(key!(ctrl-s), Action::Save).unwrap();
+/// map.insert("g g", Action::Top).unwrap();
+/// assert!(map.insert("ctrl-nothing", Action::Top).is_err());
+/// assert_eq!(map.get(key!(ctrl-s)), Some(&Action::Save));
+/// assert_eq!(map.get_sequence(&[key!(g), key!(g)]), Some(&Action::Top));
+/// ```
+///
+/// Bindings may be [disabled](KeyBindingMap::set_enabled): they're then
+/// ignored by lookups but still listed, e.g. greyed out in help screens.
+#[derive(Debug, Clone)]
+pub struct KeyBindingMap<A> {
+    bindings: HashMap<KeySequence, A>,
+    meta: HashMap<KeySequence, BindingMeta>,
+    disabled: HashSet<KeySequence>,
+    /// the pattern bindings, in insertion order
+    patterns: Vec<(KeyPattern, A)>,
+}
+
+/// Information about a binding, used to generate help screens
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BindingMeta {
+    /// Name of the group of bindings in help screens
+    pub category: Option<String>,
+    /// What the binding does, shown instead of the action in help screens
+    pub description: Option<String>,
+    /// Whether the binding should be left out of help screens
+    pub hidden: bool,
+}
+
+impl<A> Default for KeyBindingMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            meta: HashMap::new(),
+            disabled: HashSet::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl<A> KeyBindingMap<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Bind an action to a key, which may be given as anything convertible
+    /// into a key sequence: a key combination, a key sequence, or a string
+    /// to parse.
+    ///
+    /// Return the action previously bound to the same key, if any.
+    pub fn insert<K>(&mut self, keys: K, action: A) -> Result<Option<A>, ParseKeyError>
+    where
+        K: TryInto<KeySequence>,
+        ParseKeyError: From<K::Error>,
+    {
+        let keys = keys.try_into()?.canonical();
+        self.meta.remove(&keys);
+        self.disabled.remove(&keys);
+        Ok(self.bindings.insert(keys, action))
+    }
+    /// Bind an action to a key, with metadata describing the binding.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert_with_meta("ctrl-s", "save", BindingMeta {
+    ///     category: Some("File".to_string()),
+    ///     description: Some("Save the current file".to_string()),
+    ///     hidden: false,
+    /// }).unwrap();
+    /// assert_eq!(map.meta(&[key!(ctrl-s)]).unwrap().category.as_deref(), Some("File"));
+    /// ```
+    pub fn insert_with_meta<K>(
+        &mut self,
+        keys: K,
+        action: A,
+        meta: BindingMeta,
+    ) -> Result<Option<A>, ParseKeyError>
+    where
+        K: TryInto<KeySequence>,
+        ParseKeyError: From<K::Error>,
+    {
+        let keys = keys.try_into()?.canonical();
+        self.meta.insert(keys.clone(), meta);
+        self.disabled.remove(&keys);
+        Ok(self.bindings.insert(keys, action))
+    }
+    /// Bind an action to a [KeyPattern], e.g. `*-enter`, which may be
+    /// given as a string to parse.
+    ///
+    /// Patterns are separate from the other bindings: they're found by
+    /// [KeyBindingMap::get_pattern] and by [KeyBindingMap::resolve_with_fallbacks],
+    /// in the [MatchMode::Pattern] mode (and in the [MatchMode::Exact] one for
+    /// the `none-` patterns).
+    ///
+    /// Return the action previously bound to the same pattern, if any.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-enter", "submit").unwrap();
+    /// map.insert_pattern("*-enter", "confirm").unwrap();
+    /// assert_eq!(map.get_pattern(key!(alt-shift-enter)), Some(&"confirm"));
+    /// assert_eq!(map.get(key!(alt-enter)), None);
+    /// assert_eq!(
+    ///     map.resolve_with_fallbacks(key!(ctrl-enter), &MatchMode::DEFAULT_FALLBACKS),
+    ///     Some((&"submit", MatchMode::Exact)),
+    /// );
+    /// assert_eq!(
+    ///     map.resolve_with_fallbacks(key!(alt-enter), &MatchMode::DEFAULT_FALLBACKS),
+    ///     Some((&"confirm", MatchMode::Pattern)),
+    /// );
+    /// ```
+    pub fn insert_pattern<P>(&mut self, pattern: P, action: A) -> Result<Option<A>, ParseKeyError>
+    where
+        P: TryInto<KeyPattern>,
+        ParseKeyError: From<P::Error>,
+    {
+        let pattern = pattern.try_into()?;
+        match self.patterns.iter_mut().find(|(p, _)| *p == pattern) {
+            Some((_, previous)) => Ok(Some(std::mem::replace(previous, action))),
+            None => {
+                self.patterns.push((pattern, action));
+                Ok(None)
+            }
+        }
+    }
+    /// Return the action bound to the first inserted pattern matching
+    /// the key combination
+    pub fn get_pattern(&self, key: KeyCombination) -> Option<&A> {
+        self.patterns
+            .iter()
+            .find(|(pattern, _)| pattern.matches(key))
+            .map(|(_, action)| action)
+    }
+    /// Return the action bound to this very pattern, if any
+    pub fn pattern_action(&self, pattern: KeyPattern) -> Option<&A> {
+        self.patterns
+            .iter()
+            .find(|(p, _)| *p == pattern)
+            .map(|(_, action)| action)
+    }
+    /// Remove a pattern binding, returning its action
+    pub fn remove_pattern(&mut self, pattern: KeyPattern) -> Option<A> {
+        let index = self.patterns.iter().position(|(p, _)| *p == pattern)?;
+        Some(self.patterns.remove(index).1)
+    }
+    /// Iterate over the pattern bindings, in insertion order
+    pub fn patterns(&self) -> impl Iterator<Item = (&KeyPattern, &A)> {
+        self.patterns.iter().map(|(pattern, action)| (pattern, action))
+    }
+    /// Return the metadata of a binding, if any
+    pub fn meta(&self, keys: &[KeyCombination]) -> Option<&BindingMeta> {
+        with_canonical_keys(keys, |keys| self.meta.get(keys))
+    }
+    /// Enable or disable a binding, returning false when there's no
+    /// binding for those keys.
+    ///
+    /// A disabled binding isn't returned by lookups but is still iterated
+    /// over and shown in help screens. Bindings are enabled when inserted.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-s", "save").unwrap();
+    /// assert!(map.set_enabled(&[key!(ctrl-s)], false));
+    /// assert_eq!(map.get(key!(ctrl-s)), None);
+    /// assert!(!map.is_enabled(&[key!(ctrl-s)]));
+    /// assert!(map.set_enabled(&[key!(ctrl-s)], true));
+    /// assert_eq!(map.get(key!(ctrl-s)), Some(&"save"));
+    /// assert!(!map.set_enabled(&[key!(ctrl-q)], false));
+    /// ```
+    pub fn set_enabled(&mut self, keys: &[KeyCombination], enabled: bool) -> bool {
+        let keys: KeySequence = canonical_keys(keys).into();
+        if !self.bindings.contains_key(&keys) {
+            return false;
+        }
+        if enabled {
+            self.disabled.remove(&keys);
+        } else {
+            self.disabled.insert(keys);
+        }
+        true
+    }
+    /// Tell whether there's an enabled binding for those keys
+    pub fn is_enabled(&self, keys: &[KeyCombination]) -> bool {
+        with_canonical_keys(keys, |keys| {
+            self.bindings.contains_key(keys) && !self.disabled.contains(keys)
+        })
+    }
+    /// Tell whether the binding of keys already in canonical form, e.g.
+    /// ones iterated over, is disabled
+    pub(crate) fn is_disabled_canonical(&self, keys: &[KeyCombination]) -> bool {
+        self.disabled.contains(keys)
+    }
+    /// Enable or disable all the bindings of a [category](BindingMeta::category),
+    /// returning the number of bindings in this category
+    pub fn set_category_enabled(&mut self, category: &str, enabled: bool) -> usize {
+        let keys: Vec<KeySequence> = self
+            .meta
+            .iter()
+            .filter(|(_, meta)| meta.category.as_deref() == Some(category))
+            .map(|(keys, _)| keys.clone())
+            .collect();
+        for keys in &keys {
+            self.set_enabled(keys, enabled);
+        }
+        keys.len()
+    }
+    /// Disable all the bindings of a category, e.g. the edition bindings
+    /// in a read-only view.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// let edit = BindingMeta {
+    ///     category: Some("Edit".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// map.insert_with_meta("ctrl-x", "cut", edit.clone()).unwrap();
+    /// map.insert_with_meta("ctrl-v", "paste", edit).unwrap();
+    /// map.insert("ctrl-c", "copy").unwrap();
+    /// assert_eq!(map.disable_category("Edit"), 2);
+    /// assert_eq!(map.get(key!(ctrl-v)), None);
+    /// assert_eq!(map.get(key!(ctrl-c)), Some(&"copy"));
+    /// map.enable_category("Edit");
+    /// assert_eq!(map.get(key!(ctrl-v)), Some(&"paste"));
+    /// ```
+    pub fn disable_category(&mut self, category: &str) -> usize {
+        self.set_category_enabled(category, false)
+    }
+    /// Enable all the bindings of a category
+    pub fn enable_category(&mut self, category: &str) -> usize {
+        self.set_category_enabled(category, true)
+    }
+    /// Return the action bound to a single key combination, if enabled
+    pub fn get(&self, key: KeyCombination) -> Option<&A> {
+        self.get_sequence(&[key])
+    }
+    /// Return the action bound to a key sequence, if enabled
+    pub fn get_sequence(&self, keys: &[KeyCombination]) -> Option<&A> {
+        with_canonical_keys(keys, |keys| {
+            if self.disabled.contains(keys) {
+                return None;
+            }
+            self.bindings.get(keys)
+        })
+    }
+    /// Return the action bound to a key sequence, even when disabled
+    pub(crate) fn get_sequence_ignoring_state(&self, keys: &[KeyCombination]) -> Option<&A> {
+        with_canonical_keys(keys, |keys| self.bindings.get(keys))
+    }
+    /// Remove a binding, returning its action
+    pub fn remove(&mut self, keys: &[KeyCombination]) -> Option<A> {
+        let keys = canonical_keys(keys);
+        self.meta.remove(keys.as_slice());
+        self.disabled.remove(keys.as_slice());
+        self.bindings.remove(keys.as_slice())
+    }
+    /// Return the number of bindings, the pattern ones included
+    pub fn len(&self) -> usize {
+        self.bindings.len() + self.patterns.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty() && self.patterns.is_empty()
+    }
+    /// Iterate over the bindings, in no specific order, including the
+    /// disabled ones
+    pub fn iter(&self) -> hash_map::Iter<'_, KeySequence, A> {
+        self.bindings.iter()
+    }
+    /// Iterate over the bindings, including the disabled ones, sorted by
+    /// category, the bindings without category coming last, then by keys
+    /// in the [KeyCombination] order (so `F2` comes before `F10`).
+    ///
+    /// This is the order in which the map is serialized, so that saving
+    /// the same bindings always gives the same file.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// let view = BindingMeta {
+    ///     category: Some("View".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// map.insert("ctrl-q", "quit").unwrap();
+    /// map.insert_with_meta("ctrl-z", "zoom", view.clone()).unwrap();
+    /// map.insert("ctrl-c", "copy").unwrap();
+    /// map.insert_with_meta("ctrl-b", "bar", view).unwrap();
+    /// let actions: Vec<&str> = map.iter_sorted().map(|(_, action)| *action).collect();
+    /// assert_eq!(actions, ["bar", "zoom", "copy", "quit"]);
+    /// ```
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&KeySequence, &A)> {
+        let mut entries: Vec<(&KeySequence, &A)> = self.bindings.iter().collect();
+        entries.sort_by_cached_key(|(keys, _)| {
+            let category = self.meta.get(*keys).and_then(|meta| meta.category.clone());
+            (category.is_none(), category, *keys)
+        });
+        entries.into_iter()
+    }
+}
+
+impl<A: fmt::Display> KeyBindingMap<A> {
+    /// Write all the bindings, in the [sorted](KeyBindingMap::iter_sorted)
+    /// order, as aligned columns of keys and actions, e.g. for a snapshot
+    /// test of the keymap of an application.
+    ///
+    /// Hidden bindings are included, disabled ones are marked. The
+    /// [pattern](KeyBindingMap::insert_pattern) bindings come last.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-x ctrl-c", "quit").unwrap();
+    /// map.insert("f1", "help").unwrap();
+    /// map.insert("ctrl-s", "save").unwrap();
+    /// map.set_enabled(&[key!(f1)], false);
+    /// assert_eq!(
+    ///     map.to_table_string(&KeyCombinationFormat::default().with_control("^")),
+    ///     "F1     help (disabled)\n^s     save\n^x ^c  quit\n",
+    /// );
+    /// assert_eq!(
+    ///     map.to_string(),
+    ///     "F1             help (disabled)\nCtrl-s         save\nCtrl-x Ctrl-c  quit\n",
+    /// );
+    /// map.insert_pattern("*-enter", "confirm").unwrap();
+    /// assert!(map.to_string().ends_with("*-Enter        confirm\n"));
+    /// ```
+    pub fn to_table_string(&self, format: &KeyCombinationFormat) -> String {
+        let rows: Vec<(String, bool, &A)> = self
+            .iter_sorted()
+            .map(|(keys, action)| (format.format_sequence(keys), self.is_enabled(keys), action))
+            .chain(
+                self.patterns()
+                    .map(|(pattern, action)| (pattern.to_string_with(format), true, action)),
+            )
+            .collect();
+        let width = rows
+            .iter()
+            .map(|(formatted, _, _)| formatted.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut s = String::new();
+        for (formatted, enabled, action) in rows {
+            let padding = width - formatted.chars().count();
+            // writing to a String can't fail
+            let _ = write!(s, "{}{:padding$}  {}", formatted, "", action);
+            if !enabled {
+                s.push_str(" (disabled)");
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// Display the bindings as a table, with the default format
+/// (see [KeyBindingMap::to_table_string])
+impl<A: fmt::Display> fmt::Display for KeyBindingMap<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_table_string(&KeyCombinationFormat::default()))
+    }
+}
+
+fn canonical_keys(keys: &[KeyCombination]) -> Vec<KeyCombination> {
+    keys.iter().map(|key| key.canonical()).collect()
+}
+
+/// Call `f` with the canonical form of the keys, without allocating
+/// for the usual short sequences
+fn with_canonical_keys<R>(keys: &[KeyCombination], f: impl FnOnce(&[KeyCombination]) -> R) -> R {
+    const STACK_LEN: usize = 4;
+    match keys.first() {
+        Some(&first) if keys.len() <= STACK_LEN => {
+            let mut canonical = [first; STACK_LEN];
+            for (canonical, key) in canonical.iter_mut().zip(keys) {
+                *canonical = key.canonical();
+            }
+            f(&canonical[..keys.len()])
+        }
+        Some(_) => f(&canonical_keys(keys)),
+        None => f(keys),
+    }
+}
+
+/// The keys of a binding: a key sequence, or a [KeyPattern]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BindingKeys {
+    Pattern(KeyPattern),
+    Sequence(KeySequence),
+}
+
+impl From<KeySequence> for BindingKeys {
+    fn from(keys: KeySequence) -> Self {
+        Self::Sequence(keys)
+    }
+}
+
+impl From<KeyPattern> for BindingKeys {
+    fn from(pattern: KeyPattern) -> Self {
+        Self::Pattern(pattern)
+    }
+}
+
+impl fmt::Display for BindingKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pattern(pattern) => write!(f, "{}", pattern),
+            Self::Sequence(keys) => write!(f, "{}", keys),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for BindingKeys {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        // `*-enter` is a pattern, not the simultaneous `*` and enter keys
+        if let Ok(pattern) = raw.parse() {
+            return Ok(Self::Pattern(pattern));
+        }
+        raw.parse().map(Self::Sequence).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct KeyBindingMapVisitor<A> {
+    _action: std::marker::PhantomData<A>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, A: Deserialize<'de>> de::Visitor<'de> for KeyBindingMapVisitor<A> {
+    type Value = KeyBindingMap<A>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of key bindings")
+    }
+    fn visit_map<M: de::MapAccess<'de>>(self, mut access: M) -> Result<Self::Value, M::Error> {
+        let mut map = KeyBindingMap::default();
+        while let Some((keys, action)) = access.next_entry::<BindingKeys, A>()? {
+            match keys {
+                BindingKeys::Pattern(pattern) => {
+                    if map.patterns.iter().any(|(p, _)| *p == pattern) {
+                        return Err(de::Error::custom(format!("{} is bound twice", pattern)));
+                    }
+                    map.patterns.push((pattern, action));
+                }
+                BindingKeys::Sequence(keys) => {
+                    // e.g. `A` and `shift-a`, which are the same key
+                    let keys = keys.canonical();
+                    if map.bindings.contains_key(&keys) {
+                        return Err(de::Error::custom(format!("{} is bound twice", keys)));
+                    }
+                    map.bindings.insert(keys, action);
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Reading a map fails when two entries are bound to the same keys
+#[cfg(feature = "serde")]
+impl<'de, A: Deserialize<'de>> Deserialize<'de> for KeyBindingMap<A> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(KeyBindingMapVisitor {
+            _action: std::marker::PhantomData,
+        })
+    }
+}
+
+/// The bindings are written in the [sorted](KeyBindingMap::iter_sorted)
+/// order, then the patterns in insertion order
+#[cfg(feature = "serde")]
+impl<A: Serialize> Serialize for KeyBindingMap<A> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.bindings.len() + self.patterns.len()))?;
+        for (keys, action) in self.iter_sorted() {
+            map.serialize_entry(keys, action)?;
+        }
+        // in insertion order, which decides which pattern wins
+        for (pattern, action) in self.patterns() {
+            map.serialize_entry(pattern, action)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_binding_map_deserialization() {
+    let map: KeyBindingMap<String> = deser_hjson::from_str(r#"
+        {
+            ctrl-s: save
+            "ctrl-x ctrl-c": quit
+            "*-enter": confirm
+            "none-q": quit
+            "alt-*": star
+        }
+    "#).unwrap();
+    assert_eq!(map.len(), 5); // patterns included
+    assert_eq!(map.get(key!(ctrl-s)).unwrap(), "save");
+    assert_eq!(map.get_sequence(&[key!(ctrl-x), key!(ctrl-c)]).unwrap(), "quit");
+    assert_eq!(map.get(key!(alt-'*')).unwrap(), "star");
+    assert_eq!(map.get(key!(ctrl-enter)), None);
+    assert_eq!(map.get_pattern(key!(ctrl-enter)).unwrap(), "confirm");
+    let json = serde_json::to_string(&map).unwrap();
+    let read: KeyBindingMap<String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(map.get_pattern(key!(q)).unwrap(), "quit");
+    assert_eq!(map.get_pattern(key!(alt-q)), None);
+    assert_eq!(read.patterns().count(), 2);
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    for colliding in [
+        r#"{"shift-a": "up", "Shift-A": "top"}"#,
+        r#"{"ctrl-x ctrl-s": "save", "ctrl-x 0x13": "save"}"#,
+        r#"{"*-enter": "confirm", "any-Enter": "validate"}"#,
+    ] {
+        assert!(serde_json::from_str::<KeyBindingMap<String>>(colliding).is_err(), "{}", colliding);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_binding_map_serialization_is_stable() {
+    let keys = ["ctrl-x ctrl-c", "alt-'", "f1", "ctrl-s", "g g", "shift-a", "ctrl-q"];
+    let mut map = KeyBindingMap::new();
+    let mut reversed = KeyBindingMap::new();
+    for (i, keys) in keys.iter().enumerate() {
+        map.insert(*keys, i).unwrap();
+    }
+    for (i, keys) in keys.iter().enumerate().rev() {
+        reversed.insert(*keys, i).unwrap();
+    }
+    let bytes = bincode::serialize(&map).unwrap();
+    assert_eq!(bytes, bincode::serialize(&reversed).unwrap());
+    let read: std::collections::HashMap<KeySequence, usize> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(read.len(), keys.len());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_pattern_order_is_kept() {
+    let mut map = KeyBindingMap::new();
+    map.insert_pattern("none-enter", "newline").unwrap();
+    map.insert_pattern("*-enter", "submit").unwrap();
+    assert_eq!(map.get_pattern(key!(enter)), Some(&"newline"));
+    let json = serde_json::to_string(&map).unwrap();
+    let read: KeyBindingMap<String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.get_pattern(key!(enter)).unwrap(), "newline");
+    assert_eq!(read.get_pattern(key!(alt-enter)).unwrap(), "submit");
+    let read: KeyBindingMap<String> = deser_hjson::from_str(r#"{
+        "*-enter": submit
+        "none-enter": newline
+    }"#).unwrap();
+    assert_eq!(read.get_pattern(key!(enter)).unwrap(), "submit");
+}
+
+#[test]
+fn check_binding_map_lookups_are_canonical() {
+    use crossterm::event::KeyCode;
+    let mut map = KeyBindingMap::new();
+    let upper = KeyCombination::from(KeyCode::Char('A'));
+    map.insert(upper, "up").unwrap();
+    assert_eq!(map.insert("shift-a", "top").unwrap(), Some("up"));
+    assert_eq!(map.get(upper), Some(&"top"));
+    map.insert("ctrl-x ctrl-s", "save").unwrap();
+    let ctrl_s = KeyCombination::from(KeyCode::Char('\u{13}'));
+    assert_eq!(map.get_sequence(&[key!(ctrl-x), ctrl_s]), Some(&"save"));
+}
+
+#[test]
+fn check_sorted_bindings_follow_key_order() {
+    let mut map = KeyBindingMap::new();
+    for keys in ["f10", "ctrl-a", "f2", "b", "f1"] {
+        map.insert(keys, keys).unwrap();
+    }
+    let actions: Vec<&str> = map.iter_sorted().map(|(_, action)| *action).collect();
+    assert_eq!(actions, ["f1", "f2", "f10", "b", "ctrl-a"]);
+}
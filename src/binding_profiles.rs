@@ -0,0 +1,176 @@
+use {
+    crate::*,
+    std::{
+        collections::HashMap,
+        fmt,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+/// A named keymap, which may inherit the bindings of another profile
+/// and override some of them
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "A: Deserialize<'de>")))]
+pub struct BindingProfile<A> {
+    /// name of the profile whose bindings are inherited
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inherit: Option<String>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub bindings: KeyBindingMap<A>,
+}
+
+impl<A> Default for BindingProfile<A> {
+    fn default() -> Self {
+        Self {
+            inherit: None,
+            bindings: KeyBindingMap::default(),
+        }
+    }
+}
+
+/// Error returned when a profile can't be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    /// No profile has this name
+    UnknownProfile(String),
+    /// The profiles inherit from each other in a loop
+    InheritanceCycle(Vec<String>),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownProfile(name) => write!(f, "unknown binding profile {:?}", name),
+            Self::InheritanceCycle(names) => {
+                write!(f, "binding profiles inherit in a loop: {}", names.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+/// Several named keymaps (e.g. `default`, `emacs`, `vim`) defined in one
+/// configuration, where profiles may inherit from other ones.
+///
+/// ```
+/// use crokey::*;
+/// # #[cfg(feature = "serde")] {
+/// let profiles: BindingProfiles<String> = deser_hjson::from_str(r#"
+/// {
+///     default: {
+///         ctrl-s: save
+///         ctrl-q: quit
+///     }
+///     vim: {
+///         inherit: default
+///         j: down
+///         ctrl-s: write
+///     }
+/// }
+/// "#).unwrap();
+/// let vim = profiles.resolve("vim").unwrap();
+/// assert_eq!(vim.len(), 3);
+/// assert_eq!(vim.get(key!(ctrl-s)).unwrap(), "write");
+/// assert_eq!(vim.get(key!(ctrl-q)).unwrap(), "quit");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "A: Deserialize<'de>")))]
+pub struct BindingProfiles<A> {
+    profiles: HashMap<String, BindingProfile<A>>,
+}
+
+impl<A> Default for BindingProfiles<A> {
+    fn default() -> Self {
+        Self {
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Clone> BindingProfiles<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn insert<S: Into<String>>(&mut self, name: S, profile: BindingProfile<A>) {
+        self.profiles.insert(name.into(), profile);
+    }
+    pub fn get(&self, name: &str) -> Option<&BindingProfile<A>> {
+        self.profiles.get(name)
+    }
+    /// Iterate over the names of the profiles, in no specific order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+    /// Build the complete bindings of a profile, applying its
+    /// bindings over the ones it inherits
+    pub fn resolve(&self, name: &str) -> Result<KeyBindingMap<A>, ProfileError> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = Some(name);
+        while let Some(name) = current {
+            if chain.contains(&name) {
+                let mut names: Vec<String> = chain.iter().map(|s| s.to_string()).collect();
+                names.push(name.to_string());
+                return Err(ProfileError::InheritanceCycle(names));
+            }
+            let profile = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| ProfileError::UnknownProfile(name.to_string()))?;
+            chain.push(name);
+            current = profile.inherit.as_deref();
+        }
+        let mut bindings = KeyBindingMap::new();
+        for name in chain.iter().rev() {
+            let profile = &self.profiles[*name];
+            for (keys, action) in profile.bindings.iter() {
+                let _ = match profile.bindings.meta(keys) {
+                    Some(meta) => bindings.insert_with_meta(keys.clone(), action.clone(), meta.clone()),
+                    None => bindings.insert(keys.clone(), action.clone()),
+                };
+                if !profile.bindings.is_enabled(keys) {
+                    bindings.set_enabled(keys, false);
+                }
+            }
+            for (pattern, action) in profile.bindings.patterns() {
+                let _ = bindings.insert_pattern(*pattern, action.clone());
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+#[test]
+fn check_profile_errors() {
+    let mut profiles: BindingProfiles<&str> = BindingProfiles::new();
+    profiles.insert("a", BindingProfile { inherit: Some("b".to_string()), ..Default::default() });
+    profiles.insert("b", BindingProfile { inherit: Some("a".to_string()), ..Default::default() });
+    profiles.insert("c", BindingProfile { inherit: Some("d".to_string()), ..Default::default() });
+    assert_eq!(
+        profiles.resolve("a").unwrap_err(),
+        ProfileError::InheritanceCycle(vec!["a".to_string(), "b".to_string(), "a".to_string()]),
+    );
+    assert_eq!(profiles.resolve("c").unwrap_err(), ProfileError::UnknownProfile("d".to_string()));
+}
+
+#[test]
+fn check_profile_patterns() {
+    let mut default = BindingProfile::default();
+    default.bindings.insert_pattern("*-enter", "confirm").unwrap();
+    default.bindings.insert_pattern("none-x", "cut").unwrap();
+    let mut vim = BindingProfile { inherit: Some("default".to_string()), ..Default::default() };
+    vim.bindings.insert_pattern("none-x", "delete").unwrap();
+    let mut profiles = BindingProfiles::new();
+    profiles.insert("default", default);
+    profiles.insert("vim", vim);
+    let vim = profiles.resolve("vim").unwrap();
+    assert_eq!(vim.len(), 2);
+    assert_eq!(vim.get_pattern(key!(alt-enter)), Some(&"confirm"));
+    assert_eq!(vim.get_pattern(key!(x)), Some(&"delete"));
+}
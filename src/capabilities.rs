@@ -0,0 +1,170 @@
+use std::{
+    fs::File,
+    io,
+};
+
+/// What the terminal tells about the keys, which decides whether
+/// key combinations can be combined.
+///
+/// On Windows, crossterm reads the console input records which report
+/// key releases natively, so combining doesn't need the keyboard
+/// enhancement flags, which the console rejects. On other platforms,
+/// release events are only sent by terminals supporting the kitty
+/// keyboard protocol, once the flags are pushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyboardCapabilities {
+    /// The keyboard enhancement flags (kitty protocol) can be pushed
+    pub keyboard_enhancement: bool,
+    /// Key releases are reported without the keyboard enhancement flags
+    pub native_release_events: bool,
+}
+
+impl KeyboardCapabilities {
+    /// Query the terminal (on unix, this writes to the controlling
+    /// terminal, `/dev/tty`, and waits for its answer, like [Self::detect_on])
+    pub fn detect() -> io::Result<Self> {
+        #[cfg(windows)]
+        {
+            Ok(Self {
+                keyboard_enhancement: false,
+                native_release_events: true,
+            })
+        }
+        #[cfg(unix)]
+        {
+            let tty = File::options().read(true).write(true).open("/dev/tty")?;
+            Self::detect_on(&tty)
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            Ok(Self {
+                keyboard_enhancement: crossterm::terminal::supports_keyboard_enhancement()?,
+                native_release_events: false,
+            })
+        }
+    }
+    /// Query the given terminal, e.g. an opened `/dev/pts/N`, instead of
+    /// the one of stdout.
+    ///
+    /// On unix, the query is written to the terminal and its answer read
+    /// from it (see [query_keyboard_enhancement](crate::query_keyboard_enhancement)),
+    /// its echo and line buffering being disabled meanwhile, so nothing else
+    /// should read this terminal during the call. Keys typed meanwhile are lost.
+    pub fn detect_on(tty: &File) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            Ok(Self {
+                keyboard_enhancement: query_tty(tty)?,
+                native_release_events: false,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tty;
+            Self::detect()
+        }
+    }
+    /// Tell whether key releases are reported, natively or after the
+    /// keyboard enhancement flags are pushed, so that keys can be combined
+    pub fn release_events(&self) -> bool {
+        self.native_release_events || self.keyboard_enhancement
+    }
+    /// Tell whether the keyboard enhancement flags should be pushed to
+    /// get key releases
+    pub fn needs_keyboard_enhancement_flags(&self) -> bool {
+        self.keyboard_enhancement && !self.native_release_events
+    }
+}
+
+/// Query the terminal, its echo and line buffering being disabled meanwhile
+#[cfg(unix)]
+fn query_tty(tty: &File) -> io::Result<bool> {
+    use {
+        crate::{
+            query_keyboard_enhancement,
+            tty_keys::sys,
+        },
+        std::{
+            os::unix::io::AsRawFd,
+            time::{
+                Duration,
+                Instant,
+            },
+        },
+    };
+    let fd = tty.as_raw_fd();
+    let original = sys::get_termios(fd)?;
+    let mut termios = original;
+    termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+    sys::set_termios(fd, &termios)?;
+    let reader = DeadlineReader {
+        tty,
+        deadline: Instant::now() + Duration::from_secs(2),
+    };
+    let reply = query_keyboard_enhancement(reader, tty);
+    sys::set_termios(fd, &original)?;
+    Ok(reply?.is_supported())
+}
+
+/// A reader of the tty failing when nothing was read before the deadline
+#[cfg(unix)]
+struct DeadlineReader<'t> {
+    tty: &'t File,
+    deadline: std::time::Instant,
+}
+
+#[cfg(unix)]
+impl io::Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::{
+            os::unix::io::AsRawFd,
+            time::Instant,
+        };
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        let mut fds = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let n = unsafe { libc::poll(&mut fds, 1, remaining.as_millis() as i32) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if n == 0 {
+            return Err(crate::combining_support::query_timeout());
+        }
+        self.tty.read(buf)
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn check_query_timeout() {
+    use {
+        crate::combining_support::is_query_timeout,
+        std::{
+            io::Read,
+            os::unix::io::FromRawFd,
+            time::Instant,
+        },
+    };
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (silent, _writer) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let mut reader = DeadlineReader {
+        tty: &silent,
+        deadline: Instant::now(),
+    };
+    let error = reader.read(&mut [0; 8]).unwrap_err();
+    assert!(is_query_timeout(&error));
+    let error = crate::query_keyboard_enhancement(reader, io::sink()).unwrap_err();
+    assert!(is_query_timeout(&error));
+}
+
+#[cfg(windows)]
+#[test]
+fn check_windows_console_capabilities() {
+    let capabilities = KeyboardCapabilities::detect().unwrap();
+    assert!(capabilities.release_events());
+    assert!(!capabilities.needs_keyboard_enhancement_flags());
+}
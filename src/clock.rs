@@ -0,0 +1,31 @@
+use {
+    std::{
+        fmt,
+        sync::Arc,
+        time::Instant,
+    },
+};
+
+/// The source of the current time of the time-based features: the instant
+/// of the first press of a combination, long presses, repeat detection, and
+/// the key log.
+///
+/// The default clock is the [SystemClock]. Tests may give a
+/// [MockClock](crate::testing::MockClock) to advance time deterministically.
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The clock of the system, i.e. `Instant::now()`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
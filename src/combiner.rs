@@ -2,12 +2,10 @@ use {
     crate::*,
     crossterm::{
         event::{
-            KeyCode,
+            self,
+            Event,
             KeyEvent,
             KeyboardEnhancementFlags,
-            KeyEventKind,
-            KeyModifiers,
-            ModifierKeyCode,
             PopKeyboardEnhancementFlags,
             PushKeyboardEnhancementFlags,
         },
@@ -15,78 +13,300 @@ use {
         terminal,
     },
     std::{
-        io,
-        ops::Drop,
+        fs::File,
+        io::{
+            self,
+            Write,
+        },
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
     },
 };
 
-/// This is the maximum number of keys we can combine.
-/// It can't be changed just here, as the KeyCombination type doesn't support
-/// more than 3 non-modifier keys
-const MAX_PRESS_COUNT: usize = 3;
-
 /// Consumes key events and combines them into key combinations.
 ///
+/// The combining itself is done by a [CombinerCore], the Combiner
+/// handles the terminal's keyboard enhancement flags.
+///
+/// By default, the flags are written to stdout. A combiner may be given
+/// its own terminal with [Combiner::with_tty], e.g. when the application
+/// drives a popup in a second terminal: each terminal has its own flag stack,
+/// and combiners on different terminals don't interfere.
+///
 /// See the print_key_events example.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Combiner {
-    combining: bool,
-    keyboard_enhancement_flags_pushed: bool,
+    core: CombinerCore,
+    /// the terminal the flags are written to, stdout when `None`
+    tty: Option<File>,
+    keyboard_enhancement_flags: EnhancementFlags,
+    keyboard_enhancement_guard: Option<KeyboardEnhancementGuard>,
     keyboard_enhancement_flags_externally_managed: bool,
-    mandate_modifier_for_multiple_keys: bool,
-    down_keys: Vec<KeyEvent>,
-    shift_pressed: bool,
+    capabilities: Option<KeyboardCapabilities>,
+    suspended: Option<Suspension>,
+    /// how long [Combiner::poll_transform] waits for the release of down
+    /// keys, [DEFAULT_FLUSH_DELAY] when `None`
+    flush_delay: Option<Duration>,
+    /// when the last key event was received
+    last_key_at: Option<Instant>,
 }
 
-impl Default for Combiner {
-    fn default() -> Self {
-        Self {
-            combining: false,
-            keyboard_enhancement_flags_pushed: false,
-            keyboard_enhancement_flags_externally_managed: false,
-            mandate_modifier_for_multiple_keys: true,
-            down_keys: Vec::new(),
-            shift_pressed: false,
-        }
-    }
+/// How long [Combiner::poll_transform] waits, by default, for the release
+/// of the keys which are down before returning their combination
+pub const DEFAULT_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+/// What was undone by [Combiner::suspend]
+#[derive(Debug, Clone, Copy)]
+struct Suspension {
+    flags_released: bool,
+    raw_mode_disabled: bool,
 }
 
 impl Combiner {
+    /// Make the combiner handle the given terminal (e.g. an opened
+    /// `/dev/pts/N`) instead of the one of stdout.
+    ///
+    /// The keyboard enhancement flags and the alternate screen sequences are
+    /// written to this terminal, and its capabilities are queried there.
+    ///
+    /// crossterm only reads the events of its own terminal, so the combiner
+    /// doesn't read this one: [Combiner::poll_transform] returns an error, read
+    /// the key events of the terminal and give them to [Combiner::transform].
+    /// Raw mode isn't changed by [Combiner::suspend] either.
+    ///
+    /// ```no_run
+    /// use {crokey::*, std::fs::OpenOptions};
+    /// # fn main() -> std::io::Result<()> {
+    /// let popup_tty = OpenOptions::new().read(true).write(true).open("/dev/pts/3")?;
+    /// let mut main_combiner = Combiner::default();
+    /// let mut popup_combiner = Combiner::default().with_tty(popup_tty);
+    /// main_combiner.enable_combining()?;
+    /// popup_combiner.enable_combining()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_tty(mut self, tty: File) -> Self {
+        self.tty = Some(tty);
+        self
+    }
+    /// Return the terminal given with [Combiner::with_tty], if any
+    pub fn tty(&self) -> Option<&File> {
+        self.tty.as_ref()
+    }
+    /// Change the keyboard enhancement flags pushed when combining is
+    /// enabled, e.g. to let users work around a terminal misbehaving with
+    /// one of them. The default ones are [KEYBOARD_ENHANCEMENT_FLAGS].
+    ///
+    /// `REPORT_EVENT_TYPES` is always pushed, as combining needs the key
+    /// releases.
+    ///
+    /// ```no_run
+    /// use crokey::*;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let flags: EnhancementFlags = "disambiguate, report-event-types".parse()?;
+    /// let mut combiner = Combiner::default().with_keyboard_enhancement_flags(flags);
+    /// combiner.enable_combining()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_keyboard_enhancement_flags<F: Into<KeyboardEnhancementFlags>>(
+        mut self,
+        flags: F,
+    ) -> Self {
+        let flags = flags.into() | KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        self.keyboard_enhancement_flags = EnhancementFlags(flags);
+        self
+    }
+    /// Return the keyboard enhancement flags pushed when combining is enabled
+    pub fn keyboard_enhancement_flags(&self) -> KeyboardEnhancementFlags {
+        self.keyboard_enhancement_flags.0
+    }
     /// Try to enable combining more than one non-modifier key into a combination.
     ///
-    /// Return Ok(false) when the terminal doesn't support the kitty protocol.
+    /// Return Ok(false) when the terminal doesn't report key releases.
     ///
     /// Behind the scene, this function pushes the keyboard enhancement flags
     /// to the terminal. The flags are popped, and the normal state of the terminal
     /// restored, when the Combiner is dropped.
+    /// When several combiners enable combining, the flags are pushed once and
+    /// popped when the last one is dropped (see [KeyboardEnhancementGuard]).
+    ///
+    /// On Windows, key releases are reported by the console without any flag,
+    /// so nothing is pushed. See [KeyboardCapabilities].
     ///
     /// This function does nothing if combining is already enabled.
+    ///
+    /// See [Combiner::enable_combining_with_report] to know why combining
+    /// couldn't be enabled.
     pub fn enable_combining(&mut self) -> io::Result<bool> {
-        if self.combining {
-            return Ok(true);
+        let support = self.enable_combining_with_report();
+        match support.unavailability {
+            Some(CombiningUnavailability::Io(e)) => Err(e),
+            Some(CombiningUnavailability::Timeout) => Err(query_timeout()),
+            _ => Ok(support.enabled),
+        }
+    }
+    /// Try to enable combining, like [Combiner::enable_combining], and
+    /// return what was done or why combining isn't possible.
+    ///
+    /// ```no_run
+    /// # let mut combiner = crokey::Combiner::default();
+    /// let support = combiner.enable_combining_with_report();
+    /// if let Some(unavailability) = support.unavailability {
+    ///     eprintln!("Key combinations are disabled: {}", unavailability);
+    /// }
+    /// ```
+    pub fn enable_combining_with_report(&mut self) -> CombiningSupport {
+        let mut support = CombiningSupport::enabled();
+        support.externally_managed = self.keyboard_enhancement_flags_externally_managed;
+        if self.core.is_combining() {
+            if self.keyboard_enhancement_guard.is_some() {
+                support.pushed_flags = Some(self.keyboard_enhancement_flags());
+            }
+            return support;
         }
         if !self.keyboard_enhancement_flags_externally_managed {
-            if self.keyboard_enhancement_flags_pushed {
-                return Ok(self.combining);
+            if !is_a_terminal(self.tty.as_ref()) {
+                return CombiningSupport::unavailable(CombiningUnavailability::NotATerminal);
+            }
+            let capabilities = match self.detect_capabilities() {
+                Ok(capabilities) => capabilities,
+                Err(e) if is_query_timeout(&e) => {
+                    return CombiningSupport::unavailable(CombiningUnavailability::Timeout);
+                }
+                Err(e) => {
+                    return CombiningSupport::unavailable(CombiningUnavailability::Io(e));
+                }
+            };
+            if !capabilities.release_events() {
+                debug_event!("keyboard enhancement not supported by the terminal");
+                return CombiningSupport::unavailable(match multiplexer() {
+                    Some(name) => CombiningUnavailability::Multiplexer(name),
+                    None => CombiningUnavailability::Unsupported,
+                });
             }
-            if !terminal::supports_keyboard_enhancement()? {
-                return Ok(false);
+            support.native_release_events = capabilities.native_release_events;
+            if capabilities.needs_keyboard_enhancement_flags() {
+                let flags = self.keyboard_enhancement_flags();
+                match KeyboardEnhancementGuard::acquire_with_flags(self.tty.as_ref(), flags) {
+                    Ok(guard) => self.keyboard_enhancement_guard = Some(guard),
+                    Err(e) => {
+                        return CombiningSupport::unavailable(CombiningUnavailability::Io(e));
+                    }
+                }
+                support.pushed_flags = Some(flags);
             }
-            push_keyboard_enhancement_flags()?;
-            self.keyboard_enhancement_flags_pushed = true;
         }
-        self.combining = true;
-        Ok(true)
+        self.core.set_combining(true);
+        support
     }
     /// Disable combining.
     pub fn disable_combining(&mut self) -> io::Result<()> {
-        if !self.keyboard_enhancement_flags_externally_managed && self.keyboard_enhancement_flags_pushed {
-            pop_keyboard_enhancement_flags()?;
-            self.keyboard_enhancement_flags_pushed = false;
+        if let Some(guard) = self.keyboard_enhancement_guard.take() {
+            guard.release()?;
         }
-        self.combining = false;
+        self.core.set_combining(false);
         Ok(())
     }
+    /// Push the keyboard enhancement flags again, if this combiner pushed them.
+    ///
+    /// Terminals keep a separate flag stack for the main and the alternate
+    /// screens, so the flags pushed on the main screen are lost when entering
+    /// the alternate screen (as most TUI applications do), and combining stops
+    /// working. Call this function after switching screen, or use
+    /// [Combiner::enter_alternate_screen].
+    ///
+    /// The stack of the alternate screen is discarded by the terminal when
+    /// leaving it, so there's nothing to undo then.
+    pub fn reapply(&mut self) -> io::Result<()> {
+        if self.keyboard_enhancement_guard.is_some() {
+            push_flags(self.tty.as_ref(), self.keyboard_enhancement_flags())?;
+            debug_event!("keyboard enhancement flags pushed again");
+        }
+        Ok(())
+    }
+    /// Enter the alternate screen, then push again the keyboard enhancement
+    /// flags if needed
+    pub fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        match &self.tty {
+            Some(tty) => execute!(&mut &*tty, terminal::EnterAlternateScreen)?,
+            None => execute!(io::stdout(), terminal::EnterAlternateScreen)?,
+        }
+        self.reapply()
+    }
+    /// Leave the alternate screen, whose keyboard enhancement flags are
+    /// discarded by the terminal, the ones of the main screen being back.
+    ///
+    /// Leave the alternate screen before dropping the combiner, so that the
+    /// flags are popped from the stack of the main screen.
+    pub fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        match &self.tty {
+            Some(tty) => execute!(&mut &*tty, terminal::LeaveAlternateScreen),
+            None => execute!(io::stdout(), terminal::LeaveAlternateScreen),
+        }
+    }
+    /// Restore the normal state of the terminal before the application is
+    /// suspended (e.g. on `ctrl-z`, before raising SIGTSTP), so that the
+    /// user's shell doesn't receive keys in the kitty protocol.
+    ///
+    /// The claim of this combiner on the keyboard enhancement flags is
+    /// released, which pops them unless another combiner of the terminal
+    /// still needs them (see [KeyboardEnhancementGuard]), and raw mode
+    /// is disabled if it's enabled.
+    /// Call [Combiner::resume] when the application is resumed (SIGCONT).
+    ///
+    /// On unix, the sequence would look like
+    ///
+    /// ```ignore
+    /// combiner.suspend()?;
+    /// unsafe { libc::raise(libc::SIGTSTP); } // returns on SIGCONT
+    /// combiner.resume()?;
+    /// ```
+    pub fn suspend(&mut self) -> io::Result<()> {
+        if self.suspended.is_some() {
+            return Ok(());
+        }
+        let flags_released = match self.keyboard_enhancement_guard.take() {
+            Some(guard) => {
+                guard.release()?;
+                true
+            }
+            None => false,
+        };
+        let raw_mode_disabled = self.tty.is_none() && terminal::is_raw_mode_enabled()?;
+        if raw_mode_disabled {
+            terminal::disable_raw_mode()?;
+        }
+        debug_event!(flags_released, raw_mode_disabled, "combiner suspended");
+        self.suspended = Some(Suspension { flags_released, raw_mode_disabled });
+        Ok(())
+    }
+    /// Put back the terminal in the state it was before [Combiner::suspend]
+    ///
+    /// The combination in progress when suspending, if any, is discarded.
+    pub fn resume(&mut self) -> io::Result<()> {
+        let suspension = match self.suspended.take() {
+            Some(suspension) => suspension,
+            None => return Ok(()),
+        };
+        if suspension.raw_mode_disabled {
+            terminal::enable_raw_mode()?;
+        }
+        if suspension.flags_released {
+            let flags = self.keyboard_enhancement_flags();
+            let guard = KeyboardEnhancementGuard::acquire_with_flags(self.tty.as_ref(), flags)?;
+            self.keyboard_enhancement_guard = Some(guard);
+        }
+        debug_event!("combiner resumed");
+        self.core.discard_pending();
+        Ok(())
+    }
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.is_some()
+    }
     /// Tell the Combiner not to push/pop the keyboard enhancement flags.
     ///
     /// Call before enable_combining if you want to manage the flags yourself.
@@ -95,7 +315,20 @@ impl Combiner {
         self.keyboard_enhancement_flags_externally_managed = true;
     }
     pub fn is_combining(&self) -> bool {
-        self.combining
+        self.core.is_combining()
+    }
+    /// Return the capabilities of the terminal, detected on the first call
+    pub fn detect_capabilities(&mut self) -> io::Result<KeyboardCapabilities> {
+        if let Some(capabilities) = self.capabilities {
+            return Ok(capabilities);
+        }
+        let capabilities = match &self.tty {
+            Some(tty) => KeyboardCapabilities::detect_on(tty)?,
+            None => KeyboardCapabilities::detect()?,
+        };
+        debug_event!(?capabilities, "keyboard capabilities detected");
+        self.capabilities = Some(capabilities);
+        Ok(capabilities)
     }
     /// When combining is enabled, you may either want "simple" keys
     /// (i.e. without modifier or space) to be handled on key press,
@@ -106,106 +339,182 @@ impl Combiner {
     ///
     /// This setting has no effect when combining isn't enabled.
     pub fn set_mandate_modifier_for_multiple_keys(&mut self, mandate: bool) {
-        self.mandate_modifier_for_multiple_keys = mandate;
-    }
-    /// Take all the down_keys, combine them into a KeyCombination
-    fn combine(&mut self, clear: bool) -> Option<KeyCombination> {
-        let mut key_combination = KeyCombination::try_from(self.down_keys.as_slice())
-            .ok(); // it may be empty, in which case we return None
-        if self.shift_pressed {
-            if let Some(ref mut key_combination) = key_combination {
-                key_combination.modifiers |= KeyModifiers::SHIFT;
-            }
-        }
-        if clear {
-            self.down_keys.clear();
-            self.shift_pressed = false;
-        }
-        key_combination
+        self.core.set_mandate_modifier_for_multiple_keys(mandate);
+    }
+    /// Change the predicate deciding which keys are "simple", i.e. returned
+    /// on press when [Combiner::set_mandate_modifier_for_multiple_keys] is set.
+    ///
+    /// See [CombinerCore::set_simple_key_predicate].
+    pub fn set_simple_key_predicate(&mut self, predicate: fn(KeyEvent) -> bool) {
+        self.core.set_simple_key_predicate(predicate);
+    }
+    /// Inject a synthetic key combination, as if its keys were pressed
+    /// then released, and return the resulting key combination.
+    ///
+    /// This lets applications run a binding programmatically (e.g. from
+    /// a command palette) through the same path as real input.
+    ///
+    /// See [CombinerCore::simulate].
+    pub fn simulate(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        self.core.simulate(key)
+    }
+    /// Set how shift is expressed in the produced key combinations.
+    ///
+    /// See [CombinerCore::set_shift_policy].
+    pub fn set_shift_policy(&mut self, shift_policy: ShiftPolicy) {
+        self.core.set_shift_policy(shift_policy);
+    }
+    /// Set how shift is handled with the ASCII punctuation chars.
+    ///
+    /// See [CombinerCore::set_shifted_symbol_policy].
+    pub fn set_shifted_symbol_policy(&mut self, policy: ShiftedSymbolPolicy) {
+        self.core.set_shifted_symbol_policy(policy);
+    }
+    /// Set what to do with the events whose key code is `KeyCode::Null`.
+    ///
+    /// See [CombinerCore::set_null_key_policy].
+    pub fn set_null_key_policy(&mut self, null_key_policy: NullKeyPolicy) {
+        self.core.set_null_key_policy(null_key_policy);
+    }
+    /// Tell whether the numpad keys should be distinguished from the ones
+    /// of the main keyboard, when the terminal reports them.
+    ///
+    /// See [CombinerCore::set_keypad_distinction].
+    pub fn set_keypad_distinction(&mut self, distinction: bool) {
+        self.core.set_keypad_distinction(distinction);
+    }
+    /// Enable or disable sticky modifiers, so that ctrl then c is ctrl-c.
+    ///
+    /// See [CombinerCore::set_sticky_modifiers].
+    pub fn set_sticky_modifiers(&mut self, sticky: bool) {
+        self.core.set_sticky_modifiers(sticky);
+    }
+    /// Set the clock giving the instant the first key of a combination
+    /// was pressed, and measuring the [flush delay](Combiner::set_flush_delay).
+    ///
+    /// See [CombinerCore::set_clock].
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.core.set_clock(clock);
+    }
+    pub(crate) fn set_shared_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.core.set_shared_clock(clock);
+    }
+    /// Return the counts of the key events received but filtered out
+    /// (modifier keys alone, releases in ansi mode, `Null` key codes),
+    /// to detect misbehaving terminals.
+    ///
+    /// See [CombinerStats].
+    pub fn stats(&self) -> CombinerStats {
+        self.core.stats()
+    }
+    pub fn reset_stats(&mut self) {
+        self.core.reset_stats();
+    }
+    /// Return the I/O free state machine doing the combining
+    pub fn core(&self) -> &CombinerCore {
+        &self.core
     }
     /// Receive a key event and return a key combination if one is ready.
     ///
     /// When combining is enabled, the key combination is only returned on a
     /// key release event.
     pub fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
-        if self.combining {
-            self.transform_combining(key)
-        } else {
-            self.transform_ansi(key)
-        }
+        self.last_key_at = Some(self.core.now());
+        self.core.transform(key)
     }
-    fn transform_combining(&mut self, key: KeyEvent) -> Option<KeyCombination> {
-        if let KeyCode::Modifier(modifier) = key.code {
-            if modifier == ModifierKeyCode::LeftShift || modifier == ModifierKeyCode::RightShift {
-                self.shift_pressed = key.kind != KeyEventKind::Release;
-            }
-            // we ignore modifier keys as independent events
-            // (which means we never return a combination with only modifiers)
-            return None;
+    /// Set how long [Combiner::poll_transform] waits for the release of
+    /// the keys which are down, [DEFAULT_FLUSH_DELAY] by default
+    pub fn set_flush_delay(&mut self, flush_delay: Duration) {
+        self.flush_delay = Some(flush_delay);
+    }
+    pub fn flush_delay(&self) -> Duration {
+        self.flush_delay.unwrap_or(DEFAULT_FLUSH_DELAY)
+    }
+    /// Wait at most `timeout` for a key combination, reading the events
+    /// with crossterm's `poll` and `read`.
+    ///
+    /// When no key event was received during the [flush delay](Combiner::set_flush_delay)
+    /// while keys are down, their key combination is returned without waiting
+    /// for their release, so that a lost release event doesn't block the
+    /// combiner. This doesn't depend on `timeout`, so that short timeouts
+    /// don't cut combinations.
+    ///
+    /// Non key events (resize, mouse, etc.) are dropped: when you need them,
+    /// call `poll`/`read` yourself and give the key events to [Combiner::transform].
+    ///
+    /// crossterm only reads stdin, so this fails with `ErrorKind::Unsupported`
+    /// when the combiner was given another terminal with [Combiner::with_tty].
+    ///
+    /// ```no_run
+    /// use {crokey::*, std::time::Duration};
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut combiner = Combiner::default();
+    /// combiner.enable_combining()?;
+    /// crossterm::terminal::enable_raw_mode()?;
+    /// loop {
+    ///     match combiner.poll_transform(Duration::from_millis(500))? {
+    ///         Some(key!(ctrl-q)) => break,
+    ///         Some(key) => println!("{key}\r"),
+    ///         None => {} // timeout: do some background work
+    ///     }
+    /// }
+    /// crossterm::terminal::disable_raw_mode()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn poll_transform(&mut self, timeout: Duration) -> io::Result<Option<KeyCombination>> {
+        if self.tty.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the events of a terminal given with with_tty can't be polled by the combiner",
+            ));
         }
-        if
-                self.mandate_modifier_for_multiple_keys
-                && is_key_simple(key)
-                && !self.shift_pressed
-                && self.down_keys.is_empty()
-        {
-            // "simple key" are handled differently: they're returned on press and repeat
-            match key.kind {
-                KeyEventKind::Press | KeyEventKind::Repeat => {
-                    self.down_keys.push(key);
-                    self.combine(true)
-                }
-                KeyEventKind::Release => {
-                    None
-                }
+        let deadline = Instant::now() + timeout;
+        loop {
+            // the flush delay is measured with the clock of the core,
+            // the timeout with the system one
+            let flush_at = match self.last_key_at {
+                Some(last_key_at) if self.core.has_pending() => Some(last_key_at + self.flush_delay()),
+                _ => None,
+            };
+            let flush_wait = flush_at.map(|flush_at| flush_at.saturating_duration_since(self.core.now()));
+            if flush_wait == Some(Duration::ZERO) {
+                debug_event!("no key event during the flush delay, flushing");
+                return Ok(self.core.flush());
             }
-        } else {
-            // not a single simple key
-            match key.kind {
-                KeyEventKind::Press => {
-                    self.down_keys.push(key);
-                    if self.down_keys.len() == MAX_PRESS_COUNT {
-                        self.combine(true)
-                    } else {
-                        None
-                    }
-                }
-                KeyEventKind::Release => {
-                    // this release ends the combination in progress
-                    self.combine(true)
-                }
-                KeyEventKind::Repeat => {
-                    self.combine(false)
+            let now = Instant::now();
+            if deadline <= now {
+                return Ok(None);
+            }
+            let wait = match flush_wait {
+                Some(flush_wait) => flush_wait.min(deadline - now),
+                None => deadline - now,
+            };
+            if !event::poll(wait)? {
+                continue;
+            }
+            if let Event::Key(key_event) = event::read()? {
+                if let Some(key_combination) = self.transform(key_event) {
+                    return Ok(Some(key_combination));
                 }
             }
         }
     }
-    /// In ansi mode, no combination is possible, and we don't expect to
-    /// receive anything else than a single key or than key presses.
-    fn transform_ansi(&mut self, key: KeyEvent) -> Option<KeyCombination> {
-        match key.kind {
-            KeyEventKind::Press => Some(key.into()),
-            _ => {
-                // this is unexpected, we don't seem to be really in ansi mode
-                // but for consistency we must filter out this event
-                None
-            }
-        }
+    /// Receive a key event and return a key combination event, keeping
+    /// the kind and state of the key event, if a key combination is ready.
+    ///
+    /// See [CombinerCore::transform_event].
+    pub fn transform_event(&mut self, key: KeyEvent) -> Option<KeyCombinationEvent> {
+        self.last_key_at = Some(self.core.now());
+        self.core.transform_event(key)
     }
 }
 
-/// For the purpose of key combination, we consider that a key is "simple"
-/// when it's neither a modifier (ctrl,alt,shift) nor a space.
-pub fn is_key_simple(key: KeyEvent) -> bool {
-    key.modifiers.is_empty()
-        && key.code != KeyCode::Char(' ')
-}
-
-impl Drop for Combiner {
-    fn drop(&mut self) {
-        if self.keyboard_enhancement_flags_pushed {
-            let _ = pop_keyboard_enhancement_flags();
-        }
+/// As a stage, a combiner combines the keys of the key combinations it
+/// receives, like [Combiner::simulate].
+impl KeyStage for Combiner {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        self.simulate(key)
     }
 }
 
@@ -213,15 +522,14 @@ impl Drop for Combiner {
 /// This is done automatically by Combiner::enable_combining
 /// so you should usually not need to call this function.
 pub fn push_keyboard_enhancement_flags() -> io::Result<()> {
-    let mut stdout = io::stdout();
+    push_keyboard_enhancement_flags_to(io::stdout())
+}
+
+/// Change the state of the given terminal to enable combining keys.
+pub fn push_keyboard_enhancement_flags_to<W: Write>(mut tty: W) -> io::Result<()> {
     execute!(
-        stdout,
-        PushKeyboardEnhancementFlags(
-            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-                | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
-                | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
-                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
-        )
+        tty,
+        PushKeyboardEnhancementFlags(KEYBOARD_ENHANCEMENT_FLAGS)
     )
 }
 
@@ -229,6 +537,39 @@ pub fn push_keyboard_enhancement_flags() -> io::Result<()> {
 /// This is done automatically by the combiner on drop,
 /// so you should usually not need to call this function.
 pub fn pop_keyboard_enhancement_flags() -> io::Result<()>{
-    let mut stdout = io::stdout();
-    execute!(stdout, PopKeyboardEnhancementFlags)
+    pop_keyboard_enhancement_flags_from(io::stdout())
+}
+
+/// Restore the "normal" state of the given terminal.
+pub fn pop_keyboard_enhancement_flags_from<W: Write>(mut tty: W) -> io::Result<()> {
+    execute!(tty, PopKeyboardEnhancementFlags)
+}
+
+#[test]
+fn check_flush_delay_follows_the_clock() {
+    use crossterm::event::{KeyCode, KeyEventKind, KeyEventState, KeyModifiers};
+    let clock = testing::MockClock::new();
+    let mut combiner = Combiner::default();
+    combiner.set_clock(clock.clone());
+    combiner.set_keyboard_enhancement_flags_externally_managed();
+    assert!(combiner.enable_combining().unwrap());
+    let press = KeyEvent {
+        code: KeyCode::Char('a'),
+        modifiers: KeyModifiers::CONTROL,
+        kind: KeyEventKind::Press,
+        state: KeyEventState::empty(),
+    };
+    assert_eq!(combiner.transform_event(press), None);
+    clock.advance(combiner.flush_delay());
+    // the release was lost, the flush happens without reading any event
+    assert_eq!(combiner.poll_transform(Duration::ZERO).unwrap(), Some(key!(ctrl-a)));
+}
+
+#[cfg(unix)]
+#[test]
+fn check_poll_transform_refuses_other_terminals() {
+    let null = File::options().write(true).open("/dev/null").unwrap();
+    let mut combiner = Combiner::default().with_tty(null);
+    let error = combiner.poll_transform(Duration::ZERO).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::Unsupported);
 }
@@ -0,0 +1,1000 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        KeyEventState,
+        KeyModifiers,
+        ModifierKeyCode,
+    },
+    std::{
+        sync::Arc,
+        time::Instant,
+    },
+};
+
+/// This is the maximum number of keys we can combine.
+/// It can't be changed just here, as the KeyCombination type doesn't support
+/// more than 3 non-modifier keys
+const MAX_PRESS_COUNT: usize = 3;
+
+/// The keys down in the combination in progress, stored inline so that
+/// the combiner doesn't allocate
+#[derive(Debug, Clone, Copy)]
+struct DownKeys {
+    keys: [KeyEvent; MAX_PRESS_COUNT],
+    len: usize,
+}
+
+impl Default for DownKeys {
+    fn default() -> Self {
+        Self {
+            keys: [KeyEvent::new(KeyCode::Null, KeyModifiers::NONE); MAX_PRESS_COUNT],
+            len: 0,
+        }
+    }
+}
+
+impl DownKeys {
+    fn push(&mut self, key: KeyEvent) {
+        // the combination is made as soon as the last slot is filled,
+        // so there's always room here
+        if self.len < MAX_PRESS_COUNT {
+            self.keys[self.len] = key;
+            self.len += 1;
+        }
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn as_slice(&self) -> &[KeyEvent] {
+        &self.keys[..self.len]
+    }
+}
+
+/// What to do with the events whose key code is [KeyCode::Null], which
+/// some terminals send for keys they can't describe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NullKeyPolicy {
+    /// Ignore the event
+    Drop,
+    /// Return, on press, the `unknown` key combination, without any
+    /// modifier, so that all those events may be handled in one place
+    /// and don't get mixed with other keys
+    Unknown,
+    /// Handle the event like any other one, as before this policy existed
+    #[default]
+    Pass,
+}
+
+/// Counts of the key events a [CombinerCore] received but filtered out,
+/// to detect terminals sending unexpected events, e.g. in opt-in
+/// diagnostics.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+/// };
+/// let mut core = CombinerCore::default();
+/// let release = KeyEvent {
+///     kind: KeyEventKind::Release,
+///     ..KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)
+/// };
+/// assert_eq!(core.transform(release), None); // a release in ansi mode
+/// assert_eq!(core.stats().unexpected_kinds, 1);
+/// assert_eq!(core.stats().total(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CombinerStats {
+    /// Events of modifier keys alone, which are never returned as
+    /// key combinations
+    pub modifier_only: u64,
+    /// Events of a kind not expected in ansi mode (i.e. releases),
+    /// which hint at a terminal reporting more than it was asked
+    pub unexpected_kinds: u64,
+    /// Events with a `Null` key code dropped per the [NullKeyPolicy]
+    pub null_keys: u64,
+}
+
+impl CombinerStats {
+    /// Return the number of filtered out events
+    pub fn total(&self) -> u64 {
+        self.modifier_only + self.unexpected_kinds + self.null_keys
+    }
+}
+
+/// The state machine combining key events into key combinations,
+/// without any interaction with the terminal.
+///
+/// A [Combiner] wraps a `CombinerCore` and manages the terminal's
+/// keyboard enhancement flags. You may use the core directly when
+/// you manage the terminal yourself or when your events don't come
+/// from a terminal at all, for example in tests.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+/// };
+/// let event = |code, kind| KeyEvent {
+///     code,
+///     modifiers: KeyModifiers::NONE,
+///     kind,
+///     state: KeyEventState::empty(),
+/// };
+/// let mut core = CombinerCore::default();
+/// core.set_combining(true);
+/// core.set_mandate_modifier_for_multiple_keys(false);
+/// assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Press)), None);
+/// assert_eq!(core.transform(event(KeyCode::Char('b'), KeyEventKind::Press)), None);
+/// assert_eq!(
+///     core.transform(event(KeyCode::Char('a'), KeyEventKind::Release)),
+///     Some(key!(a-b)),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct CombinerCore {
+    combining: bool,
+    mandate_modifier_for_multiple_keys: bool,
+    is_key_simple: fn(KeyEvent) -> bool,
+    down_keys: DownKeys,
+    /// When the first key of the combination in progress was pressed
+    pressed_at: Option<Instant>,
+    /// When the first key of the last returned combination was pressed,
+    /// given to its repeats
+    last_pressed_at: Option<Instant>,
+    shift_pressed: bool,
+    shift_policy: ShiftPolicy,
+    shifted_symbol_policy: ShiftedSymbolPolicy,
+    null_key_policy: NullKeyPolicy,
+    keypad_distinction: bool,
+    sticky_modifiers: bool,
+    tapped_modifier: Option<KeyModifiers>,
+    latched_modifiers: KeyModifiers,
+    locked_modifiers: KeyModifiers,
+    stats: CombinerStats,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for CombinerCore {
+    fn default() -> Self {
+        Self {
+            combining: false,
+            mandate_modifier_for_multiple_keys: true,
+            is_key_simple,
+            down_keys: DownKeys::default(),
+            pressed_at: None,
+            last_pressed_at: None,
+            shift_pressed: false,
+            shift_policy: ShiftPolicy::default(),
+            shifted_symbol_policy: ShiftedSymbolPolicy::default(),
+            null_key_policy: NullKeyPolicy::default(),
+            keypad_distinction: false,
+            sticky_modifiers: false,
+            tapped_modifier: None,
+            latched_modifiers: KeyModifiers::NONE,
+            locked_modifiers: KeyModifiers::NONE,
+            stats: CombinerStats::default(),
+            clock: system_clock(),
+        }
+    }
+}
+
+impl CombinerCore {
+    /// Tell the core whether the events it receives come from a terminal
+    /// with keyboard enhancement flags set, i.e. whether key releases
+    /// are reported and non-modifier keys may be combined.
+    ///
+    /// Any combination in progress is discarded.
+    pub fn set_combining(&mut self, combining: bool) {
+        debug_event!(combining, "combiner core set_combining");
+        self.combining = combining;
+        self.discard_pending();
+    }
+    /// Forget the keys which are down, without returning their combination,
+    /// e.g. when their releases can't be received anymore
+    pub fn discard_pending(&mut self) {
+        self.down_keys.clear();
+        self.pressed_at = None;
+        self.shift_pressed = false;
+        self.tapped_modifier = None;
+    }
+    pub fn is_combining(&self) -> bool {
+        self.combining
+    }
+    /// When combining is enabled, you may either want "simple" keys
+    /// (i.e. without modifier or space) to be handled on key press,
+    /// or to wait for a key release so that maybe they may
+    /// be part of a combination like 'a-b'.
+    /// If combinations without modifier or space are unlikely in your application, you
+    /// may make it feel snappier by setting this to true.
+    ///
+    /// This setting has no effect when combining isn't enabled.
+    pub fn set_mandate_modifier_for_multiple_keys(&mut self, mandate: bool) {
+        self.mandate_modifier_for_multiple_keys = mandate;
+    }
+    /// Change the predicate deciding which keys are "simple", i.e. returned
+    /// on press when [CombinerCore::set_mandate_modifier_for_multiple_keys]
+    /// is set. The default is [is_key_simple], which excludes space.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut core = CombinerCore::default();
+    /// // make plain space snappy too
+    /// core.set_simple_key_predicate(|key| key.modifiers.is_empty());
+    /// ```
+    pub fn set_simple_key_predicate(&mut self, predicate: fn(KeyEvent) -> bool) {
+        self.is_key_simple = predicate;
+    }
+    /// Set how shift is expressed in the produced key combinations.
+    ///
+    /// The default, [ShiftPolicy::Both], is what crossterm sends.
+    pub fn set_shift_policy(&mut self, shift_policy: ShiftPolicy) {
+        self.shift_policy = shift_policy;
+    }
+    /// Set how shift is handled with the ASCII punctuation chars,
+    /// e.g. whether `!` typed with shift is kept as received (the default),
+    /// is `!` or is `shift-1`
+    pub fn set_shifted_symbol_policy(&mut self, policy: ShiftedSymbolPolicy) {
+        self.shifted_symbol_policy = policy;
+    }
+    /// Set what to do with the events whose key code is [KeyCode::Null].
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    /// };
+    /// let null = KeyEvent::new(KeyCode::Null, KeyModifiers::CONTROL);
+    /// let mut core = CombinerCore::default();
+    /// assert_eq!(core.transform(null), Some(key!(ctrl-unknown)));
+    /// core.set_null_key_policy(NullKeyPolicy::Unknown);
+    /// assert_eq!(core.transform(null), Some(key!(unknown)));
+    /// core.set_null_key_policy(NullKeyPolicy::Drop);
+    /// assert_eq!(core.transform(null), None);
+    /// ```
+    pub fn set_null_key_policy(&mut self, null_key_policy: NullKeyPolicy) {
+        self.null_key_policy = null_key_policy;
+    }
+    /// Tell whether the numpad keys reported by the terminal should be
+    /// distinguished from the ones of the main keyboard, so that `kp1`
+    /// isn't `1` (see [KEYPAD_KEYS]).
+    ///
+    /// Only terminals supporting the kitty keyboard protocol report the
+    /// numpad. Other ones send the keys of the main keyboard, which you may
+    /// bind too with [KeyCombination::without_keypad].
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+    /// };
+    /// let kp1 = KeyEvent::new_with_kind_and_state(
+    ///     KeyCode::Char('1'),
+    ///     KeyModifiers::NONE,
+    ///     KeyEventKind::Press,
+    ///     KeyEventState::KEYPAD,
+    /// );
+    /// let mut core = CombinerCore::default();
+    /// assert_eq!(core.transform(kp1), Some(key!('1')));
+    /// core.set_keypad_distinction(true);
+    /// assert_eq!(core.transform(kp1), Some(key!(kp1)));
+    /// ```
+    pub fn set_keypad_distinction(&mut self, distinction: bool) {
+        self.keypad_distinction = distinction;
+    }
+    /// Enable or disable sticky modifiers, an accessibility mode in which
+    /// modifiers don't have to be held.
+    ///
+    /// A modifier (ctrl, alt or shift) pressed then released alone is latched:
+    /// it's added to the next key combination (so that ctrl then c is ctrl-c).
+    /// Tapping a latched modifier again locks it, so that it's added to all
+    /// key combinations until it's tapped a third time.
+    ///
+    /// This needs the modifier key events, which are only sent by the terminal
+    /// when combining is enabled.
+    ///
+    /// Disabling sticky modifiers releases the latched and locked modifiers.
+    pub fn set_sticky_modifiers(&mut self, sticky: bool) {
+        self.sticky_modifiers = sticky;
+        self.tapped_modifier = None;
+        self.latched_modifiers = KeyModifiers::NONE;
+        self.locked_modifiers = KeyModifiers::NONE;
+    }
+    pub fn has_sticky_modifiers(&self) -> bool {
+        self.sticky_modifiers
+    }
+    /// Return the modifiers which will be added to the next key combination
+    /// (including the locked ones), e.g. to display them in a status bar
+    pub fn latched_modifiers(&self) -> KeyModifiers {
+        self.latched_modifiers | self.locked_modifiers
+    }
+    /// Return the modifiers added to all key combinations until unlocked
+    pub fn locked_modifiers(&self) -> KeyModifiers {
+        self.locked_modifiers
+    }
+    /// Handle a modifier key event for sticky modifiers
+    fn tap_modifier(&mut self, modifier: ModifierKeyCode, kind: KeyEventKind) {
+        let modifier = match modifier {
+            ModifierKeyCode::LeftShift | ModifierKeyCode::RightShift => KeyModifiers::SHIFT,
+            ModifierKeyCode::LeftControl | ModifierKeyCode::RightControl => KeyModifiers::CONTROL,
+            ModifierKeyCode::LeftAlt | ModifierKeyCode::RightAlt => KeyModifiers::ALT,
+            _ => {
+                return;
+            }
+        };
+        match kind {
+            KeyEventKind::Press => {
+                self.tapped_modifier = Some(modifier);
+            }
+            KeyEventKind::Repeat => {}
+            KeyEventKind::Release => {
+                if self.tapped_modifier.take() != Some(modifier) {
+                    // the modifier was used with another key
+                    return;
+                }
+                if self.locked_modifiers.contains(modifier) {
+                    self.locked_modifiers.remove(modifier);
+                } else if self.latched_modifiers.contains(modifier) {
+                    self.latched_modifiers.remove(modifier);
+                    self.locked_modifiers.insert(modifier);
+                } else {
+                    self.latched_modifiers.insert(modifier);
+                }
+                debug_event!(
+                    latched = ?self.latched_modifiers,
+                    locked = ?self.locked_modifiers,
+                    "sticky modifier tapped",
+                );
+            }
+        }
+    }
+    /// Add the latched and locked modifiers to a key combination
+    fn apply_sticky_modifiers(&mut self, mut key_combination: KeyCombination) -> KeyCombination {
+        let sticky = self.latched_modifiers | self.locked_modifiers;
+        self.latched_modifiers = KeyModifiers::NONE;
+        if !key_combination.modifiers.contains(sticky) {
+            key_combination.modifiers |= sticky;
+            if sticky.contains(KeyModifiers::SHIFT) {
+                key_combination = key_combination.normalized();
+            }
+        }
+        key_combination
+    }
+    /// Take all the down_keys, combine them into a KeyCombination
+    fn combine(&mut self, clear: bool) -> Option<KeyCombination> {
+        let mut key_combination = match KeyCombination::try_from(self.down_keys.as_slice()) {
+            Ok(key_combination) => Some(key_combination),
+            // nothing is down, e.g. on the release of a key already returned
+            Err(KeyCombinationError::Empty) => None,
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            Err(error) => {
+                debug_event!(%error, "keys can't be combined");
+                None
+            }
+        };
+        if self.shift_pressed {
+            key_combination = key_combination.map(|key_combination| {
+                KeyCombination {
+                    modifiers: key_combination.modifiers | KeyModifiers::SHIFT,
+                    ..key_combination
+                }
+                .normalized()
+            });
+        }
+        if clear {
+            self.down_keys.clear();
+            self.shift_pressed = false;
+        }
+        key_combination
+    }
+    /// Set the clock giving the instant the first key of a combination
+    /// was pressed, see [CombinerCore::transform_event]
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.set_shared_clock(Arc::new(clock));
+    }
+    pub(crate) fn set_shared_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    /// Return the current instant, according to the clock of the core
+    #[cfg(feature = "terminal")]
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+    /// Return the counts of the events filtered out since the core
+    /// was created or its stats were reset
+    pub fn stats(&self) -> CombinerStats {
+        self.stats
+    }
+    pub fn reset_stats(&mut self) {
+        self.stats = CombinerStats::default();
+    }
+    /// Tell whether some keys are down, waiting for a release
+    pub fn has_pending(&self) -> bool {
+        !self.down_keys.is_empty()
+    }
+    /// Return the key combination of the keys which are down, without
+    /// waiting for their release, and forget them.
+    ///
+    /// This is useful when a release may have been missed, for example
+    /// after a timeout.
+    pub fn flush(&mut self) -> Option<KeyCombination> {
+        let key_combination = self.combine(true);
+        self.finish(key_combination)
+    }
+    /// Receive a key event and return a key combination if one is ready.
+    ///
+    /// When combining is enabled, the key combination is only returned on a
+    /// key release event.
+    pub fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("crokey_transform", combining = self.combining).entered();
+        debug_event!(?key.code, ?key.modifiers, ?key.kind, "key event received");
+        let key = if self.keypad_distinction {
+            distinguish_keypad(key)
+        } else {
+            key
+        };
+        if
+                key.kind == KeyEventKind::Press
+                && self.down_keys.is_empty()
+                && !matches!(key.code, KeyCode::Modifier(_))
+        {
+            self.pressed_at = Some(self.clock.now());
+        }
+        if key.code == KeyCode::Null {
+            match self.null_key_policy {
+                NullKeyPolicy::Drop => {
+                    debug_event!("null key dropped");
+                    self.stats.null_keys += 1;
+                    return None;
+                }
+                NullKeyPolicy::Unknown => {
+                    return match key.kind {
+                        KeyEventKind::Release => None,
+                        _ => Some(KeyCombination::new(KeyCode::Null, KeyModifiers::NONE)),
+                    };
+                }
+                NullKeyPolicy::Pass => {}
+            }
+        }
+        let key_combination = if self.combining {
+            self.transform_combining(key)
+        } else {
+            self.transform_ansi(key)
+        };
+        self.finish(key_combination)
+    }
+    /// Apply the sticky modifiers and the policies to the key combination
+    /// built from the received events, if any
+    fn finish(&mut self, mut key_combination: Option<KeyCombination>) -> Option<KeyCombination> {
+        if self.sticky_modifiers {
+            key_combination = key_combination.map(|k| self.apply_sticky_modifiers(k));
+        }
+        if self.shift_policy != ShiftPolicy::Both {
+            key_combination = key_combination.map(|k| k.with_shift_policy(self.shift_policy));
+        }
+        key_combination = key_combination
+            .map(|k| k.with_shifted_symbol_policy(self.shifted_symbol_policy));
+        #[cfg(feature = "tracing")]
+        match key_combination {
+            Some(key_combination) => {
+                tracing::debug!(%key_combination, "key combination emitted");
+            }
+            None => {
+                tracing::debug!(
+                    down_keys = self.down_keys.len(),
+                    shift_pressed = self.shift_pressed,
+                    "no key combination yet",
+                );
+            }
+        }
+        key_combination
+    }
+    /// Receive a key event and return a key combination event if a key
+    /// combination is ready.
+    ///
+    /// The kind and state are the ones of the key event completing the
+    /// combination: when combining, it's a release for the combinations
+    /// which aren't returned on press.
+    ///
+    /// The event's `pressed_at` is the instant the first key of the
+    /// combination was pressed (for a repeat, the initial press).
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    /// };
+    /// let mut core = CombinerCore::default();
+    /// core.set_combining(true);
+    /// let a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+    /// assert_eq!(core.transform_event(a), None);
+    /// let event = core
+    ///     .transform_event(KeyEvent { kind: KeyEventKind::Release, ..a })
+    ///     .unwrap();
+    /// assert_eq!(event.combination, key!(ctrl-a));
+    /// assert!(event.pressed_at.is_some());
+    /// ```
+    pub fn transform_event(&mut self, key: KeyEvent) -> Option<KeyCombinationEvent> {
+        let combination = self.transform(key)?;
+        let pressed_at = if key.kind == KeyEventKind::Repeat {
+            self.pressed_at.or(self.last_pressed_at)
+        } else if self.down_keys.is_empty() {
+            self.pressed_at.take()
+        } else {
+            self.pressed_at
+        };
+        if key.kind != KeyEventKind::Repeat {
+            self.last_pressed_at = pressed_at;
+        }
+        let event = KeyCombinationEvent::new(combination, key.kind, key.state);
+        Some(match pressed_at {
+            Some(pressed_at) => event.with_pressed_at(pressed_at),
+            None => event,
+        })
+    }
+    /// Build the key combination the given one would produce if its keys
+    /// were pressed then released, as if it came from the terminal.
+    ///
+    /// The combination in progress, if any, isn't affected, so that
+    /// synthetic combinations may be injected at any time. Neither are
+    /// the sticky modifiers, which aren't added to the synthetic combination,
+    /// nor the [stats](CombinerCore::stats).
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut core = CombinerCore::default();
+    /// assert_eq!(core.simulate(key!(ctrl-a-b)), Some(key!(ctrl-a-b)));
+    /// assert_eq!(core.simulate(key!(backtab)), Some(key!(backtab)));
+    /// ```
+    pub fn simulate(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        let down_keys = std::mem::take(&mut self.down_keys);
+        let pressed_at = self.pressed_at;
+        let shift_pressed = std::mem::replace(&mut self.shift_pressed, false);
+        let combining = std::mem::replace(&mut self.combining, true);
+        let mandate = std::mem::replace(&mut self.mandate_modifier_for_multiple_keys, false);
+        // the sticky modifiers are kept for the next real key
+        let sticky_modifiers = std::mem::replace(&mut self.sticky_modifiers, false);
+        let tapped_modifier = self.tapped_modifier;
+        let stats = self.stats;
+        let event = |code, kind| KeyEvent {
+            code,
+            modifiers: key.modifiers,
+            kind,
+            state: KeyEventState::empty(),
+        };
+        let mut key_combination = None;
+        for &code in key.codes.iter() {
+            key_combination = self.transform(event(code, KeyEventKind::Press));
+        }
+        if key_combination.is_none() {
+            key_combination = self.transform(event(*key.codes.first(), KeyEventKind::Release));
+        }
+        self.down_keys = down_keys;
+        self.pressed_at = pressed_at;
+        self.shift_pressed = shift_pressed;
+        self.combining = combining;
+        self.mandate_modifier_for_multiple_keys = mandate;
+        self.sticky_modifiers = sticky_modifiers;
+        self.tapped_modifier = tapped_modifier;
+        self.stats = stats;
+        key_combination
+    }
+    fn transform_combining(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        if let KeyCode::Modifier(modifier) = key.code {
+            if modifier == ModifierKeyCode::LeftShift || modifier == ModifierKeyCode::RightShift {
+                self.shift_pressed = key.kind != KeyEventKind::Release;
+            }
+            if self.sticky_modifiers {
+                self.tap_modifier(modifier, key.kind);
+            }
+            // we ignore modifier keys as independent events
+            // (which means we never return a combination with only modifiers)
+            self.stats.modifier_only += 1;
+            return None;
+        }
+        self.tapped_modifier = None;
+        if
+                self.mandate_modifier_for_multiple_keys
+                && (self.is_key_simple)(key)
+                && !self.shift_pressed
+                && self.down_keys.is_empty()
+        {
+            // "simple key" are handled differently: they're returned on press and repeat
+            match key.kind {
+                KeyEventKind::Press | KeyEventKind::Repeat => {
+                    self.down_keys.push(key);
+                    self.combine(true)
+                }
+                KeyEventKind::Release => {
+                    None
+                }
+            }
+        } else {
+            // not a single simple key
+            match key.kind {
+                KeyEventKind::Press => {
+                    self.down_keys.push(key);
+                    if self.down_keys.len() == MAX_PRESS_COUNT {
+                        self.combine(true)
+                    } else {
+                        None
+                    }
+                }
+                KeyEventKind::Release => {
+                    // this release ends the combination in progress
+                    self.combine(true)
+                }
+                KeyEventKind::Repeat => {
+                    self.combine(false)
+                }
+            }
+        }
+    }
+    /// In ansi mode, no combination is possible, and we don't expect to
+    /// receive anything else than a single key or than key presses (or
+    /// repetitions, when a [RepeatDetector] is used).
+    fn transform_ansi(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        match key.kind {
+            KeyEventKind::Press | KeyEventKind::Repeat => Some(key.into()),
+            _ => {
+                // this is unexpected, we don't seem to be really in ansi mode
+                // but for consistency we must filter out this event
+                self.stats.unexpected_kinds += 1;
+                None
+            }
+        }
+    }
+}
+
+/// For the purpose of key combination, we consider that a key is "simple"
+/// when it's neither a modifier (ctrl,alt,shift) nor a space.
+pub fn is_key_simple(key: KeyEvent) -> bool {
+    key.modifiers.is_empty()
+        && key.code != KeyCode::Char(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::*,
+        crossterm::event::{
+            KeyCode,
+            KeyEvent,
+            KeyEventKind,
+            KeyEventState,
+            KeyModifiers,
+            ModifierKeyCode,
+        },
+        std::time::{
+            Duration,
+            Instant,
+        },
+    };
+
+    fn event(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::empty(),
+        }
+    }
+
+    #[test]
+    fn ansi_mode_ignores_releases() {
+        let mut core = CombinerCore::default();
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Press)), Some(key!(a)));
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Release)), None);
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Repeat)), Some(key!(a)));
+    }
+
+    #[test]
+    fn simple_keys_are_returned_on_press() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Press)), Some(key!(a)));
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Release)), None);
+    }
+
+    #[test]
+    fn simple_key_predicate_is_configurable() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Release)), Some(key!(space)));
+        core.set_simple_key_predicate(|key| key.modifiers.is_empty());
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Press)), Some(key!(space)));
+    }
+
+    #[test]
+    fn shift_is_combined() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_null_key_policy(NullKeyPolicy::Drop);
+        let shift = KeyCode::Modifier(ModifierKeyCode::LeftShift);
+        assert_eq!(core.transform(event(shift, KeyEventKind::Press)), None);
+        // while shift is down, the terminal reports it in the key events
+        let shifted = |code, kind| KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            ..event(code, kind)
+        };
+        assert_eq!(core.transform(shifted(KeyCode::Char('A'), KeyEventKind::Press)), None);
+        assert_eq!(core.transform(shifted(KeyCode::Char('B'), KeyEventKind::Press)), None);
+        assert_eq!(
+            core.transform(shifted(KeyCode::Char('B'), KeyEventKind::Release)),
+            Some(key!(shift-a-b)),
+        );
+    }
+
+    #[test]
+    fn shift_pressed_gives_normalized_combinations() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_null_key_policy(NullKeyPolicy::Drop);
+        let shift = KeyCode::Modifier(ModifierKeyCode::LeftShift);
+        assert_eq!(core.transform(event(shift, KeyEventKind::Press)), None);
+        // some terminals don't report shift in the key events
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(KeyCode::Char('b'), KeyEventKind::Press)), None);
+        let key = core.transform(event(KeyCode::Char('a'), KeyEventKind::Release)).unwrap();
+        key.assert_normalized();
+        assert_eq!(key, key!(shift-a-b));
+    }
+
+    #[test]
+    fn pending_keys_can_be_discarded() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        let shift = KeyCode::Modifier(ModifierKeyCode::LeftShift);
+        assert_eq!(core.transform(event(shift, KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Press)), None);
+        assert!(core.has_pending());
+        core.discard_pending();
+        assert!(!core.has_pending());
+        assert!(core.is_combining());
+        assert_eq!(core.transform(event(KeyCode::Char('b'), KeyEventKind::Press)), Some(key!(b)));
+    }
+
+    #[test]
+    fn simulate_keeps_sticky_modifiers() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_sticky_modifiers(true);
+        let ctrl = KeyCode::Modifier(ModifierKeyCode::LeftControl);
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Release)), None);
+        assert_eq!(core.latched_modifiers(), KeyModifiers::CONTROL);
+        let stats = core.stats();
+        assert_eq!(core.simulate(key!(a)), Some(key!(a)));
+        assert_eq!(core.latched_modifiers(), KeyModifiers::CONTROL);
+        assert_eq!(core.stats(), stats);
+        assert_eq!(core.transform(event(KeyCode::Char('c'), KeyEventKind::Press)), Some(key!(ctrl-c)));
+        // a modifier tapped before the simulation is still tapped after it
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Press)), None);
+        assert_eq!(core.simulate(key!(b)), Some(key!(b)));
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Release)), None);
+        assert_eq!(core.latched_modifiers(), KeyModifiers::CONTROL);
+    }
+
+    #[test]
+    fn shift_policy_is_applied() {
+        let mut core = CombinerCore::default();
+        core.set_shift_policy(ShiftPolicy::PreferUppercaseChar);
+        let shifted = KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            ..event(KeyCode::Char('A'), KeyEventKind::Press)
+        };
+        assert_eq!(
+            core.transform(shifted),
+            Some(KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE)),
+        );
+    }
+
+    #[test]
+    fn null_keys_follow_the_policy() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        let null = |kind| KeyEvent {
+            modifiers: KeyModifiers::ALT,
+            ..event(KeyCode::Null, kind)
+        };
+        assert_eq!(core.null_key_policy, NullKeyPolicy::Pass);
+        core.set_null_key_policy(NullKeyPolicy::Drop);
+        assert_eq!(core.transform(null(KeyEventKind::Press)), None);
+        assert_eq!(core.transform(null(KeyEventKind::Release)), None);
+        core.set_null_key_policy(NullKeyPolicy::Unknown);
+        assert_eq!(core.transform(null(KeyEventKind::Press)), Some(key!(unknown)));
+        assert_eq!(core.transform(null(KeyEventKind::Release)), None);
+        core.set_null_key_policy(NullKeyPolicy::Pass);
+        assert_eq!(core.transform(null(KeyEventKind::Press)), None);
+        assert_eq!(core.transform(null(KeyEventKind::Release)), Some(key!(alt-unknown)));
+        assert_eq!(KeyCombinationFormat::default().to_string(key!(unknown)), "Unknown");
+        assert_eq!(core.stats().null_keys, 2);
+    }
+
+    #[test]
+    fn filtered_out_events_are_counted() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_null_key_policy(NullKeyPolicy::Drop);
+        let shift = KeyCode::Modifier(ModifierKeyCode::LeftShift);
+        assert_eq!(core.transform(event(shift, KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(shift, KeyEventKind::Release)), None);
+        assert_eq!(core.transform(event(KeyCode::Null, KeyEventKind::Press)), None);
+        assert_eq!(
+            core.stats(),
+            CombinerStats { modifier_only: 2, unexpected_kinds: 0, null_keys: 1 },
+        );
+        core.set_combining(false);
+        assert_eq!(core.transform(event(KeyCode::Char('a'), KeyEventKind::Release)), None);
+        assert_eq!(core.stats().unexpected_kinds, 1);
+        assert_eq!(core.stats().total(), 4);
+        core.reset_stats();
+        assert_eq!(core.stats().total(), 0);
+    }
+
+    #[test]
+    fn sticky_modifiers_are_latched_and_locked() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_sticky_modifiers(true);
+        let tap = |core: &mut CombinerCore, modifier| {
+            assert_eq!(core.transform(event(KeyCode::Modifier(modifier), KeyEventKind::Press)), None);
+            assert_eq!(core.transform(event(KeyCode::Modifier(modifier), KeyEventKind::Release)), None);
+        };
+        let hit = |core: &mut CombinerCore, c| {
+            core.transform(event(KeyCode::Char(c), KeyEventKind::Press))
+        };
+        tap(&mut core, ModifierKeyCode::LeftControl);
+        assert_eq!(core.latched_modifiers(), KeyModifiers::CONTROL);
+        assert_eq!(hit(&mut core, 'c'), Some(key!(ctrl-c)));
+        assert_eq!(hit(&mut core, 'c'), Some(key!(c)));
+        tap(&mut core, ModifierKeyCode::LeftShift);
+        assert_eq!(hit(&mut core, 'a'), Some(key!(shift-a)));
+        // double tap to lock
+        tap(&mut core, ModifierKeyCode::LeftAlt);
+        tap(&mut core, ModifierKeyCode::RightAlt);
+        assert_eq!(core.locked_modifiers(), KeyModifiers::ALT);
+        assert_eq!(hit(&mut core, 'x'), Some(key!(alt-x)));
+        assert_eq!(hit(&mut core, 'y'), Some(key!(alt-y)));
+        tap(&mut core, ModifierKeyCode::LeftAlt);
+        assert_eq!(hit(&mut core, 'z'), Some(key!(z)));
+        // a modifier held with another key isn't latched
+        let ctrl = KeyCode::Modifier(ModifierKeyCode::LeftControl);
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Press)), None);
+        let ctrl_v = KeyEvent {
+            modifiers: KeyModifiers::CONTROL,
+            ..event(KeyCode::Char('v'), KeyEventKind::Press)
+        };
+        assert_eq!(core.transform(ctrl_v), None);
+        assert_eq!(core.transform(KeyEvent { kind: KeyEventKind::Release, ..ctrl_v }), Some(key!(ctrl-v)));
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Release)), None);
+        assert_eq!(core.latched_modifiers(), KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn transform_event_keeps_the_kind() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        let a = |kind| event(KeyCode::Char('a'), kind);
+        let key_event = core.transform_event(a(KeyEventKind::Press)).unwrap();
+        assert_eq!(key_event.combination, key!(a));
+        assert!(key_event.is_press());
+        assert!(core.transform_event(a(KeyEventKind::Repeat)).unwrap().is_repeat());
+        assert_eq!(core.transform_event(a(KeyEventKind::Release)), None);
+    }
+
+    #[test]
+    fn transform_event_gives_the_first_press_instant() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_mandate_modifier_for_multiple_keys(false);
+        let before = Instant::now();
+        assert_eq!(core.transform_event(event(KeyCode::Char('a'), KeyEventKind::Press)), None);
+        let between = Instant::now();
+        assert_eq!(core.transform_event(event(KeyCode::Char('b'), KeyEventKind::Press)), None);
+        let key_event = core
+            .transform_event(event(KeyCode::Char('b'), KeyEventKind::Release))
+            .unwrap();
+        assert_eq!(key_event.combination, key!(a-b));
+        let pressed_at = key_event.pressed_at.unwrap();
+        assert!(before <= pressed_at && pressed_at <= between);
+        // the next combination has its own instant
+        let after = Instant::now();
+        core.transform_event(event(KeyCode::Char('c'), KeyEventKind::Press));
+        let key_event = core
+            .transform_event(event(KeyCode::Char('c'), KeyEventKind::Release))
+            .unwrap();
+        assert!(key_event.pressed_at.unwrap() >= after);
+    }
+
+    #[test]
+    fn pressed_at_is_given_by_the_clock() {
+        let clock = testing::MockClock::new();
+        let start = clock.now();
+        let mut core = CombinerCore::default();
+        core.set_clock(clock.clone());
+        core.set_combining(true);
+        core.transform_event(event(KeyCode::Char(' '), KeyEventKind::Press));
+        clock.advance(Duration::from_millis(250));
+        let key_event = core
+            .transform_event(event(KeyCode::Char(' '), KeyEventKind::Release))
+            .unwrap();
+        assert_eq!(key_event.pressed_at, Some(start));
+    }
+
+    #[test]
+    fn repeats_give_the_press_instant() {
+        let clock = testing::MockClock::new();
+        let start = clock.now();
+        let mut core = CombinerCore::default();
+        core.set_clock(clock.clone());
+        let press = core.transform_event(event(KeyCode::Char('a'), KeyEventKind::Press));
+        assert_eq!(press.unwrap().pressed_at, Some(start));
+        clock.advance(Duration::from_millis(500));
+        let repeat = core.transform_event(event(KeyCode::Char('a'), KeyEventKind::Repeat));
+        assert_eq!(repeat.unwrap().pressed_at, Some(start));
+        let repeat = core.transform_event(event(KeyCode::Char('a'), KeyEventKind::Repeat));
+        assert_eq!(repeat.unwrap().pressed_at, Some(start));
+    }
+
+    #[test]
+    fn flush_returns_the_pending_combination() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        assert_eq!(core.flush(), None);
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Press)), None);
+        assert!(core.has_pending());
+        assert_eq!(core.flush(), Some(key!(space)));
+        assert!(!core.has_pending());
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Release)), None);
+    }
+
+    #[test]
+    fn flush_applies_the_sticky_modifiers_and_policies() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        core.set_sticky_modifiers(true);
+        core.set_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift);
+        let ctrl = KeyCode::Modifier(ModifierKeyCode::LeftControl);
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Press)), None);
+        assert_eq!(core.transform(event(ctrl, KeyEventKind::Release)), None);
+        assert_eq!(core.transform(event(KeyCode::Char(' '), KeyEventKind::Press)), None);
+        assert_eq!(core.flush(), Some(key!(ctrl-space)));
+        assert_eq!(core.latched_modifiers(), KeyModifiers::NONE);
+        let question_mark = KeyEvent {
+            modifiers: KeyModifiers::SHIFT,
+            ..event(KeyCode::Char('?'), KeyEventKind::Press)
+        };
+        assert_eq!(core.transform(question_mark), None);
+        assert_eq!(core.flush(), Some(key!('?')));
+    }
+
+    #[test]
+    fn simulate_keeps_combination_in_progress() {
+        let mut core = CombinerCore::default();
+        core.set_combining(true);
+        let ctrl = |code, kind| KeyEvent {
+            modifiers: KeyModifiers::CONTROL,
+            ..event(code, kind)
+        };
+        assert_eq!(core.transform(ctrl(KeyCode::Char('x'), KeyEventKind::Press)), None);
+        assert_eq!(core.simulate(key!(a)), Some(key!(a)));
+        assert_eq!(core.simulate(key!(alt-b-c)), Some(key!(alt-b-c)));
+        assert!(core.is_combining());
+        assert_eq!(
+            core.transform(ctrl(KeyCode::Char('x'), KeyEventKind::Release)),
+            Some(key!(ctrl-x)),
+        );
+    }
+}
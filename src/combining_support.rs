@@ -0,0 +1,121 @@
+use {
+    crossterm::event::KeyboardEnhancementFlags,
+    std::{
+        env,
+        fmt,
+        fs::File,
+        io,
+    },
+};
+
+/// The reason why combining couldn't be enabled
+#[derive(Debug)]
+pub enum CombiningUnavailability {
+    /// stdout isn't a terminal (e.g. it's redirected to a file)
+    NotATerminal,
+    /// The terminal answered it doesn't support the kitty keyboard protocol
+    Unsupported,
+    /// The terminal inside a multiplexer (whose name is given) answered
+    /// it doesn't support the kitty keyboard protocol, maybe because the
+    /// multiplexer doesn't forward it
+    Multiplexer(&'static str),
+    /// The terminal didn't answer the support query in time
+    Timeout,
+    /// Querying the terminal or pushing the flags failed
+    Io(io::Error),
+}
+
+impl fmt::Display for CombiningUnavailability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotATerminal => write!(f, "stdout isn't a terminal"),
+            Self::Unsupported => write!(f, "the terminal doesn't support the kitty keyboard protocol"),
+            Self::Multiplexer(name) => write!(
+                f,
+                "the kitty keyboard protocol isn't available through {}, try outside of it",
+                name,
+            ),
+            Self::Timeout => write!(f, "the terminal didn't tell whether it supports the kitty keyboard protocol"),
+            Self::Io(e) => write!(f, "terminal error: {}", e),
+        }
+    }
+}
+
+/// What [Combiner::enable_combining_with_report](crate::Combiner::enable_combining_with_report)
+/// did, and why combining isn't enabled when it's not, so that applications
+/// may show actionable messages.
+#[derive(Debug)]
+pub struct CombiningSupport {
+    /// Whether combining is enabled
+    pub enabled: bool,
+    /// The keyboard enhancement flags pushed to the terminal, if any
+    pub pushed_flags: Option<KeyboardEnhancementFlags>,
+    /// Whether combining relies on release events reported natively
+    /// by the platform (the Windows console)
+    pub native_release_events: bool,
+    /// Whether the flags are managed by the application
+    pub externally_managed: bool,
+    /// Why combining isn't enabled, when it's not
+    pub unavailability: Option<CombiningUnavailability>,
+}
+
+impl CombiningSupport {
+    pub(crate) fn enabled() -> Self {
+        Self {
+            enabled: true,
+            pushed_flags: None,
+            native_release_events: false,
+            externally_managed: false,
+            unavailability: None,
+        }
+    }
+    pub(crate) fn unavailable(unavailability: CombiningUnavailability) -> Self {
+        Self {
+            enabled: false,
+            unavailability: Some(unavailability),
+            ..Self::enabled()
+        }
+    }
+}
+
+/// Tell whether the tty, or stdout, is a terminal. Always true on non unix platforms.
+pub(crate) fn is_a_terminal(tty: Option<&File>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        let fd = tty.map_or(libc::STDOUT_FILENO, |tty| tty.as_raw_fd());
+        unsafe { libc::isatty(fd) == 1 }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tty;
+        true
+    }
+}
+
+/// Return the name of the terminal multiplexer we're running in, if any
+pub(crate) fn multiplexer() -> Option<&'static str> {
+    if env::var_os("TMUX").is_some() {
+        Some("tmux")
+    } else if env::var_os("STY").is_some() {
+        Some("GNU screen")
+    } else if env::var_os("ZELLIJ").is_some() {
+        Some("zellij")
+    } else {
+        None
+    }
+}
+
+/// The error of a terminal not answering the keyboard enhancement query in time
+pub(crate) fn query_timeout() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        "the terminal didn't answer the keyboard enhancement query in time",
+    )
+}
+
+/// Tell whether the error is the query of the keyboard enhancement
+/// support giving up waiting for the answer of the terminal
+pub(crate) fn is_query_timeout(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::TimedOut
+}
@@ -0,0 +1,170 @@
+//! Parsing with the syntax of previous crokey versions, so that
+//! applications may migrate the configurations of their users
+//! deliberately instead of silently changing their meaning.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+};
+
+/// A version of the key syntax, named after the crokey version
+/// which introduced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SyntaxVersion {
+    /// The syntax of crokey 1.1, which differs from the current one in that
+    /// - single chars must be ASCII, so `é` is rejected
+    /// - shifted chars are only uppercased when ASCII
+    /// - there's no `super` modifier, no modifier synonym (e.g. `command`)
+    ///   and no platform modifier (`primary`, `secondary`)
+    /// - the key names added since (e.g. `return`, `cr`, `pgup`, `kp5`,
+    ///   `capslock`, `unknown`) and the `0x` code points are rejected
+    /// - a raw line break (`"\r"` or `"\n"`) is a char, not Enter
+    V1_1,
+    /// The syntax of [parse]
+    Current,
+}
+
+/// Parse a key combination exactly as the given version of crokey did.
+///
+/// ```
+/// use crokey::*;
+/// assert!(parse_compat(SyntaxVersion::V1_1, "ctrl-é").is_err());
+/// assert_eq!(parse_compat(SyntaxVersion::Current, "ctrl-é").unwrap(), key!(ctrl-'é'));
+/// assert!(parse_compat(SyntaxVersion::V1_1, "return").is_err());
+/// assert_eq!(parse_compat(SyntaxVersion::Current, "return").unwrap(), key!(enter));
+/// // the syntax which didn't change gives the same keys
+/// assert_eq!(parse_compat(SyntaxVersion::V1_1, "ctrl-Hyphen").unwrap(), key!(ctrl-'-'));
+/// assert_eq!(parse_compat(SyntaxVersion::V1_1, "F2").unwrap(), key!(f2));
+/// ```
+pub fn parse_compat(version: SyntaxVersion, raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    match version {
+        SyntaxVersion::V1_1 => parse_v1_1(raw),
+        SyntaxVersion::Current => parse(raw),
+    }
+}
+
+/// Tell whether the key is read differently by the current syntax than by
+/// the given version, so that the user may be warned about it.
+///
+/// A key which is invalid in both versions hasn't changed.
+///
+/// ```
+/// use crokey::*;
+/// assert!(has_changed_since(SyntaxVersion::V1_1, "return"));
+/// assert!(has_changed_since(SyntaxVersion::V1_1, "ctrl-é"));
+/// assert!(!has_changed_since(SyntaxVersion::V1_1, "ctrl-x"));
+/// assert!(!has_changed_since(SyntaxVersion::V1_1, "ctrl-nothing"));
+/// ```
+pub fn has_changed_since(version: SyntaxVersion, raw: &str) -> bool {
+    match (parse_compat(version, raw), parse(raw)) {
+        (Ok(old), Ok(new)) => old != new,
+        (Err(_), Err(_)) => false,
+        _ => true,
+    }
+}
+
+// kept identical to the parse_key_code of crokey 1.1
+fn parse_key_code_v1_1(raw: &str, shift: bool) -> Result<KeyCode, ParseKeyError> {
+    let code = match raw {
+        "esc" => Esc,
+        "enter" => Enter,
+        "left" => Left,
+        "right" => Right,
+        "up" => Up,
+        "down" => Down,
+        "home" => Home,
+        "end" => End,
+        "pageup" => PageUp,
+        "pagedown" => PageDown,
+        "backtab" => BackTab,
+        "backspace" => Backspace,
+        "del" => Delete,
+        "delete" => Delete,
+        "insert" => Insert,
+        "ins" => Insert,
+        "f1" => F(1),
+        "f2" => F(2),
+        "f3" => F(3),
+        "f4" => F(4),
+        "f5" => F(5),
+        "f6" => F(6),
+        "f7" => F(7),
+        "f8" => F(8),
+        "f9" => F(9),
+        "f10" => F(10),
+        "f11" => F(11),
+        "f12" => F(12),
+        "space" => Char(' '),
+        "hyphen" => Char('-'),
+        "minus" => Char('-'),
+        "tab" => Tab,
+        c if c.len() == 1 => {
+            let mut c = c.chars().next().unwrap();
+            if shift {
+                c = c.to_ascii_uppercase();
+            }
+            Char(c)
+        }
+        _ => {
+            return Err(ParseKeyError::new(raw));
+        }
+    };
+    Ok(code)
+}
+
+// kept identical to the parse of crokey 1.1
+fn parse_v1_1(raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    let mut modifiers = KeyModifiers::empty();
+    let raw = raw.to_ascii_lowercase();
+    let mut raw: &str = raw.as_ref();
+    loop {
+        if let Some(end) = raw.strip_prefix("ctrl-") {
+            raw = end;
+            modifiers.insert(KeyModifiers::CONTROL);
+        } else if let Some(end) = raw.strip_prefix("alt-") {
+            raw = end;
+            modifiers.insert(KeyModifiers::ALT);
+        } else if let Some(end) = raw.strip_prefix("shift-") {
+            raw = end;
+            modifiers.insert(KeyModifiers::SHIFT);
+        } else {
+            break;
+        }
+    }
+    let codes = if raw == "-" {
+        OneToThree::One(Char('-'))
+    } else {
+        let mut codes = Vec::new();
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        for raw in raw.split('-') {
+            let code = parse_key_code_v1_1(raw, shift)?;
+            if code == BackTab {
+                // Crossterm always sends SHIFT with backtab
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+            codes.push(code);
+        }
+        codes.try_into().map_err(|_| ParseKeyError::new("".to_string()))?
+    };
+    Ok(KeyCombination::new(codes, modifiers))
+}
+
+#[test]
+fn check_v1_1_syntax() {
+    let v1_1 = |raw| parse_compat(SyntaxVersion::V1_1, raw);
+    for raw in [
+        "é", "ctrl-é", "unknown", "cr", "lf", "0xe9", "super-s", "command-c", "primary-s",
+        "pgup", "kp5", "capslock",
+    ] {
+        assert!(v1_1(raw).is_err(), "{:?} was invalid in 1.1", raw);
+        assert!(parse(raw).is_ok(), "{:?} is valid now", raw);
+    }
+    for raw in ["ctrl-q", "shift-q", "alt-f12-@", "-", "alt--", "backtab", "a-b-c", "ctrl-Shift-alt-space"] {
+        assert_eq!(v1_1(raw).unwrap(), parse(raw).unwrap(), "{:?} didn't change", raw);
+    }
+    // a raw line break was a char, not Enter
+    assert!(has_changed_since(SyntaxVersion::V1_1, "\r"));
+}
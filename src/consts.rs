@@ -0,0 +1,114 @@
+//! Constants for the most common key combinations, for the code which
+//! can't or doesn't want to use the `key!` macro.
+//!
+//! Those constants can be compared with `==`, used in match guards, or put
+//! in const arrays:
+//!
+//! ```
+//! use crokey::{*, consts::*};
+//! const QUIT_KEYS: [KeyCombination; 2] = [CTRL_C, CTRL_Q];
+//! let key = parse("ctrl-c").unwrap();
+//! let action = match key {
+//!     k if QUIT_KEYS.contains(&k) => "quit",
+//!     k if k == F1 => "help",
+//!     _ => "nothing",
+//! };
+//! assert_eq!(action, "quit");
+//! ```
+//!
+//! They can't be used as patterns (`CTRL_C => ...`) because the type of the
+//! codes, `OneToThree`, doesn't derive `PartialEq`. Use `key!` in patterns.
+//!
+//! Like the `key!` macro, those constants are normalized: compare them
+//! with normalized combinations, e.g. the ones produced by a [Combiner](crate::Combiner).
+
+use crate::{
+    key,
+    KeyCombination,
+};
+
+// keys without modifier
+pub const ENTER: KeyCombination = key!(enter);
+pub const ESC: KeyCombination = key!(esc);
+pub const TAB: KeyCombination = key!(tab);
+pub const BACKTAB: KeyCombination = key!(backtab);
+pub const BACKSPACE: KeyCombination = key!(backspace);
+pub const DELETE: KeyCombination = key!(delete);
+pub const INSERT: KeyCombination = key!(insert);
+pub const HOME: KeyCombination = key!(home);
+pub const END: KeyCombination = key!(end);
+pub const PAGE_UP: KeyCombination = key!(pageup);
+pub const PAGE_DOWN: KeyCombination = key!(pagedown);
+pub const UP: KeyCombination = key!(up);
+pub const DOWN: KeyCombination = key!(down);
+pub const LEFT: KeyCombination = key!(left);
+pub const RIGHT: KeyCombination = key!(right);
+pub const SPACE: KeyCombination = key!(space);
+pub const F1: KeyCombination = key!(f1);
+pub const F2: KeyCombination = key!(f2);
+pub const F3: KeyCombination = key!(f3);
+pub const F4: KeyCombination = key!(f4);
+pub const F5: KeyCombination = key!(f5);
+pub const F6: KeyCombination = key!(f6);
+pub const F7: KeyCombination = key!(f7);
+pub const F8: KeyCombination = key!(f8);
+pub const F9: KeyCombination = key!(f9);
+pub const F10: KeyCombination = key!(f10);
+pub const F11: KeyCombination = key!(f11);
+pub const F12: KeyCombination = key!(f12);
+
+// ctrl and a letter
+pub const CTRL_A: KeyCombination = key!(ctrl-a);
+pub const CTRL_B: KeyCombination = key!(ctrl-b);
+pub const CTRL_C: KeyCombination = key!(ctrl-c);
+pub const CTRL_D: KeyCombination = key!(ctrl-d);
+pub const CTRL_E: KeyCombination = key!(ctrl-e);
+pub const CTRL_F: KeyCombination = key!(ctrl-f);
+pub const CTRL_G: KeyCombination = key!(ctrl-g);
+pub const CTRL_H: KeyCombination = key!(ctrl-h);
+pub const CTRL_I: KeyCombination = key!(ctrl-i);
+pub const CTRL_J: KeyCombination = key!(ctrl-j);
+pub const CTRL_K: KeyCombination = key!(ctrl-k);
+pub const CTRL_L: KeyCombination = key!(ctrl-l);
+pub const CTRL_M: KeyCombination = key!(ctrl-m);
+pub const CTRL_N: KeyCombination = key!(ctrl-n);
+pub const CTRL_O: KeyCombination = key!(ctrl-o);
+pub const CTRL_P: KeyCombination = key!(ctrl-p);
+pub const CTRL_Q: KeyCombination = key!(ctrl-q);
+pub const CTRL_R: KeyCombination = key!(ctrl-r);
+pub const CTRL_S: KeyCombination = key!(ctrl-s);
+pub const CTRL_T: KeyCombination = key!(ctrl-t);
+pub const CTRL_U: KeyCombination = key!(ctrl-u);
+pub const CTRL_V: KeyCombination = key!(ctrl-v);
+pub const CTRL_W: KeyCombination = key!(ctrl-w);
+pub const CTRL_X: KeyCombination = key!(ctrl-x);
+pub const CTRL_Y: KeyCombination = key!(ctrl-y);
+pub const CTRL_Z: KeyCombination = key!(ctrl-z);
+
+// other common combinations
+pub const CTRL_SPACE: KeyCombination = key!(ctrl-space);
+pub const CTRL_ENTER: KeyCombination = key!(ctrl-enter);
+pub const ALT_ENTER: KeyCombination = key!(alt-enter);
+pub const SHIFT_ENTER: KeyCombination = key!(shift-enter);
+pub const CTRL_UP: KeyCombination = key!(ctrl-up);
+pub const CTRL_DOWN: KeyCombination = key!(ctrl-down);
+pub const CTRL_LEFT: KeyCombination = key!(ctrl-left);
+pub const CTRL_RIGHT: KeyCombination = key!(ctrl-right);
+pub const CTRL_HOME: KeyCombination = key!(ctrl-home);
+pub const CTRL_END: KeyCombination = key!(ctrl-end);
+pub const SHIFT_UP: KeyCombination = key!(shift-up);
+pub const SHIFT_DOWN: KeyCombination = key!(shift-down);
+pub const SHIFT_LEFT: KeyCombination = key!(shift-left);
+pub const SHIFT_RIGHT: KeyCombination = key!(shift-right);
+pub const ALT_LEFT: KeyCombination = key!(alt-left);
+pub const ALT_RIGHT: KeyCombination = key!(alt-right);
+
+#[test]
+fn check_consts() {
+    use crate::parse;
+    for (key, raw) in [(CTRL_C, "ctrl-c"), (BACKTAB, "backtab"), (F12, "F12"), (SHIFT_ENTER, "shift-enter")] {
+        assert_eq!(key, parse(raw).unwrap());
+    }
+    assert_ne!(parse("ctrl-shift-z").unwrap(), CTRL_Z);
+    assert_ne!(CTRL_I, TAB);
+}
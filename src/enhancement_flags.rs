@@ -0,0 +1,176 @@
+use {
+    crossterm::event::KeyboardEnhancementFlags,
+    std::{
+        fmt,
+        str::FromStr,
+    },
+};
+
+#[cfg(feature = "serde")]
+use {
+    crate::key_combination::FromStrVisitor,
+    serde::{
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+};
+
+/// The keyboard enhancement flags pushed by the [Combiner](crate::Combiner)
+pub const KEYBOARD_ENHANCEMENT_FLAGS: KeyboardEnhancementFlags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+    .union(KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES)
+    .union(KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS)
+    .union(KeyboardEnhancementFlags::REPORT_EVENT_TYPES);
+
+/// The names of the keyboard enhancement flags, as read and written
+/// by [EnhancementFlags]
+pub const ENHANCEMENT_FLAG_NAMES: &[(&str, KeyboardEnhancementFlags)] = &[
+    ("disambiguate", KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
+    ("report-event-types", KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
+    ("report-alternate-keys", KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS),
+    ("report-all-keys", KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES),
+];
+
+/// A set of keyboard enhancement flags (kitty protocol) which can be read
+/// from and written to a configuration file, as a comma separated list of
+/// [names](ENHANCEMENT_FLAG_NAMES).
+///
+/// The default value is the set of flags pushed by the [Combiner](crate::Combiner).
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::KeyboardEnhancementFlags,
+/// };
+/// let flags: EnhancementFlags = "disambiguate, report-event-types".parse().unwrap();
+/// assert_eq!(
+///     KeyboardEnhancementFlags::from(flags),
+///     KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+///         | KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+/// );
+/// assert_eq!(flags.to_string(), "disambiguate, report-event-types");
+/// assert_eq!(EnhancementFlags::default().0, KEYBOARD_ENHANCEMENT_FLAGS);
+/// assert!("report-everything".parse::<EnhancementFlags>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnhancementFlags(pub KeyboardEnhancementFlags);
+
+/// Error returned when a string can't be parsed as [EnhancementFlags]
+#[derive(Debug)]
+pub struct ParseEnhancementFlagsError {
+    /// the flag name which isn't known
+    pub raw: String,
+}
+
+impl fmt::Display for ParseEnhancementFlagsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} isn't a keyboard enhancement flag", self.raw)
+    }
+}
+
+impl std::error::Error for ParseEnhancementFlagsError {}
+
+impl Default for EnhancementFlags {
+    fn default() -> Self {
+        Self(KEYBOARD_ENHANCEMENT_FLAGS)
+    }
+}
+
+impl From<KeyboardEnhancementFlags> for EnhancementFlags {
+    fn from(flags: KeyboardEnhancementFlags) -> Self {
+        Self(flags)
+    }
+}
+
+impl From<EnhancementFlags> for KeyboardEnhancementFlags {
+    fn from(flags: EnhancementFlags) -> Self {
+        flags.0
+    }
+}
+
+/// Parse flag names separated by `,` or `|`, like
+/// `"disambiguate, report-all-keys"`. Names are case insensitive and an
+/// empty string (or `"none"`) gives no flag.
+impl FromStr for EnhancementFlags {
+    type Err = ParseEnhancementFlagsError;
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut flags = KeyboardEnhancementFlags::empty();
+        let raw = raw.trim();
+        if raw.is_empty() || raw.eq_ignore_ascii_case("none") {
+            return Ok(Self(flags));
+        }
+        for name in raw.split([',', '|']) {
+            let name = name.trim();
+            let flag = ENHANCEMENT_FLAG_NAMES
+                .iter()
+                .find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(name))
+                .map(|(_, flag)| *flag)
+                .ok_or_else(|| ParseEnhancementFlagsError { raw: name.to_string() })?;
+            flags |= flag;
+        }
+        Ok(Self(flags))
+    }
+}
+
+/// Write the flags the way [FromStr] reads them, like
+/// `"disambiguate, report-all-keys"`, or `"none"`
+impl fmt::Display for EnhancementFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("none");
+        }
+        let mut first = true;
+        for (name, flag) in ENHANCEMENT_FLAG_NAMES {
+            if self.0.contains(*flag) {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EnhancementFlags {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FromStrVisitor::new("keyboard enhancement flags"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EnhancementFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[test]
+fn check_enhancement_flags_parsing() {
+    let parse = |raw: &str| raw.parse::<EnhancementFlags>().map(|flags| flags.0);
+    assert_eq!(parse("").unwrap(), KeyboardEnhancementFlags::empty());
+    assert_eq!(parse("None").unwrap(), KeyboardEnhancementFlags::empty());
+    assert_eq!(
+        parse("Report-All-Keys|disambiguate").unwrap(),
+        KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
+            | KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+    );
+    assert_eq!(parse("disambiguate,").unwrap_err().raw, "");
+    assert_eq!(parse("disambiguate, kitty").unwrap_err().raw, "kitty");
+    for flags in [
+        EnhancementFlags::default(),
+        EnhancementFlags(KeyboardEnhancementFlags::empty()),
+        EnhancementFlags(KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS),
+    ] {
+        assert_eq!(flags.to_string().parse::<EnhancementFlags>().unwrap(), flags);
+    }
+}
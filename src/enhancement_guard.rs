@@ -0,0 +1,242 @@
+use {
+    crate::*,
+    crossterm::{
+        event::{
+            KeyboardEnhancementFlags,
+            PushKeyboardEnhancementFlags,
+        },
+        execute,
+    },
+    once_cell::sync::Lazy,
+    std::{
+        fs::File,
+        io,
+        sync::Mutex,
+    },
+};
+
+/// The device of a terminal, so that the flags pushed through different
+/// handles of the same terminal are counted together (`None` when it can't
+/// be known, e.g. on Windows)
+type TerminalId = Option<u64>;
+
+/// Number of live guards per terminal, i.e. of users of the pushed flags
+static GUARD_COUNTS: Lazy<Mutex<Vec<(TerminalId, usize)>>> = Lazy::new(Default::default);
+
+/// A shared claim on the keyboard enhancement flags of a terminal.
+///
+/// The flags are pushed when the first guard of the terminal is acquired and
+/// popped when its last one is released, so that several combiners (or other
+/// users of the flags) can't desynchronize the flag stack of the terminal by
+/// popping flags another one still needs.
+///
+/// Each terminal has its own flag stack: guards on different terminals
+/// (see [KeyboardEnhancementGuard::acquire_on]) don't interfere.
+///
+/// [Combiner] uses such a guard, you only need this type when you
+/// otherwise need the flags.
+#[derive(Debug)]
+pub struct KeyboardEnhancementGuard {
+    released: bool,
+    /// where the flags are pushed, stdout when `None`
+    tty: Option<File>,
+    terminal: TerminalId,
+}
+
+impl KeyboardEnhancementGuard {
+    /// Take a claim on the flags of the terminal of stdout, pushing them
+    /// if there was no other one
+    pub fn acquire() -> io::Result<Self> {
+        Self::acquire_with(None, KEYBOARD_ENHANCEMENT_FLAGS)
+    }
+    /// Take a claim on the flags of the given terminal, e.g. an opened
+    /// `/dev/pts/N`, pushing them if there was no other one
+    pub fn acquire_on(tty: &File) -> io::Result<Self> {
+        Self::acquire_with(Some(tty.try_clone()?), KEYBOARD_ENHANCEMENT_FLAGS)
+    }
+    /// Take a claim on the flags of the given terminal, or of the one of
+    /// stdout, pushing the given flags if there was no other claim.
+    ///
+    /// When the terminal already has a live guard, the flags it pushed are
+    /// kept and the given ones are ignored.
+    pub fn acquire_with_flags(
+        tty: Option<&File>,
+        flags: KeyboardEnhancementFlags,
+    ) -> io::Result<Self> {
+        let tty = match tty {
+            Some(tty) => Some(tty.try_clone()?),
+            None => None,
+        };
+        Self::acquire_with(tty, flags)
+    }
+    fn acquire_with(tty: Option<File>, flags: KeyboardEnhancementFlags) -> io::Result<Self> {
+        let terminal = terminal_id(tty.as_ref());
+        if increment(terminal) == 0 {
+            if let Err(e) = push_flags(tty.as_ref(), flags) {
+                decrement(terminal);
+                return Err(e);
+            }
+            debug_event!(?terminal, "keyboard enhancement flags pushed");
+        }
+        Ok(Self {
+            released: false,
+            tty,
+            terminal,
+        })
+    }
+    /// Return the number of live guards on the terminal of stdout
+    pub fn count() -> usize {
+        count_of(terminal_id(None))
+    }
+    /// Return the number of live guards on the given terminal
+    pub fn count_on(tty: &File) -> usize {
+        count_of(terminal_id(Some(tty)))
+    }
+    /// Release the claim, popping the flags if it was the last one.
+    ///
+    /// Dropping the guard does the same but ignores errors.
+    pub fn release(mut self) -> io::Result<()> {
+        self.released = true;
+        self.release_claim()
+    }
+    fn release_claim(&self) -> io::Result<()> {
+        if decrement(self.terminal) == 0 {
+            pop_flags(self.tty.as_ref())?;
+            debug_event!(terminal = ?self.terminal, "keyboard enhancement flags popped");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for KeyboardEnhancementGuard {
+    fn drop(&mut self) {
+        if !self.released {
+            self.released = true;
+            let _ = self.release_claim();
+        }
+    }
+}
+
+/// Push the keyboard enhancement flags to the tty, or to stdout
+pub(crate) fn push_flags(tty: Option<&File>, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+    match tty {
+        Some(mut tty) => execute!(tty, PushKeyboardEnhancementFlags(flags)),
+        None => execute!(io::stdout(), PushKeyboardEnhancementFlags(flags)),
+    }
+}
+
+/// Pop the keyboard enhancement flags from the tty, or from stdout
+pub(crate) fn pop_flags(tty: Option<&File>) -> io::Result<()> {
+    match tty {
+        Some(tty) => pop_keyboard_enhancement_flags_from(tty),
+        None => pop_keyboard_enhancement_flags(),
+    }
+}
+
+/// Increment the count of guards of the terminal, returning the previous one
+fn increment(terminal: TerminalId) -> usize {
+    let mut counts = GUARD_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    match counts.iter_mut().find(|(t, _)| *t == terminal) {
+        Some((_, count)) => {
+            *count += 1;
+            *count - 1
+        }
+        None => {
+            counts.push((terminal, 1));
+            0
+        }
+    }
+}
+
+/// Decrement the count of guards of the terminal, returning the new one
+fn decrement(terminal: TerminalId) -> usize {
+    let mut counts = GUARD_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    let idx = match counts.iter().position(|(t, _)| *t == terminal) {
+        Some(idx) => idx,
+        None => return 0,
+    };
+    counts[idx].1 -= 1;
+    let count = counts[idx].1;
+    if count == 0 {
+        counts.swap_remove(idx);
+    }
+    count
+}
+
+fn count_of(terminal: TerminalId) -> usize {
+    let counts = GUARD_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    counts
+        .iter()
+        .find(|(t, _)| *t == terminal)
+        .map_or(0, |(_, count)| *count)
+}
+
+/// Return the device of the tty, or of stdout
+fn terminal_id(tty: Option<&File>) -> TerminalId {
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        let fd = tty.map_or(libc::STDOUT_FILENO, |tty| tty.as_raw_fd());
+        let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+        // SAFETY: fstat fully initializes the struct when it succeeds
+        let stat = unsafe {
+            if libc::fstat(fd, stat.as_mut_ptr()) != 0 {
+                return None;
+            }
+            stat.assume_init()
+        };
+        if stat.st_mode & libc::S_IFMT != libc::S_IFCHR {
+            return None;
+        }
+        Some(stat.st_rdev as u64)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tty;
+        None
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn check_guard_count() {
+    // the flags are pushed to /dev/null, a character device, so that
+    // nothing is written to the terminal running the tests
+    let null = File::options().write(true).open("/dev/null").unwrap();
+    let before = KeyboardEnhancementGuard::count_on(&null);
+    let guard = KeyboardEnhancementGuard::acquire_on(&null).unwrap();
+    assert_eq!(KeyboardEnhancementGuard::count_on(&null), before + 1);
+    drop(guard);
+    assert_eq!(KeyboardEnhancementGuard::count_on(&null), before);
+}
+
+#[cfg(unix)]
+#[test]
+fn check_guard_count_per_terminal() {
+    use std::{io::Read, os::unix::io::FromRawFd};
+    let (mut master, mut slave) = (-1, -1);
+    let res = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(res, 0);
+    let mut master = unsafe { File::from_raw_fd(master) };
+    let slave = unsafe { File::from_raw_fd(slave) };
+    let on_stdout = KeyboardEnhancementGuard::count();
+    let first = KeyboardEnhancementGuard::acquire_on(&slave).unwrap();
+    let second = KeyboardEnhancementGuard::acquire_on(&slave).unwrap();
+    assert_eq!(KeyboardEnhancementGuard::count_on(&slave), 2);
+    assert_eq!(KeyboardEnhancementGuard::count(), on_stdout);
+    first.release().unwrap();
+    second.release().unwrap();
+    assert_eq!(KeyboardEnhancementGuard::count_on(&slave), 0);
+    // the flags were pushed once and popped once
+    let mut written = [0; 11];
+    master.read_exact(&mut written).unwrap();
+    assert_eq!(&written, b"\x1b[>15u\x1b[<1u");
+}
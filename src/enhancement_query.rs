@@ -0,0 +1,135 @@
+//! A query of the keyboard enhancement support of the terminal working on
+//! any reader and writer, for applications which don't read the terminal
+//! with crossterm's event loop.
+
+use {
+    crossterm::event::KeyboardEnhancementFlags,
+    std::io::{
+        self,
+        Read,
+        Write,
+    },
+};
+
+/// The query to write to the terminal: the keyboard enhancement flags
+/// (`CSI ? u`), then the primary device attributes (`CSI c`) which all
+/// terminals answer, so that the end of the answers is known
+pub const KEYBOARD_ENHANCEMENT_QUERY: &[u8] = b"\x1b[?u\x1b[c";
+
+/// The answers of a terminal to [KEYBOARD_ENHANCEMENT_QUERY]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardEnhancementReply {
+    /// The flags currently pushed, when the terminal supports the
+    /// keyboard enhancement
+    pub flags: Option<KeyboardEnhancementFlags>,
+    /// The bytes read which aren't part of the answers, e.g. keys typed
+    /// during the query, to give back to your decoder
+    pub unrelated: Vec<u8>,
+}
+
+impl KeyboardEnhancementReply {
+    /// Tell whether the terminal supports the keyboard enhancement flags
+    pub fn is_supported(&self) -> bool {
+        self.flags.is_some()
+    }
+}
+
+/// Write the query to the terminal then read its answers, and tell
+/// whether it supports the keyboard enhancement flags.
+///
+/// The terminal must be in raw mode (or at least without line buffering)
+/// so that the answers can be read. The reader is read until the answer
+/// to the device attributes query: give it a timeout (e.g. make it return
+/// a `TimedOut` error) if the terminal may not answer.
+///
+/// ```
+/// use crokey::*;
+/// let terminal_answers: &[u8] = b"\x1b[?0u\x1b[?62;22c";
+/// let mut written = Vec::new();
+/// let reply = query_keyboard_enhancement(terminal_answers, &mut written).unwrap();
+/// assert_eq!(written, KEYBOARD_ENHANCEMENT_QUERY);
+/// assert!(reply.is_supported());
+/// ```
+pub fn query_keyboard_enhancement<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+) -> io::Result<KeyboardEnhancementReply> {
+    writer.write_all(KEYBOARD_ENHANCEMENT_QUERY)?;
+    writer.flush()?;
+    let mut answers = Vec::new();
+    let mut buf = [0; 64];
+    loop {
+        if let Some(reply) = parse_keyboard_enhancement_reply(&answers) {
+            return Ok(reply);
+        }
+        let n = match reader.read(&mut buf) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the terminal didn't answer the device attributes query",
+                ));
+            }
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        answers.extend_from_slice(&buf[..n]);
+    }
+}
+
+/// Parse the bytes read after writing [KEYBOARD_ENHANCEMENT_QUERY],
+/// returning `None` while the answer to the device attributes query,
+/// which ends the answers, isn't complete.
+pub fn parse_keyboard_enhancement_reply(answers: &[u8]) -> Option<KeyboardEnhancementReply> {
+    let mut flags = None;
+    let mut unrelated = Vec::new();
+    let mut i = 0;
+    while i < answers.len() {
+        if !answers[i..].starts_with(b"\x1b[?") {
+            unrelated.push(answers[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 3;
+        while i < answers.len() && (answers[i].is_ascii_digit() || answers[i] == b';') {
+            i += 1;
+        }
+        match answers.get(i) {
+            Some(b'u') => {
+                let bits = std::str::from_utf8(&answers[start + 3..i])
+                    .ok()
+                    .and_then(|param| param.parse().ok())
+                    .unwrap_or(0);
+                flags = Some(KeyboardEnhancementFlags::from_bits_truncate(bits));
+            }
+            Some(b'c') => {
+                unrelated.extend_from_slice(&answers[i + 1..]);
+                return Some(KeyboardEnhancementReply { flags, unrelated });
+            }
+            Some(_) => unrelated.extend_from_slice(&answers[start..=i]),
+            None => return None, // incomplete sequence
+        }
+        i += 1;
+    }
+    None
+}
+
+#[test]
+fn check_keyboard_enhancement_reply_parsing() {
+    assert_eq!(parse_keyboard_enhancement_reply(b""), None);
+    assert_eq!(parse_keyboard_enhancement_reply(b"\x1b[?0u"), None);
+    assert_eq!(parse_keyboard_enhancement_reply(b"\x1b[?0u\x1b[?62;2"), None);
+    let reply = parse_keyboard_enhancement_reply(b"\x1b[?15u\x1b[?62;22c").unwrap();
+    assert_eq!(reply.flags, Some(KeyboardEnhancementFlags::from_bits_truncate(15)));
+    assert!(reply.unrelated.is_empty());
+    let reply = parse_keyboard_enhancement_reply(b"a\x1b[?64;1;2cb").unwrap();
+    assert!(!reply.is_supported());
+    assert_eq!(reply.unrelated, b"ab");
+    let reply = parse_keyboard_enhancement_reply(b"\x1b[?1hx\x1b[?0u\x1b[?1c").unwrap();
+    assert_eq!(reply.flags, Some(KeyboardEnhancementFlags::empty()));
+    assert_eq!(reply.unrelated, b"\x1b[?1hx");
+    let silent: &[u8] = b"\x1b[?0u";
+    let error = query_keyboard_enhancement(silent, io::sink()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+}
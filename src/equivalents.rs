@@ -0,0 +1,122 @@
+use {
+    crate::*,
+    crossterm::event::KeyModifiers,
+};
+
+/// The classic ASCII control aliases: in each class, the key combinations
+/// a terminal without keyboard enhancement can't tell apart, as they're
+/// sent as the same control char.
+///
+/// For example Tab is sent as `^I`, so that `tab` and `ctrl-i` arrive as
+/// the same key event, and which one you get depends on the terminal.
+/// The first combination of a class is the one crossterm usually reports.
+pub static CONTROL_EQUIVALENTS: &[&[KeyCombination]] = &[
+    // NUL
+    &[key!(ctrl-space), key!(ctrl-'@'), key!(ctrl-'2')],
+    // BS and DEL
+    &[key!(backspace), key!(ctrl-h), key!(ctrl-'?'), key!(ctrl-'8')],
+    // HT
+    &[key!(tab), key!(ctrl-i)],
+    // LF and CR
+    &[key!(enter), key!(ctrl-m), key!(ctrl-j)],
+    // ESC
+    &[key!(esc), key!(ctrl-'['), key!(ctrl-'3')],
+    // FS
+    &[key!(ctrl-'\\'), key!(ctrl-'4')],
+    // GS
+    &[key!(ctrl-']'), key!(ctrl-'5')],
+    // RS
+    &[key!(ctrl-'^'), key!(ctrl-'6')],
+    // US
+    &[key!(ctrl-'_'), key!(ctrl-'/'), key!(ctrl-'7')],
+];
+
+/// The modifiers a terminal sends on top of a control char
+const EXTRA_MODIFIERS: KeyModifiers = KeyModifiers::ALT.union(KeyModifiers::SUPER);
+
+impl KeyCombination {
+    /// Return the other combinations a terminal may send for this one,
+    /// according to the [CONTROL_EQUIVALENTS] table, so that you may bind
+    /// all of them.
+    ///
+    /// The alt and super modifiers are kept, as they're sent apart from
+    /// the control char.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(key!(tab).equivalents(), vec![key!(ctrl-i)]);
+    /// assert_eq!(key!(ctrl-'[').equivalents(), vec![key!(esc), key!(ctrl-'3')]);
+    /// assert_eq!(key!(alt-enter).equivalents(), vec![key!(ctrl-alt-m), key!(ctrl-alt-j)]);
+    /// assert!(key!(ctrl-a).equivalents().is_empty());
+    /// ```
+    pub fn equivalents(self) -> Vec<KeyCombination> {
+        let key = self.canonical();
+        for class in CONTROL_EQUIVALENTS {
+            for member in class.iter() {
+                let member = member.canonical();
+                if member.codes != key.codes || !key.modifiers.contains(member.modifiers) {
+                    continue;
+                }
+                let extra = key.modifiers - member.modifiers;
+                if !EXTRA_MODIFIERS.contains(extra) {
+                    continue;
+                }
+                return class
+                    .iter()
+                    .map(|other| {
+                        let mut other = other.canonical();
+                        other.modifiers |= extra;
+                        other
+                    })
+                    .filter(|other| *other != key)
+                    .collect();
+            }
+        }
+        Vec::new()
+    }
+}
+
+impl<A: Clone> KeyBindingMap<A> {
+    /// Bind an action to a key combination and to its
+    /// [equivalents](KeyCombination::equivalents) which aren't already bound.
+    ///
+    /// Return the action previously bound to the key itself, if any.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-h", "help").unwrap();
+    /// map.insert_with_equivalents(key!(backspace), "delete");
+    /// assert_eq!(map.get(key!(ctrl-h)), Some(&"help"));
+    /// assert_eq!(map.get(key!(ctrl-'?')), Some(&"delete"));
+    /// ```
+    pub fn insert_with_equivalents(&mut self, key: KeyCombination, action: A) -> Option<A> {
+        for equivalent in key.equivalents() {
+            if self.get_sequence_ignoring_state(&[equivalent]).is_none() {
+                let _ = self.insert(equivalent, action.clone());
+            }
+        }
+        self.insert(key, action).ok().flatten()
+    }
+}
+
+#[test]
+fn check_control_equivalents() {
+    // the classes are disjoint
+    let mut all: Vec<KeyCombination> = CONTROL_EQUIVALENTS
+        .iter()
+        .flat_map(|class| class.iter().map(|key| key.canonical()))
+        .collect();
+    let n = all.len();
+    all.sort_by_key(|key| key.to_string());
+    all.dedup();
+    assert_eq!(all.len(), n);
+    // every member finds the others
+    for class in CONTROL_EQUIVALENTS {
+        for key in class.iter() {
+            assert_eq!(key.equivalents().len(), class.len() - 1, "{}", key);
+        }
+    }
+    assert!(key!(shift-tab).equivalents().is_empty());
+    assert_eq!(key!(ctrl-alt-i).equivalents(), vec![key!(alt-tab)]);
+}
@@ -0,0 +1,165 @@
+use {
+    crate::*,
+    crossterm::event::{
+        Event,
+        KeyEvent,
+    },
+};
+
+/// Something turning key events into key combinations, i.e. a
+/// [CombinerCore], a [Combiner] or a [Pipeline]
+pub trait KeyEventCombiner {
+    fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination>;
+}
+
+impl KeyEventCombiner for CombinerCore {
+    fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        CombinerCore::transform(self, key)
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl KeyEventCombiner for Combiner {
+    fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        Combiner::transform(self, key)
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl KeyEventCombiner for Pipeline {
+    fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        Pipeline::transform(self, key)
+    }
+}
+
+impl<C: KeyEventCombiner + ?Sized> KeyEventCombiner for &mut C {
+    fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        (**self).transform(key)
+    }
+}
+
+/// An event of a [CombinedEvents] iterator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinedEvent {
+    /// A key combination made from key events
+    Key(KeyCombination),
+    /// An event which isn't a key event (mouse, resize, paste, etc.)
+    Other(Event),
+}
+
+/// Iterator over the key combinations made from the key events of
+/// an iterator of crossterm events, the other events being dropped.
+///
+/// Made by [EventIteratorExt::key_combinations].
+#[derive(Debug)]
+pub struct KeyCombinations<I, C> {
+    events: I,
+    combiner: C,
+}
+
+impl<I, C> Iterator for KeyCombinations<I, C>
+where
+    I: Iterator<Item = Event>,
+    C: KeyEventCombiner,
+{
+    type Item = KeyCombination;
+    fn next(&mut self) -> Option<KeyCombination> {
+        for event in &mut self.events {
+            if let Event::Key(key_event) = event {
+                if let Some(key) = self.combiner.transform(key_event) {
+                    return Some(key);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the key combinations made from the key events of an
+/// iterator of crossterm events, and over the other events.
+///
+/// Made by [EventIteratorExt::combined_events].
+#[derive(Debug)]
+pub struct CombinedEvents<I, C> {
+    events: I,
+    combiner: C,
+}
+
+impl<I, C> Iterator for CombinedEvents<I, C>
+where
+    I: Iterator<Item = Event>,
+    C: KeyEventCombiner,
+{
+    type Item = CombinedEvent;
+    fn next(&mut self) -> Option<CombinedEvent> {
+        for event in &mut self.events {
+            match event {
+                Event::Key(key_event) => {
+                    if let Some(key) = self.combiner.transform(key_event) {
+                        return Some(CombinedEvent::Key(key));
+                    }
+                }
+                event => {
+                    return Some(CombinedEvent::Other(event));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Extension of iterators of crossterm events, for synchronous event loops.
+///
+/// The combiner may be given by value or by mutable reference.
+///
+/// ```no_run
+/// use {
+///     crokey::*,
+///     crossterm::event,
+/// };
+/// # #[cfg(feature = "terminal")] {
+/// let mut combiner = Combiner::default();
+/// combiner.enable_combining().unwrap();
+/// let events = std::iter::from_fn(|| event::read().ok());
+/// for key in events.key_combinations(&mut combiner) {
+///     if key == key!(ctrl-q) {
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+pub trait EventIteratorExt: Iterator<Item = Event> + Sized {
+    /// Return an iterator over the key combinations, other events being dropped
+    fn key_combinations<C: KeyEventCombiner>(self, combiner: C) -> KeyCombinations<Self, C> {
+        KeyCombinations { events: self, combiner }
+    }
+    /// Return an iterator over the key combinations and the other events
+    fn combined_events<C: KeyEventCombiner>(self, combiner: C) -> CombinedEvents<Self, C> {
+        CombinedEvents { events: self, combiner }
+    }
+}
+
+impl<I: Iterator<Item = Event>> EventIteratorExt for I {}
+
+#[test]
+fn check_event_iterators() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let events = vec![
+        Event::FocusGained,
+        Event::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)),
+        Event::Resize(80, 20),
+        Event::Key(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE)),
+    ];
+    let keys: Vec<_> = events.clone().into_iter()
+        .key_combinations(CombinerCore::default())
+        .collect();
+    assert_eq!(keys, vec![key!(ctrl-a), key!(b)]);
+    let mut core = CombinerCore::default();
+    let combined: Vec<_> = events.into_iter().combined_events(&mut core).collect();
+    assert_eq!(combined, vec![
+        CombinedEvent::Other(Event::FocusGained),
+        CombinedEvent::Key(key!(ctrl-a)),
+        CombinedEvent::Other(Event::Resize(80, 20)),
+        CombinedEvent::Key(key!(b)),
+    ]);
+}
@@ -4,9 +4,25 @@
 //! - describing key combinations in strings
 
 use {
-    crate::KeyCombination,
-    crossterm::event::{KeyCode::*, KeyModifiers},
-    std::fmt,
+    crate::{
+        keypad::keypad_display_name_of,
+        shift_policy::{
+            has_case,
+            uppercase_char,
+        },
+        parse_key_code,
+        KeyCombination,
+        KeyNames,
+        ModifierRemap,
+        OneToThree,
+        ParseKeyError,
+        ShiftPolicy,
+    },
+    crossterm::event::{KeyCode::{self, *}, KeyModifiers},
+    std::{
+        fmt,
+        str::FromStr,
+    },
 };
 
 /// A formatter to produce key combinations descriptions.
@@ -62,9 +78,24 @@ pub struct KeyCombinationFormat {
     pub control: String,
     pub alt: String,
     pub shift: String,
+    /// written for the SUPER modifier (the command or windows key)
+    pub super_key: String,
     pub enter: String,
     pub uppercase_shift: bool,
     pub key_separator: String,
+    /// whether BackTab should be written as Shift-Tab instead of Shift-BackTab
+    pub backtab_as_shift_tab: bool,
+    /// how shift is written with chars having a case
+    pub shift_policy: ShiftPolicy,
+    /// custom names of keys, also accepted by [KeyCombinationFormat::parse]
+    pub key_names: KeyNames,
+    /// whether the chars which would need escaping in configuration files,
+    /// like quotes, are written as code points (e.g. `0x22` for `"`), see
+    /// [KeyCombinationFormat::config_safe]
+    pub config_safe: bool,
+    /// remaps applied to the modifiers before they're written,
+    /// see [KeyCombinationFormat::with_modifier_remap]
+    pub modifier_remap: ModifierRemap,
 }
 
 impl Default for KeyCombinationFormat {
@@ -73,18 +104,153 @@ impl Default for KeyCombinationFormat {
             control: "Ctrl-".to_string(),
             alt: "Alt-".to_string(),
             shift: "Shift-".to_string(),
+            super_key: "Super-".to_string(),
             enter: "Enter".to_string(),
             uppercase_shift: false,
             key_separator: "-".to_string(),
+            backtab_as_shift_tab: false,
+            shift_policy: ShiftPolicy::PreferModifier,
+            key_names: KeyNames::default(),
+            config_safe: false,
+            modifier_remap: ModifierRemap::default(),
         }
     }
 }
 
+/// Options of [KeyCombinationFormat::format_set]
+#[derive(Debug, Clone)]
+pub struct SetFormat {
+    /// written between the combinations
+    pub separator: String,
+    /// whether combinations which differ only in the way shift is
+    /// written (e.g. `A` and `shift-a`) are written only once
+    pub dedup_shift_variants: bool,
+    /// whether the combinations are sorted by their description,
+    /// instead of being written in the given order
+    pub sorted: bool,
+}
+
+impl Default for SetFormat {
+    fn default() -> Self {
+        Self {
+            separator: " / ".to_string(),
+            dedup_shift_variants: true,
+            sorted: false,
+        }
+    }
+}
+
+impl SetFormat {
+    pub fn with_separator<S: Into<String>>(mut self, s: S) -> Self {
+        self.separator = s.into();
+        self
+    }
+    pub fn with_dedup_shift_variants(mut self, dedup: bool) -> Self {
+        self.dedup_shift_variants = dedup;
+        self
+    }
+    pub fn with_sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+}
+
+/// The env var read by [KeyCombinationFormat::from_env]
+pub const FORMAT_ENV_VAR: &str = "CROKEY_FORMAT";
+
+/// Error returned when a format spec can't be understood
+#[derive(Debug)]
+pub struct ParseFormatSpecError {
+    /// the part of the spec which couldn't be understood
+    pub raw: String,
+}
+
+impl fmt::Display for ParseFormatSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} isn't a valid key format spec entry", self.raw)
+    }
+}
+
+impl std::error::Error for ParseFormatSpecError {}
+
 impl KeyCombinationFormat {
+    /// Build a format from a compact spec, like `"ctrl=^,alt=M-,sep=+"`,
+    /// so that end users can customize the display of keys with only one
+    /// string in their configuration.
+    ///
+    /// The spec is a comma separated list of entries applied over the
+    /// default format:
+    /// - `ctrl=`, `alt=`, `shift=`, `enter=`, `sep=` set the corresponding string
+    /// - `implicit_shift` writes shifted chars in uppercase without the shift modifier
+    /// - `lowercase_modifiers` lowercases the modifiers
+    /// - `shift_tab` writes BackTab as Shift-Tab
+    /// - `shift_policy=` is `modifier` (`Shift-a`), `uppercase` (`A`) or `both` (`Shift-A`)
+    ///
+    /// Values can't contain commas.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::parse_format_spec("ctrl=^,alt=M-,implicit_shift").unwrap();
+    /// assert_eq!(format.to_string(key!(ctrl-c)), "^c");
+    /// assert_eq!(format.to_string(key!(alt-shift-x)), "M-X");
+    /// let format = KeyCombinationFormat::parse_format_spec("ctrl=Ctrl+,sep=+").unwrap();
+    /// assert_eq!(format.to_string(key!(ctrl-a-b)), "Ctrl+a+b");
+    /// assert!(KeyCombinationFormat::parse_format_spec("ctrl=^,bold").is_err());
+    /// ```
+    pub fn parse_format_spec(spec: &str) -> Result<Self, ParseFormatSpecError> {
+        let mut format = Self::default();
+        for entry in spec.split(',') {
+            if entry.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = match entry.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value)),
+                None => (entry.trim(), None),
+            };
+            match (key, value) {
+                ("ctrl" | "control", Some(value)) => format.control = value.to_string(),
+                ("alt", Some(value)) => format.alt = value.to_string(),
+                ("shift", Some(value)) => format.shift = value.to_string(),
+                ("super", Some(value)) => format.super_key = value.to_string(),
+                ("enter", Some(value)) => format.enter = value.to_string(),
+                ("sep" | "separator", Some(value)) => format.key_separator = value.to_string(),
+                ("implicit_shift", None) => format = format.with_implicit_shift(),
+                ("lowercase_modifiers", None) => format = format.with_lowercase_modifiers(),
+                ("shift_tab", None) => format = format.with_backtab_as_shift_tab(),
+                ("shift_policy", Some("modifier")) => format.shift_policy = ShiftPolicy::PreferModifier,
+                ("shift_policy", Some("uppercase")) => format.shift_policy = ShiftPolicy::PreferUppercaseChar,
+                ("shift_policy", Some("both")) => format.shift_policy = ShiftPolicy::Both,
+                _ => {
+                    return Err(ParseFormatSpecError { raw: entry.to_string() });
+                }
+            }
+        }
+        Ok(format)
+    }
+    /// Build a format from the spec found in the `CROKEY_FORMAT` env var,
+    /// if it's set, so that users may choose how keys are displayed in all
+    /// the applications using crokey which opt in.
+    ///
+    /// See [KeyCombinationFormat::parse_format_spec] for the syntax of the spec.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::from_env()
+    ///     .ok()
+    ///     .flatten()
+    ///     .unwrap_or_default();
+    /// ```
+    pub fn from_env() -> Result<Option<Self>, ParseFormatSpecError> {
+        match std::env::var(FORMAT_ENV_VAR) {
+            Ok(spec) => Self::parse_format_spec(&spec).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
     pub fn with_lowercase_modifiers(mut self) -> Self {
         self.control = self.control.to_lowercase();
         self.alt = self.alt.to_lowercase();
         self.shift = self.shift.to_lowercase();
+        self.super_key = self.super_key.to_lowercase();
         self
     }
     pub fn with_control<S: Into<String>>(mut self, s: S) -> Self {
@@ -99,11 +265,181 @@ impl KeyCombinationFormat {
         self.shift = s.into();
         self
     }
+    pub fn with_super_key<S: Into<String>>(mut self, s: S) -> Self {
+        self.super_key = s.into();
+        self
+    }
+    /// Write the key combinations with their modifiers remapped, e.g. to
+    /// display the keys received by the application instead of the ones
+    /// pressed. Use [ModifierRemap::inverted] for the other way.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut remap = ModifierRemap::new();
+    /// remap.insert(Mods::alt(), Mods::ctrl()).unwrap();
+    /// let format = KeyCombinationFormat::default().with_modifier_remap(remap);
+    /// assert_eq!(format.to_string(key!(alt-x)), "Ctrl-x");
+    /// ```
+    pub fn with_modifier_remap(mut self, modifier_remap: ModifierRemap) -> Self {
+        self.modifier_remap = modifier_remap;
+        self
+    }
+    pub fn with_enter<S: Into<String>>(mut self, s: S) -> Self {
+        self.enter = s.into();
+        self
+    }
+    /// Set a custom name for a key, which is also accepted by
+    /// [KeyCombinationFormat::parse]
+    pub fn with_key_name<S: Into<String>>(mut self, code: KeyCode, name: S) -> Self {
+        self.key_names.set(code, name);
+        self
+    }
+    pub fn with_key_names(mut self, key_names: KeyNames) -> Self {
+        self.key_names = key_names;
+        self
+    }
     pub fn with_implicit_shift(mut self) -> Self {
         self.shift = "".to_string();
         self.uppercase_shift = true;
         self
     }
+    /// Set how shift is written with chars having a case.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default();
+    /// assert_eq!(format.to_string(key!(shift-a)), "Shift-a");
+    /// let format = format.with_shift_policy(ShiftPolicy::Both);
+    /// assert_eq!(format.to_string(key!(shift-a)), "Shift-A");
+    /// let format = format.with_shift_policy(ShiftPolicy::PreferUppercaseChar);
+    /// assert_eq!(format.to_string(key!(shift-a)), "A");
+    /// assert_eq!(format.to_string(key!(shift-up)), "Shift-Up");
+    /// ```
+    pub fn with_shift_policy(mut self, shift_policy: ShiftPolicy) -> Self {
+        self.shift_policy = shift_policy;
+        self
+    }
+    /// Return the idiomatic format of the current platform: [mac](Self::mac)
+    /// on macOS, [windows](Self::windows) on Windows, and the default one
+    /// elsewhere.
+    ///
+    /// The returned format can be further customized, and users may be given
+    /// the last word with [KeyCombinationFormat::from_env]:
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::from_env()
+    ///     .ok()
+    ///     .flatten()
+    ///     .unwrap_or_else(KeyCombinationFormat::platform_default);
+    /// ```
+    pub fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::mac()
+        } else if cfg!(windows) {
+            Self::windows()
+        } else {
+            Self::default()
+        }
+    }
+    /// Return a format with the macOS symbols of the modifiers
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::mac();
+    /// assert_eq!(format.to_string(key!(command-shift-z)), "⌘⇧z");
+    /// assert_eq!(format.to_string(key!(ctrl-option-enter)), "⌃⌥↩");
+    /// ```
+    pub fn mac() -> Self {
+        Self::default()
+            .with_control("⌃")
+            .with_alt("⌥")
+            .with_super_key("⌘")
+            .with_shift("⇧")
+            .with_enter("↩")
+    }
+    /// Return a format with the `+` separated modifiers usual on Windows
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::windows();
+    /// assert_eq!(format.to_string(key!(ctrl-shift-s)), "Ctrl+Shift+s");
+    /// assert_eq!(format.to_string(key!(windows-e)), "Win+e");
+    /// assert_eq!(format.to_string(key!(ctrl-a-b)), "Ctrl+a+b");
+    /// ```
+    pub fn windows() -> Self {
+        Self {
+            control: "Ctrl+".to_string(),
+            alt: "Alt+".to_string(),
+            shift: "Shift+".to_string(),
+            super_key: "Win+".to_string(),
+            key_separator: "+".to_string(),
+            ..Self::default()
+        }
+    }
+    /// Return the format used to write keys in configuration files: the
+    /// default format, but with the quotes, the backslash, `#` and the
+    /// whitespace and control chars written as code points.
+    ///
+    /// Its output needs no escaping in JSON or TOML strings, can't be taken
+    /// for a comment, and is read back by [parse](crate::parse). Other chars,
+    /// like `?` or non ASCII letters, are written as themselves.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::config_safe();
+    /// assert_eq!(format.to_string(key!(ctrl-'"')), "Ctrl-0x22");
+    /// assert_eq!(format.to_string(key!(alt-'#')), "Alt-0x23");
+    /// assert_eq!(format.to_string(key!(ctrl-'?')), "Ctrl-?");
+    /// assert_eq!(format.to_string(key!('é')), "é");
+    /// assert_eq!(format.to_string(key!(ctrl-alt-space)), "Ctrl-Alt-Space");
+    /// assert_eq!(parse("Ctrl-0x22").unwrap(), key!(ctrl-'"'));
+    /// ```
+    pub fn config_safe() -> Self {
+        Self::default().with_config_safe(true)
+    }
+    /// Return the format of this name, if it's one of `default` (or `text`),
+    /// `mac` (or `symbols`), `windows`, `platform` (see
+    /// [platform_default](Self::platform_default)) and `config_safe`
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::preset("symbols").unwrap();
+    /// assert_eq!(format.to_string(key!(ctrl-c)), "⌃c");
+    /// assert!(KeyCombinationFormat::preset("fancy").is_none());
+    /// ```
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" | "text" => Some(Self::default()),
+            "mac" | "symbols" => Some(Self::mac()),
+            "windows" => Some(Self::windows()),
+            "platform" => Some(Self::platform_default()),
+            "config_safe" => Some(Self::config_safe()),
+            _ => None,
+        }
+    }
+    /// Set whether the quotes, the backslash, `#` and the whitespace and
+    /// control chars are written as code points, see
+    /// [KeyCombinationFormat::config_safe]
+    pub fn with_config_safe(mut self, config_safe: bool) -> Self {
+        self.config_safe = config_safe;
+        self
+    }
+    /// Write BackTab as Shift-Tab, which is how most users call it, and
+    /// read Shift-Tab as BackTab in [KeyCombinationFormat::parse].
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default();
+    /// assert_eq!(format.to_string(key!(backtab)), "Shift-BackTab");
+    /// let format = format.with_backtab_as_shift_tab();
+    /// assert_eq!(format.to_string(key!(backtab)), "Shift-Tab");
+    /// assert_eq!(format.parse("Shift-Tab").unwrap(), key!(backtab));
+    /// ```
+    pub fn with_backtab_as_shift_tab(mut self) -> Self {
+        self.backtab_as_shift_tab = true;
+        self
+    }
     /// return a wrapper of the key implementing Display
     ///
     /// ```
@@ -113,9 +449,137 @@ impl KeyCombinationFormat {
     /// let s = format!("k={}", k);
     /// assert_eq!(s, "k=F6");
     /// ```
-    pub fn format<K: Into<KeyCombination>>(&self, key: K) -> FormattedKeyCombination {
+    pub fn format<K: Into<KeyCombination>>(&self, key: K) -> FormattedKeyCombination<'_> {
         FormattedKeyCombination { format: self, key: key.into() }
     }
+    /// Format a key sequence, its key combinations being separated by spaces
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default();
+    /// assert_eq!(format.format_sequence(&[key!(ctrl-x), key!(ctrl-s)]), "Ctrl-x Ctrl-s");
+    /// ```
+    pub fn format_sequence(&self, keys: &[KeyCombination]) -> String {
+        let mut s = String::new();
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                s.push(' ');
+            }
+            s.push_str(&self.to_string(*key));
+        }
+        s
+    }
+    /// Format several key combinations in one string, e.g. for a help line
+    /// like `Ctrl-c / Ctrl-q to quit`.
+    ///
+    /// Exact duplicates are always removed, the first occurrence being kept.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// use crossterm::event::{KeyCode, KeyModifiers};
+    /// let format = KeyCombinationFormat::default();
+    /// let upper = KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE);
+    /// let keys = [key!(ctrl-q), key!(ctrl-c), key!(ctrl-q)];
+    /// assert_eq!(format.format_set(&keys, &SetFormat::default()), "Ctrl-q / Ctrl-c");
+    /// let set_format = SetFormat::default().with_separator(", ").with_sorted(true);
+    /// assert_eq!(format.format_set(&keys, &set_format), "Ctrl-c, Ctrl-q");
+    /// assert_eq!(format.format_set(&[key!(shift-a), upper], &set_format), "Shift-a");
+    /// ```
+    pub fn format_set(&self, keys: &[KeyCombination], set_format: &SetFormat) -> String {
+        let mut kept: Vec<KeyCombination> = Vec::new();
+        for &key in keys {
+            let duplicate = kept.iter().any(|k| {
+                *k == key || (set_format.dedup_shift_variants && k.eq_loose(&key))
+            });
+            if !duplicate {
+                kept.push(key);
+            }
+        }
+        let mut descriptions: Vec<String> = kept.into_iter().map(|key| self.to_string(key)).collect();
+        if set_format.sorted {
+            descriptions.sort();
+        }
+        descriptions.join(&set_format.separator)
+    }
+    /// Parse a key combination written with this format, with its
+    /// modifier strings, key separator and key names.
+    ///
+    /// Default key names (e.g. `pageup`) and the names written by the
+    /// format (e.g. `Space`, `Hyphen`) are accepted too.
+    ///
+    /// The [modifier remap](KeyCombinationFormat::with_modifier_remap) is
+    /// inverted, so that parsing gives back the formatted combination (remaps
+    /// to zero or several modifiers can't be inverted and are ignored).
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::KeyCode,
+    /// };
+    /// let format = KeyCombinationFormat::default()
+    ///     .with_enter("Return")
+    ///     .with_key_name(KeyCode::PageUp, "PgUp")
+    ///     .with_control("^");
+    /// assert_eq!(format.to_string(key!(ctrl-pageup)), "^PgUp");
+    /// assert_eq!(format.parse("^PgUp").unwrap(), key!(ctrl-pageup));
+    /// assert_eq!(format.parse("Alt-Return").unwrap(), key!(alt-enter));
+    /// assert_eq!(format.parse("alt-enter").unwrap(), key!(alt-enter));
+    /// let mut remap = ModifierRemap::new();
+    /// remap.insert(Mods::alt(), Mods::ctrl()).unwrap();
+    /// let format = format.with_modifier_remap(remap);
+    /// assert_eq!(format.to_string(key!(alt-x)), "^x");
+    /// assert_eq!(format.parse("^x").unwrap(), key!(alt-x));
+    /// ```
+    pub fn parse(&self, raw: &str) -> Result<KeyCombination, ParseKeyError> {
+        let mut modifiers = KeyModifiers::empty();
+        let mut rest = raw;
+        loop {
+            if let Some(r) = strip_prefix_ignore_case(rest, &self.control) {
+                modifiers.insert(KeyModifiers::CONTROL);
+                rest = r;
+            } else if let Some(r) = strip_prefix_ignore_case(rest, &self.alt) {
+                modifiers.insert(KeyModifiers::ALT);
+                rest = r;
+            } else if let Some(r) = strip_prefix_ignore_case(rest, &self.shift) {
+                modifiers.insert(KeyModifiers::SHIFT);
+                rest = r;
+            } else if let Some(r) = strip_prefix_ignore_case(rest, &self.super_key) {
+                modifiers.insert(KeyModifiers::SUPER);
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let parts: Vec<&str> = if self.key_separator.is_empty() || rest == self.key_separator {
+            vec![rest]
+        } else {
+            rest.split(self.key_separator.as_str()).collect()
+        };
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let mut codes = Vec::new();
+        for part in parts {
+            let code = if let Some(code) = self.key_names.code(part) {
+                code
+            } else if part.eq_ignore_ascii_case(&self.enter) {
+                Enter
+            } else if part.chars().count() == 1 {
+                parse_key_code(part, shift)?
+            } else {
+                parse_key_code(&part.to_ascii_lowercase(), shift).map_err(|_| ParseKeyError::new(raw))?
+            };
+            let code = match code {
+                Tab if shift && self.backtab_as_shift_tab => BackTab,
+                code => code,
+            };
+            if code == BackTab {
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+            codes.push(code);
+        }
+        let codes: OneToThree<KeyCode> = codes.try_into().map_err(|_| ParseKeyError::new(raw))?;
+        let key = KeyCombination::new(codes, modifiers).normalized();
+        Ok(self.modifier_remap.inverted().apply(key))
+    }
     /// return the key formatted into a string
     ///
     /// `format.to_string(key)` is equivalent to `format.format(key).to_string()`.
@@ -124,6 +588,38 @@ impl KeyCombinationFormat {
     }
 }
 
+impl FromStr for KeyCombinationFormat {
+    type Err = ParseFormatSpecError;
+    fn from_str(spec: &str) -> Result<Self, ParseFormatSpecError> {
+        Self::parse_format_spec(spec)
+    }
+}
+
+fn strip_prefix_ignore_case<'s>(s: &'s str, prefix: &str) -> Option<&'s str> {
+    if prefix.is_empty() {
+        return None;
+    }
+    let start = s.get(..prefix.len())?;
+    if start.eq_ignore_ascii_case(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// A key combination with its format, implementing Display.
+///
+/// The width, alignment and precision of the formatter apply to the
+/// whole rendered key, so that key hints can be aligned in columns:
+///
+/// ```
+/// use crokey::*;
+/// let format = KeyCombinationFormat::default();
+/// assert_eq!(format!("[{:>8}]", format.format(key!(ctrl-s))), "[  Ctrl-s]");
+/// assert_eq!(format!("[{:-<8}]", format.format(key!(f6))), "[F6------]");
+/// assert_eq!(format!("[{:.4}]", format.format(key!(ctrl-s))), "[Ctrl]");
+/// assert_eq!(format!("[{:^7}]", key!(alt-x)), "[ Alt-x ]");
+/// ```
 pub struct FormattedKeyCombination<'s> {
     format: &'s KeyCombinationFormat,
     key: KeyCombination,
@@ -131,21 +627,151 @@ pub struct FormattedKeyCombination<'s> {
 
 impl<'s> fmt::Display for FormattedKeyCombination<'s> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.width().is_none() && f.precision().is_none() {
+            return self.write_to(f);
+        }
+        let mut s = String::new();
+        self.write_to(&mut s)?;
+        f.pad(&s)
+    }
+}
+
+#[cfg(feature = "unicode-width")]
+impl<'s> FormattedKeyCombination<'s> {
+    /// Return the number of terminal cells the rendered key takes,
+    /// ANSI escape sequences (e.g. in styled key names) being ignored.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default()
+    ///     .with_control("⌃")
+    ///     .with_key_name(crossterm::event::KeyCode::Enter, "\u{1b}[1m⏎\u{1b}[0m");
+    /// assert_eq!(format.format(key!(ctrl-enter)).width(), 2);
+    /// assert_eq!(format.format(key!('世')).width(), 2);
+    /// ```
+    pub fn width(&self) -> usize {
+        use unicode_width::UnicodeWidthChar;
+        let mut width = 0;
+        let s = self.to_string();
+        let mut rest = s.as_str();
+        while let Some(c) = rest.chars().next() {
+            let len = ansi_escape_len(rest).unwrap_or_else(|| {
+                width += c.width().unwrap_or(0);
+                c.len_utf8()
+            });
+            rest = &rest[len..];
+        }
+        width
+    }
+    /// Render the key, truncated so that it takes at most `max_width`
+    /// terminal cells, an ellipsis marking the truncation.
+    ///
+    /// ANSI escape sequences aren't cut and are all kept, so that the
+    /// styles are reset as they would be in the full string.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default();
+    /// assert_eq!(format.format(key!(ctrl-alt-s)).truncated(20), "Ctrl-Alt-s");
+    /// assert_eq!(format.format(key!(ctrl-alt-s)).truncated(6), "Ctrl-…");
+    /// ```
+    pub fn truncated(&self, max_width: usize) -> String {
+        use unicode_width::UnicodeWidthChar;
+        if self.width() <= max_width {
+            return self.to_string();
+        }
+        // one cell is kept for the ellipsis
+        let max_width = max_width.saturating_sub(1);
+        let s = self.to_string();
+        let mut truncated = String::new();
+        let mut width = 0;
+        let mut ellipsis = false;
+        let mut rest = s.as_str();
+        while let Some(c) = rest.chars().next() {
+            let len = match ansi_escape_len(rest) {
+                Some(len) => {
+                    truncated.push_str(&rest[..len]);
+                    len
+                }
+                None => {
+                    let w = c.width().unwrap_or(0);
+                    if !ellipsis && width + w > max_width {
+                        truncated.push('…');
+                        ellipsis = true;
+                    }
+                    if !ellipsis {
+                        truncated.push(c);
+                        width += w;
+                    }
+                    c.len_utf8()
+                }
+            };
+            rest = &rest[len..];
+        }
+        truncated
+    }
+}
+
+/// Return the length of the ANSI escape sequence starting the string, if any
+#[cfg(feature = "unicode-width")]
+fn ansi_escape_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\u{1b}')?;
+    if let Some(csi) = rest.strip_prefix('[') {
+        // parameters then a final byte in 0x40..=0x7e
+        let end = csi.find(|c: char| ('\u{40}'..='\u{7e}').contains(&c))?;
+        return Some(2 + end + 1);
+    }
+    if let Some(osc) = rest.strip_prefix(']') {
+        // ended by BEL or ST
+        let end = osc.find(['\u{7}', '\u{1b}'])?;
+        let terminator = if osc[end..].starts_with('\u{7}') { 1 } else { 2 };
+        return Some(2 + end + terminator);
+    }
+    Some(1 + rest.chars().next().map_or(0, char::len_utf8))
+}
+
+/// Tell whether the char must be written as a code point by the
+/// [config_safe](KeyCombinationFormat::config_safe) format
+fn is_config_unsafe(c: char) -> bool {
+    matches!(c, '"' | '\'' | '\\' | '#') || c.is_whitespace() || c.is_control()
+}
+
+impl<'s> FormattedKeyCombination<'s> {
+    fn write_to<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
         let format = &self.format;
-        let key = &self.key;
+        // control chars are written as the keys typed to produce them
+        let key = &format.modifier_remap.apply(self.key).with_folded_control_chars();
         if key.modifiers.contains(KeyModifiers::CONTROL) {
             write!(f, "{}", format.control)?;
         }
         if key.modifiers.contains(KeyModifiers::ALT) {
             write!(f, "{}", format.alt)?;
         }
-        if key.modifiers.contains(KeyModifiers::SHIFT) {
+        if key.modifiers.contains(KeyModifiers::SUPER) {
+            write!(f, "{}", format.super_key)?;
+        }
+        let backtab_shift = format.backtab_as_shift_tab
+            && key.codes.iter().any(|code| *code == BackTab);
+        let shifted = key.modifiers.contains(KeyModifiers::SHIFT);
+        let uppercase = shifted
+            && (format.uppercase_shift || format.shift_policy != ShiftPolicy::PreferModifier);
+        let shift_in_case = format.shift_policy == ShiftPolicy::PreferUppercaseChar
+            && key.codes.iter().all(|code| matches!(code, Char(c) if has_case(*c)));
+        if (shifted && !shift_in_case) || backtab_shift {
             write!(f, "{}", format.shift)?;
         }
         for (i, code) in key.codes.iter().enumerate() {
             if i > 0 {
                 write!(f, "{}", format.key_separator)?;
             }
+            if let Some(name) = format.key_names.name(*code) {
+                write!(f, "{}", name)?;
+                continue;
+            }
+            if let Some(name) = keypad_display_name_of(*code) {
+                write!(f, "{}", name)?;
+                continue;
+            }
             match code {
                 Char(' ') => {
                     write!(f, "Space")?;
@@ -156,8 +782,11 @@ impl<'s> fmt::Display for FormattedKeyCombination<'s> {
                 Char('\r') | Char('\n') | Enter => {
                     write!(f, "{}", format.enter)?;
                 }
-                Char(c) if key.modifiers.contains(KeyModifiers::SHIFT) && format.uppercase_shift => {
-                    write!(f, "{}", c.to_ascii_uppercase())?;
+                Char(c) if format.config_safe && is_config_unsafe(*c) => {
+                    write!(f, "0x{:x}", *c as u32)?;
+                }
+                Char(c) if uppercase => {
+                    write!(f, "{}", uppercase_char(*c))?;
                 }
                 Char(c) => {
                     write!(f, "{}", c.to_ascii_lowercase())?;
@@ -165,6 +794,12 @@ impl<'s> fmt::Display for FormattedKeyCombination<'s> {
                 F(u) => {
                     write!(f, "F{u}")?;
                 }
+                BackTab if format.backtab_as_shift_tab => {
+                    write!(f, "Tab")?;
+                }
+                Null => {
+                    write!(f, "Unknown")?;
+                }
                 _ => {
                     write!(f, "{:?}", code)?;
                 }
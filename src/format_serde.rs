@@ -0,0 +1,320 @@
+use {
+    crate::*,
+    serde::{
+        de::{
+            self,
+            value::MapAccessDeserializer,
+            MapAccess,
+            Visitor,
+        },
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+    std::{
+        collections::BTreeMap,
+        fmt,
+    },
+};
+
+/// The fields of a format in a configuration, all optional, applied
+/// over the `style` preset
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FormatFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<String>,
+    #[serde(alias = "control")]
+    ctrl: Option<String>,
+    alt: Option<String>,
+    shift: Option<String>,
+    #[serde(rename = "super")]
+    super_key: Option<String>,
+    enter: Option<String>,
+    #[serde(alias = "sep")]
+    separator: Option<String>,
+    uppercase_shift: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implicit_shift: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lowercase_modifiers: Option<bool>,
+    shift_policy: Option<String>,
+    backtab_as_shift_tab: Option<bool>,
+    config_safe: Option<bool>,
+    /// custom names, by key (e.g. `pageup: PgUp`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    key_names: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "ModifierRemap::is_empty")]
+    modifier_remap: ModifierRemap,
+}
+
+fn shift_policy_name(shift_policy: ShiftPolicy) -> &'static str {
+    match shift_policy {
+        ShiftPolicy::PreferModifier => "modifier",
+        ShiftPolicy::PreferUppercaseChar => "uppercase",
+        ShiftPolicy::Both => "both",
+    }
+}
+
+impl FormatFields {
+    fn into_format<E: de::Error>(self) -> Result<KeyCombinationFormat, E> {
+        let mut format = match self.style {
+            Some(style) => KeyCombinationFormat::preset(&style)
+                .ok_or_else(|| E::custom(format!("unknown key format style {:?}", style)))?,
+            None => KeyCombinationFormat::default(),
+        };
+        if let Some(ctrl) = self.ctrl {
+            format.control = ctrl;
+        }
+        if let Some(alt) = self.alt {
+            format.alt = alt;
+        }
+        if let Some(shift) = self.shift {
+            format.shift = shift;
+        }
+        if let Some(super_key) = self.super_key {
+            format.super_key = super_key;
+        }
+        if let Some(enter) = self.enter {
+            format.enter = enter;
+        }
+        if let Some(separator) = self.separator {
+            format.key_separator = separator;
+        }
+        if let Some(uppercase_shift) = self.uppercase_shift {
+            format.uppercase_shift = uppercase_shift;
+        }
+        if self.implicit_shift == Some(true) {
+            format = format.with_implicit_shift();
+        }
+        if let Some(name) = self.shift_policy {
+            format.shift_policy = [
+                ShiftPolicy::PreferModifier,
+                ShiftPolicy::PreferUppercaseChar,
+                ShiftPolicy::Both,
+            ]
+            .into_iter()
+            .find(|shift_policy| shift_policy_name(*shift_policy) == name)
+            .ok_or_else(|| E::custom(format!("unknown shift policy {:?}", name)))?;
+        }
+        if let Some(backtab_as_shift_tab) = self.backtab_as_shift_tab {
+            format.backtab_as_shift_tab = backtab_as_shift_tab;
+        }
+        if let Some(config_safe) = self.config_safe {
+            format.config_safe = config_safe;
+        }
+        for (key, name) in self.key_names {
+            let key = parse(&key).map_err(E::custom)?;
+            match key.codes {
+                OneToThree::One(code) if key.modifiers.is_empty() => format.key_names.set(code, name),
+                _ => return Err(E::custom(format!("{:?} isn't a single key", key.to_string()))),
+            }
+        }
+        if !self.modifier_remap.is_empty() {
+            format.modifier_remap = self.modifier_remap;
+        }
+        if self.lowercase_modifiers == Some(true) {
+            format = format.with_lowercase_modifiers();
+        }
+        Ok(format)
+    }
+}
+
+impl From<&KeyCombinationFormat> for FormatFields {
+    fn from(format: &KeyCombinationFormat) -> Self {
+        let key_format = KeyCombinationFormat::config_safe();
+        Self {
+            style: None,
+            ctrl: Some(format.control.clone()),
+            alt: Some(format.alt.clone()),
+            shift: Some(format.shift.clone()),
+            super_key: Some(format.super_key.clone()),
+            enter: Some(format.enter.clone()),
+            separator: Some(format.key_separator.clone()),
+            uppercase_shift: Some(format.uppercase_shift),
+            implicit_shift: None,
+            lowercase_modifiers: None,
+            shift_policy: Some(shift_policy_name(format.shift_policy).to_string()),
+            backtab_as_shift_tab: Some(format.backtab_as_shift_tab),
+            config_safe: Some(format.config_safe),
+            key_names: format
+                .key_names
+                .iter()
+                .map(|(code, name)| (key_format.to_string(code), name.to_string()))
+                .collect(),
+            modifier_remap: format.modifier_remap.clone(),
+        }
+    }
+}
+
+/// The fields of a format in the binary formats (e.g. bincode), which
+/// can't skip fields nor tell a string from a map
+#[derive(Serialize, Deserialize)]
+struct BinaryFormatFields {
+    ctrl: String,
+    alt: String,
+    shift: String,
+    super_key: String,
+    enter: String,
+    separator: String,
+    uppercase_shift: bool,
+    shift_policy: String,
+    backtab_as_shift_tab: bool,
+    config_safe: bool,
+    key_names: BTreeMap<String, String>,
+    modifier_remap: ModifierRemap,
+}
+
+impl From<FormatFields> for BinaryFormatFields {
+    fn from(fields: FormatFields) -> Self {
+        Self {
+            ctrl: fields.ctrl.unwrap_or_default(),
+            alt: fields.alt.unwrap_or_default(),
+            shift: fields.shift.unwrap_or_default(),
+            super_key: fields.super_key.unwrap_or_default(),
+            enter: fields.enter.unwrap_or_default(),
+            separator: fields.separator.unwrap_or_default(),
+            uppercase_shift: fields.uppercase_shift.unwrap_or_default(),
+            shift_policy: fields.shift_policy.unwrap_or_default(),
+            backtab_as_shift_tab: fields.backtab_as_shift_tab.unwrap_or_default(),
+            config_safe: fields.config_safe.unwrap_or_default(),
+            key_names: fields.key_names,
+            modifier_remap: fields.modifier_remap,
+        }
+    }
+}
+
+impl From<BinaryFormatFields> for FormatFields {
+    fn from(fields: BinaryFormatFields) -> Self {
+        Self {
+            ctrl: Some(fields.ctrl),
+            alt: Some(fields.alt),
+            shift: Some(fields.shift),
+            super_key: Some(fields.super_key),
+            enter: Some(fields.enter),
+            separator: Some(fields.separator),
+            uppercase_shift: Some(fields.uppercase_shift),
+            shift_policy: Some(fields.shift_policy),
+            backtab_as_shift_tab: Some(fields.backtab_as_shift_tab),
+            config_safe: Some(fields.config_safe),
+            key_names: fields.key_names,
+            modifier_remap: fields.modifier_remap,
+            ..Default::default()
+        }
+    }
+}
+
+struct FormatVisitor;
+
+impl<'de> Visitor<'de> for FormatVisitor {
+    type Value = KeyCombinationFormat;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a key format style, a key format spec, or a map of key format fields")
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        match KeyCombinationFormat::preset(s) {
+            Some(format) => Ok(format),
+            None => s.parse().map_err(E::custom),
+        }
+    }
+    fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<Self::Value, M::Error> {
+        FormatFields::deserialize(MapAccessDeserializer::new(map))?.into_format()
+    }
+}
+
+/// A format is read either from a [preset](KeyCombinationFormat::preset)
+/// name, from a [spec](KeyCombinationFormat::parse_format_spec), or from a
+/// map of fields applied over the preset given as `style`.
+///
+/// Binary formats, which can't tell those forms apart, read all the
+/// fields, as written by the Serialize implementation.
+///
+/// ```
+/// use crokey::*;
+/// #[derive(serde::Deserialize)]
+/// struct Display {
+///     keys: KeyCombinationFormat,
+/// }
+/// let display: Display = deser_hjson::from_str(r#"{
+///     keys: {
+///         style: symbols
+///         ctrl: ^
+///         separator: +
+///     }
+/// }"#).unwrap();
+/// assert_eq!(display.keys.to_string(key!(ctrl-c)), "^c");
+/// assert_eq!(display.keys.to_string(key!(command-a-b)), "⌘a+b");
+/// let display: Display = deser_hjson::from_str("{keys: windows\n}").unwrap();
+/// assert_eq!(display.keys.to_string(key!(ctrl-s)), "Ctrl+s");
+/// let display: Display = deser_hjson::from_str(r#"{keys: "ctrl=^,implicit_shift"}"#).unwrap();
+/// assert_eq!(display.keys.to_string(key!(ctrl-shift-s)), "^S");
+/// ```
+impl<'de> Deserialize<'de> for KeyCombinationFormat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(FormatVisitor)
+        } else {
+            FormatFields::from(BinaryFormatFields::deserialize(deserializer)?).into_format()
+        }
+    }
+}
+
+/// A format is written as a map of all its fields
+impl Serialize for KeyCombinationFormat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = FormatFields::from(self);
+        if serializer.is_human_readable() {
+            fields.serialize(serializer)
+        } else {
+            BinaryFormatFields::from(fields).serialize(serializer)
+        }
+    }
+}
+
+#[test]
+fn check_format_serde() {
+    use crossterm::event::KeyCode;
+    let format: KeyCombinationFormat = deser_hjson::from_str(r#"{
+        style: windows
+        key_names: {
+            pageup: PgUp
+            esc: Escape
+        }
+        lowercase_modifiers: true
+        shift_policy: uppercase
+        modifier_remap: {
+            alt: ctrl
+        }
+    }"#).unwrap();
+    assert_eq!(format.to_string(key!(ctrl-pageup)), "ctrl+PgUp");
+    assert_eq!(format.to_string(key!(alt-shift-a)), "ctrl+A");
+    assert_eq!(format.key_names.name(KeyCode::Esc), Some("Escape"));
+    let json = serde_json::to_string(&format).unwrap();
+    let read: KeyCombinationFormat = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    for key in KeyCombination::examples() {
+        assert_eq!(read.to_string(key), format.to_string(key));
+    }
+    let bytes = bincode::serialize(&format).unwrap();
+    let read: KeyCombinationFormat = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    for wrong in [
+        r#"{"style": "fancy"}"#,
+        r#"{"ctrl": "^", "bold": true}"#,
+        r#"{"shift_policy": "never"}"#,
+        r#"{"key_names": {"ctrl-a": "A"}}"#,
+        r#""ctrl=^,bold""#,
+    ] {
+        assert!(serde_json::from_str::<KeyCombinationFormat>(wrong).is_err(), "{}", wrong);
+    }
+    for right in [r#"{"style": "mac"}"#, r#"{"ctrl": "^"}"#, r#""config_safe""#, r#""sep=+""#] {
+        assert!(serde_json::from_str::<KeyCombinationFormat>(right).is_ok(), "{}", right);
+    }
+}
@@ -0,0 +1,171 @@
+//! Conversions from and to the key syntax of the Helix editor,
+//! for example `C-s`, `A-x`, `S-tab` or `ret`.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+};
+
+/// Parse a key as written in a Helix configuration.
+///
+/// As in Helix, modifiers and key names are case sensitive, and an
+/// uppercase letter is a shifted one.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(parse_helix("C-s").unwrap(), key!(ctrl-s));
+/// assert_eq!(parse_helix("A-x").unwrap(), key!(alt-x));
+/// assert_eq!(parse_helix("S-tab").unwrap(), key!(shift-tab));
+/// assert_eq!(parse_helix("ret").unwrap(), key!(enter));
+/// assert_eq!(parse_helix("G").unwrap(), key!(shift-g));
+/// ```
+pub fn parse_helix(raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    let mut modifiers = KeyModifiers::empty();
+    let mut key = raw;
+    loop {
+        let (modifier, len) = if key.len() > 2 && key.starts_with("C-") {
+            (KeyModifiers::CONTROL, 2)
+        } else if key.len() > 2 && key.starts_with("A-") {
+            (KeyModifiers::ALT, 2)
+        } else if key.len() > 2 && key.starts_with("S-") {
+            (KeyModifiers::SHIFT, 2)
+        } else if key.len() > 5 && key.starts_with("Meta-") {
+            (KeyModifiers::SUPER, 5)
+        } else if key.len() > 4 && (key.starts_with("Cmd-") || key.starts_with("Win-")) {
+            (KeyModifiers::SUPER, 4)
+        } else {
+            break;
+        };
+        modifiers.insert(modifier);
+        key = &key[len..];
+    }
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            if c.is_ascii_uppercase() {
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+            Char(c)
+        }
+        _ => helix_key_code(key).ok_or_else(|| ParseKeyError::new(raw))?,
+    };
+    let key = KeyCombination::new(code, modifiers);
+    Ok(key.normalized())
+}
+
+fn helix_key_code(name: &str) -> Option<KeyCode> {
+    let code = match name {
+        "backspace" => Backspace,
+        "space" => Char(' '),
+        "ret" => Enter,
+        "minus" => Char('-'),
+        "left" => Left,
+        "right" => Right,
+        "up" => Up,
+        "down" => Down,
+        "home" => Home,
+        "end" => End,
+        "pageup" => PageUp,
+        "pagedown" => PageDown,
+        "tab" => Tab,
+        "del" => Delete,
+        "ins" => Insert,
+        "null" => Null,
+        "esc" => Esc,
+        _ => {
+            let n = name.strip_prefix('F')?.parse().ok()?;
+            if !(1..=24).contains(&n) {
+                return None;
+            }
+            F(n)
+        }
+    };
+    Some(code)
+}
+
+fn helix_key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        Backspace => "backspace",
+        Char(' ') => "space",
+        Enter | Char('\r') | Char('\n') => "ret",
+        Char('-') => "minus",
+        Left => "left",
+        Right => "right",
+        Up => "up",
+        Down => "down",
+        Home => "home",
+        End => "end",
+        PageUp => "pageup",
+        PageDown => "pagedown",
+        Tab | BackTab => "tab",
+        Delete => "del",
+        Insert => "ins",
+        Null => "null",
+        Esc => "esc",
+        F(n) => {
+            return Some(format!("F{n}"));
+        }
+        Char(c) => {
+            return Some(c.to_string());
+        }
+        _ => {
+            return None;
+        }
+    };
+    Some(name.to_string())
+}
+
+/// Build the Helix description of a key combination.
+///
+/// With letters, shift is expressed with the case of the letter.
+/// Return `None` when the combination can't be expressed in Helix,
+/// for example when it involves several non-modifier keys.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(to_helix_string(key!(ctrl-s)).unwrap(), "C-s");
+/// assert_eq!(to_helix_string(key!(alt-shift-x)).unwrap(), "A-X");
+/// assert_eq!(to_helix_string(key!(shift-tab)).unwrap(), "S-tab");
+/// ```
+pub fn to_helix_string(key: KeyCombination) -> Option<String> {
+    let key = key.normalized();
+    let code = match key.codes {
+        OneToThree::One(code) => code,
+        _ => {
+            return None;
+        }
+    };
+    let mut s = String::new();
+    let shift_in_case = matches!(code, Char(c) if c.is_ascii_uppercase());
+    if key.modifiers.contains(KeyModifiers::SHIFT) && !shift_in_case {
+        s.push_str("S-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        s.push_str("A-");
+    }
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        s.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::SUPER) {
+        s.push_str("Meta-");
+    }
+    s.push_str(&helix_key_name(code)?);
+    Some(s)
+}
+
+#[test]
+fn check_helix_round_trip() {
+    for raw in ["C-s", "A-x", "A-X", "S-tab", "ret", "space", "C-minus", "F5", "S-A-C-left", "Meta-p"] {
+        let key = parse_helix(raw).unwrap();
+        assert_eq!(to_helix_string(key).unwrap(), raw);
+    }
+    assert_eq!(parse_helix("C-S-p").unwrap(), key!(ctrl-shift-p));
+    assert_eq!(parse_helix("Cmd-s").unwrap(), parse_helix("Meta-s").unwrap());
+    assert_eq!(parse_helix("ඞ").unwrap(), key!('ඞ'));
+    assert!(parse_helix("F25").is_err());
+    assert!(parse_helix("Ret").is_err());
+    assert!(to_helix_string(key!(ctrl-a-b)).is_none());
+}
@@ -0,0 +1,400 @@
+use {
+    crate::*,
+    std::{
+        fmt,
+        io,
+    },
+};
+
+/// A line of a help screen: keys and what they do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    /// The formatted key sequence
+    pub keys: String,
+    /// The description of the binding, or the action when there's none
+    pub description: String,
+    /// Whether the binding is enabled, disabled ones being usually
+    /// displayed greyed out
+    pub enabled: bool,
+}
+
+/// A group of help entries sharing a category
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpSection {
+    /// The category, `None` for the bindings without one
+    pub category: Option<String>,
+    pub entries: Vec<HelpEntry>,
+}
+
+impl<A: fmt::Display> KeyBindingMap<A> {
+    /// Build the content of a help screen or cheatsheet from the bindings
+    /// and their [metadata](BindingMeta).
+    ///
+    /// Sections are sorted by category, the bindings without category
+    /// coming last. Entries are sorted by keys, like in
+    /// [KeyBindingMap::iter_sorted]. Hidden bindings are omitted,
+    /// disabled ones are kept but marked. The [pattern](KeyBindingMap::insert_pattern)
+    /// bindings, which have no metadata, end the section without category.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// let file = |description: &str| BindingMeta {
+    ///     category: Some("File".to_string()),
+    ///     description: Some(description.to_string()),
+    ///     ..Default::default()
+    /// };
+    /// map.insert_with_meta("ctrl-s", "save", file("Save the file")).unwrap();
+    /// map.insert_with_meta("ctrl-o", "open", file("Open a file")).unwrap();
+    /// map.insert("ctrl-q", "quit").unwrap();
+    /// map.insert_with_meta("ctrl-d", "debug", BindingMeta { hidden: true, ..Default::default() }).unwrap();
+    /// let help = map.help(&KeyCombinationFormat::default());
+    /// assert_eq!(help.len(), 2);
+    /// assert_eq!(help[0].category.as_deref(), Some("File"));
+    /// assert_eq!(help[0].entries[0].keys, "Ctrl-o");
+    /// assert_eq!(help[0].entries[0].description, "Open a file");
+    /// assert_eq!(help[1].category, None);
+    /// assert_eq!(help[1].entries[0].description, "quit");
+    /// map.disable_category("File");
+    /// assert!(!map.help(&KeyCombinationFormat::default())[0].entries[0].enabled);
+    /// ```
+    pub fn help(&self, format: &KeyCombinationFormat) -> Vec<HelpSection> {
+        let mut sections: Vec<HelpSection> = Vec::new();
+        let bindings = self.iter_sorted().filter_map(|(keys, action)| {
+            let meta = self.meta(keys);
+            if meta.map_or(false, |meta| meta.hidden) {
+                return None;
+            }
+            let category = meta.and_then(|meta| meta.category.clone());
+            let description = meta
+                .and_then(|meta| meta.description.clone())
+                .unwrap_or_else(|| action.to_string());
+            let entry = HelpEntry {
+                keys: format.format_sequence(keys),
+                description,
+                enabled: self.is_enabled(keys),
+            };
+            Some((category, entry))
+        });
+        let patterns = self.patterns().map(|(pattern, action)| {
+            let entry = HelpEntry {
+                keys: pattern.to_string_with(format),
+                description: action.to_string(),
+                enabled: true,
+            };
+            (None, entry)
+        });
+        for (category, entry) in bindings.chain(patterns) {
+            match sections.iter_mut().find(|section| section.category == category) {
+                Some(section) => section.entries.push(entry),
+                None => sections.push(HelpSection {
+                    category,
+                    entries: vec![entry],
+                }),
+            }
+        }
+        // the bindings without category come last
+        sections.sort_by(|a, b| match (&a.category, &b.category) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        sections
+    }
+}
+
+/// The file formats in which [KeyBindingMap::write_cheatsheet] can
+/// write a shortcut reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheatsheetFormat {
+    /// A title per category and a table of keys and descriptions
+    Markdown,
+    /// Aligned columns, for a terminal or a man page
+    PlainText,
+    /// An array of sections, each with its category and bindings
+    Json,
+}
+
+impl<A: fmt::Display> KeyBindingMap<A> {
+    /// Write a complete shortcut reference, grouped and sorted like
+    /// the [help](KeyBindingMap::help), e.g. for a `--dump-keys` option.
+    ///
+    /// Hidden bindings are omitted, disabled ones are marked.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert_with_meta("ctrl-s", "save", BindingMeta {
+    ///     category: Some("File".to_string()),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// map.insert("ctrl-q", "quit").unwrap();
+    /// let mut text = Vec::new();
+    /// map.write_cheatsheet(&mut text, CheatsheetFormat::PlainText, &KeyCombinationFormat::default())
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(text).unwrap(),
+    ///     "File\n  Ctrl-s  save\n\nOther\n  Ctrl-q  quit\n",
+    /// );
+    /// let mut json = Vec::new();
+    /// map.write_cheatsheet(&mut json, CheatsheetFormat::Json, &KeyCombinationFormat::default())
+    ///     .unwrap();
+    /// assert!(String::from_utf8(json).unwrap().contains(
+    ///     r#"{"keys":"Ctrl-q","description":"quit","enabled":true}"#
+    /// ));
+    /// ```
+    pub fn write_cheatsheet<W: io::Write>(
+        &self,
+        w: &mut W,
+        format: CheatsheetFormat,
+        key_format: &KeyCombinationFormat,
+    ) -> io::Result<()> {
+        let sections = self.help(key_format);
+        let several = sections.len() > 1;
+        match format {
+            CheatsheetFormat::Markdown => {
+                for (i, section) in sections.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(w)?;
+                    }
+                    if let Some(title) = section_title(section, several) {
+                        writeln!(w, "## {}\n", title)?;
+                    }
+                    writeln!(w, "| Keys | Action |")?;
+                    writeln!(w, "|-|-|")?;
+                    for entry in &section.entries {
+                        write!(
+                            w,
+                            "| {} | {}",
+                            markdown_cell(&entry.keys),
+                            markdown_cell(&entry.description),
+                        )?;
+                        if !entry.enabled {
+                            write!(w, " *(disabled)*")?;
+                        }
+                        writeln!(w, " |")?;
+                    }
+                }
+            }
+            CheatsheetFormat::PlainText => {
+                let width = sections
+                    .iter()
+                    .flat_map(|section| &section.entries)
+                    .map(|entry| entry.keys.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                for (i, section) in sections.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(w)?;
+                    }
+                    let indent = match section_title(section, several) {
+                        Some(title) => {
+                            writeln!(w, "{}", title)?;
+                            "  "
+                        }
+                        None => "",
+                    };
+                    for entry in &section.entries {
+                        let padding = width - entry.keys.chars().count();
+                        write!(w, "{}{}{:padding$}  {}", indent, entry.keys, "", entry.description)?;
+                        if !entry.enabled {
+                            write!(w, " (disabled)")?;
+                        }
+                        writeln!(w)?;
+                    }
+                }
+            }
+            CheatsheetFormat::Json => {
+                writeln!(w, "[")?;
+                for (i, section) in sections.iter().enumerate() {
+                    let category = match &section.category {
+                        Some(category) => json_string(category),
+                        None => "null".to_string(),
+                    };
+                    writeln!(w, "  {{\"category\":{},\"bindings\":[", category)?;
+                    for (j, entry) in section.entries.iter().enumerate() {
+                        writeln!(
+                            w,
+                            "    {{\"keys\":{},\"description\":{},\"enabled\":{}}}{}",
+                            json_string(&entry.keys),
+                            json_string(&entry.description),
+                            entry.enabled,
+                            if j + 1 < section.entries.len() { "," } else { "" },
+                        )?;
+                    }
+                    writeln!(w, "  ]}}{}", if i + 1 < sections.len() { "," } else { "" })?;
+                }
+                writeln!(w, "]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The bindings without category only get a title when there are others
+fn section_title(section: &HelpSection, several: bool) -> Option<&str> {
+    match &section.category {
+        Some(category) => Some(category),
+        None if several => Some("Other"),
+        None => None,
+    }
+}
+
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// A binding found by [KeyBindingMap::search]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingMatch<'m, A> {
+    pub keys: &'m KeySequence,
+    pub action: &'m A,
+    /// How well the binding matches the query, higher is better
+    pub score: u32,
+}
+
+impl<A: fmt::Display> KeyBindingMap<A> {
+    /// Find the bindings whose action, description or keys fuzzily match
+    /// the query, best matches first, e.g. for a "which key does X?" palette.
+    ///
+    /// The chars of the query must all be found, in order and ignoring case,
+    /// in one of the strings. Consecutive chars and word starts rank higher.
+    /// Hidden bindings are omitted.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-s", "save").unwrap();
+    /// map.insert("ctrl-shift-s", "save_as").unwrap();
+    /// map.insert("ctrl-q", "quit").unwrap();
+    /// map.insert_with_meta("ctrl-w", "close", BindingMeta {
+    ///     description: Some("Close the current tab".to_string()),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// let format = KeyCombinationFormat::default();
+    /// let found: Vec<_> = map.search("sav", &format).iter().map(|m| *m.action).collect();
+    /// assert_eq!(found, vec!["save", "save_as"]);
+    /// assert_eq!(*map.search("tab", &format)[0].action, "close");
+    /// assert_eq!(*map.search("ctrl-q", &format)[0].action, "quit");
+    /// ```
+    pub fn search(&self, query: &str, format: &KeyCombinationFormat) -> Vec<BindingMatch<'_, A>> {
+        let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+        let mut matches = Vec::new();
+        for (keys, action) in self.iter() {
+            let meta = self.meta(keys);
+            if meta.map_or(false, |meta| meta.hidden) {
+                continue;
+            }
+            let action_score = fuzzy_score(&query, &action.to_string());
+            let description_score = meta
+                .and_then(|meta| meta.description.as_deref())
+                .and_then(|description| fuzzy_score(&query, description));
+            let keys_score = fuzzy_score(&query, &format.format_sequence(keys));
+            let score = action_score.max(description_score).max(keys_score);
+            if let Some(score) = score {
+                matches.push(BindingMatch { keys, action, score });
+            }
+        }
+        matches.sort_by(|a, b| {
+            b.score.cmp(&a.score)
+                .then_with(|| a.keys.len().cmp(&b.keys.len()))
+                .then_with(|| a.action.to_string().cmp(&b.action.to_string()))
+        });
+        matches
+    }
+}
+
+/// Score the match of a lowercase query in a candidate, None when some
+/// chars of the query aren't found in order
+fn fuzzy_score(query: &[char], candidate: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut query_chars = query.iter().peekable();
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in candidate.chars() {
+        let expected = match query_chars.peek() {
+            Some(&&expected) => expected,
+            None => break,
+        };
+        let matched = c.to_lowercase().eq(std::iter::once(expected));
+        if matched {
+            score += 1;
+            if previous_matched {
+                score += 4;
+            }
+            if previous.map_or(true, |p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+            query_chars.next();
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    if query_chars.peek().is_some() {
+        return None;
+    }
+    // among equal matches, prefer the shortest candidates
+    let extra = candidate.chars().count().saturating_sub(query.len()).min(20) as u32;
+    Some(score * 20 + 20 - extra)
+}
+
+#[test]
+fn check_fuzzy_score() {
+    let score = |query: &str, candidate| {
+        let query: Vec<char> = query.chars().collect();
+        fuzzy_score(&query, candidate)
+    };
+    assert_eq!(score("xyz", "save"), None);
+    assert_eq!(score("vas", "save"), None);
+    assert!(score("sa", "save").unwrap() > score("sa", "search_all").unwrap());
+    assert!(score("sa", "save").unwrap() > score("sa", "save_all").unwrap());
+    assert!(score("ct", "close_tab").unwrap() > score("ct", "cut").unwrap());
+    assert!(score("", "anything").is_some());
+}
+
+#[test]
+fn check_cheatsheet_escaping() {
+    assert_eq!(json_string("a \"b\"\\\n"), r#""a \"b\"\\\n""#);
+    assert_eq!(markdown_cell("Shift-|"), r"Shift-\|");
+    let mut map = KeyBindingMap::new();
+    map.insert("shift-|", "pipe").unwrap();
+    map.set_enabled(&[key!(shift-'|')], false);
+    let mut markdown = Vec::new();
+    map.write_cheatsheet(&mut markdown, CheatsheetFormat::Markdown, &KeyCombinationFormat::default())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(markdown).unwrap(),
+        "| Keys | Action |\n|-|-|\n| Shift-\\| | pipe *(disabled)* |\n",
+    );
+}
+
+#[test]
+fn check_cheatsheet_patterns() {
+    let mut map = KeyBindingMap::new();
+    map.insert("ctrl-q", "quit").unwrap();
+    map.insert_pattern("*-enter", "confirm").unwrap();
+    let mut text = Vec::new();
+    map.write_cheatsheet(&mut text, CheatsheetFormat::Markdown, &KeyCombinationFormat::default())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(text).unwrap(),
+        "| Keys | Action |\n|-|-|\n| Ctrl-q | quit |\n| *-Enter | confirm |\n",
+    );
+}
@@ -0,0 +1,241 @@
+//! Extraction of the key bindings defined in the configuration files
+//! of other applications, for migration and compatibility tooling.
+//!
+//! The files are read with the `toml` crate, this module needing the
+//! "import" feature.
+
+use {
+    crate::*,
+    std::fmt,
+    toml::Value,
+};
+
+/// A key binding found in the configuration of another application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedBinding {
+    /// Where the binding applies, e.g. the mode in Helix
+    pub context: Option<String>,
+    pub keys: KeySequence,
+    /// What the binding does, as written in the configuration
+    pub command: String,
+}
+
+/// Error raised when importing bindings
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file isn't valid TOML
+    Toml(toml::de::Error),
+    /// A value doesn't have the expected type, e.g. a key given as a number
+    Value { path: String },
+    /// A key can't be parsed
+    Key { path: String, error: ParseKeyError },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Toml(e) => write!(f, "invalid TOML: {}", e),
+            Self::Value { path } => write!(f, "unexpected value at {}", path),
+            Self::Key { path, error } => write!(f, "invalid key at {}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Toml(e) => Some(e),
+            Self::Value { .. } => None,
+            Self::Key { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<toml::de::Error> for ImportError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+
+/// Extract the keys of the verbs of a [broot](https://dystroy.org/broot)
+/// configuration written in TOML.
+///
+/// Broot keys use the crokey syntax. The command is the invocation of the
+/// verb or, when there's none, its internal or external execution.
+///
+/// ```
+/// use crokey::*;
+/// let bindings = import_broot_verbs(r#"
+///     [[verbs]]
+///     invocation = "edit"
+///     key = "ctrl-e"
+///     execution = "$EDITOR {file}"
+///
+///     [[verbs]]
+///     keys = ["alt-h", "ctrl-h"] # both toggle
+///     internal = ":toggle_hidden"
+/// "#).unwrap();
+/// assert_eq!(bindings.len(), 3);
+/// assert_eq!(bindings[0].keys, KeySequence::from(key!(ctrl-e)));
+/// assert_eq!(bindings[0].command, "edit");
+/// assert_eq!(bindings[2].keys, KeySequence::from(key!(ctrl-h)));
+/// assert_eq!(bindings[2].command, ":toggle_hidden");
+/// ```
+pub fn import_broot_verbs(toml: &str) -> Result<Vec<ImportedBinding>, ImportError> {
+    let root: Value = toml.parse()?;
+    let verbs = match root.get("verbs") {
+        Some(Value::Array(verbs)) => verbs.as_slice(),
+        Some(_) => return Err(ImportError::Value { path: "verbs".to_string() }),
+        None => &[],
+    };
+    let mut bindings = Vec::new();
+    for (idx, verb) in verbs.iter().enumerate() {
+        let path = |field: &str| format!("verbs[{}].{}", idx, field);
+        let string = |field: &str| match verb.get(field) {
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(ImportError::Value { path: path(field) }),
+            None => Ok(None),
+        };
+        let mut keys = Vec::new();
+        keys.extend(string("key")?);
+        match verb.get("keys") {
+            Some(Value::Array(values)) => {
+                for value in values {
+                    match value {
+                        Value::String(key) => keys.push(key.clone()),
+                        _ => return Err(ImportError::Value { path: path("keys") }),
+                    }
+                }
+            }
+            Some(_) => return Err(ImportError::Value { path: path("keys") }),
+            None => {}
+        }
+        let command = match string("invocation")? {
+            Some(command) => command,
+            None => match string("internal")? {
+                Some(command) => command,
+                None => match string("cmd")? {
+                    Some(command) => command,
+                    None => string("execution")?.unwrap_or_default(),
+                },
+            },
+        };
+        for key in keys {
+            let key = parse(&key).map_err(|error| ImportError::Key { path: path("keys"), error })?;
+            bindings.push(ImportedBinding {
+                context: None,
+                keys: key.into(),
+                command: command.clone(),
+            });
+        }
+    }
+    Ok(bindings)
+}
+
+/// Extract the key bindings of a [Helix](https://helix-editor.com)
+/// configuration (the `[keys.*]` tables of its `config.toml`).
+///
+/// The context of a binding is its mode (`normal`, `insert` or `select`).
+/// Nested tables make key sequences. A list of commands is joined with spaces.
+/// The bindings are sorted by mode, then by key.
+///
+/// ```
+/// use crokey::*;
+/// let bindings = import_helix_keys(r#"
+///     [keys.normal]
+///     C-s = ":w"
+///     g = { a = "code_action" }
+///
+///     [keys.normal.space]
+///     q = [":w", ":q"]
+///
+///     [keys.insert]
+///     "j" = { k = "normal_mode" }
+/// "#).unwrap();
+/// assert_eq!(bindings.len(), 4);
+/// assert_eq!(bindings[0].context.as_deref(), Some("insert"));
+/// assert_eq!(bindings[1].context.as_deref(), Some("normal"));
+/// assert_eq!(bindings[1].keys, KeySequence::from(key!(ctrl-s)));
+/// assert_eq!(bindings[2].keys, KeySequence::new(vec![key!(g), key!(a)]));
+/// assert_eq!(bindings[3].keys, KeySequence::new(vec![key!(space), key!(q)]));
+/// assert_eq!(bindings[3].command, ":w :q");
+/// ```
+pub fn import_helix_keys(toml: &str) -> Result<Vec<ImportedBinding>, ImportError> {
+    fn collect(
+        path: &mut Vec<String>,
+        value: &Value,
+        bindings: &mut Vec<ImportedBinding>,
+    ) -> Result<(), ImportError> {
+        let dotted_path = || format!("keys.{}", path.join("."));
+        let command = match value {
+            Value::Table(entries) => {
+                for (key, value) in entries {
+                    path.push(key.clone());
+                    collect(path, value, bindings)?;
+                    path.pop();
+                }
+                return Ok(());
+            }
+            Value::String(command) => command.clone(),
+            Value::Array(commands) => {
+                let commands: Option<Vec<&str>> = commands.iter().map(Value::as_str).collect();
+                commands
+                    .ok_or_else(|| ImportError::Value { path: dotted_path() })?
+                    .join(" ")
+            }
+            _ => return Err(ImportError::Value { path: dotted_path() }),
+        };
+        let (mode, keys) = match path.split_first() {
+            Some((mode, keys)) if !keys.is_empty() => (mode, keys),
+            _ => return Err(ImportError::Value { path: dotted_path() }),
+        };
+        let keys = keys
+            .iter()
+            .map(|key| parse_helix(key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| ImportError::Key { path: dotted_path(), error })?;
+        bindings.push(ImportedBinding {
+            context: Some(mode.clone()),
+            keys: KeySequence::new(keys),
+            command,
+        });
+        Ok(())
+    }
+    let root: Value = toml.parse()?;
+    let mut bindings = Vec::new();
+    match root.get("keys") {
+        Some(keys @ Value::Table(_)) => collect(&mut Vec::new(), keys, &mut bindings)?,
+        Some(_) => return Err(ImportError::Value { path: "keys".to_string() }),
+        None => {}
+    }
+    Ok(bindings)
+}
+
+#[test]
+fn check_imports() {
+    let bindings = import_broot_verbs(r#"
+        [[verbs]]
+        keys = [
+            "alt-h", # the first one
+            "ctrl-h",
+        ]
+        internal = ":toggle_hidden"
+    "#).unwrap();
+    assert_eq!(bindings.len(), 2);
+    assert_eq!(bindings[1].keys, KeySequence::from(key!(ctrl-h)));
+    assert!(matches!(
+        import_broot_verbs("[[verbs]]\nkey = ["),
+        Err(ImportError::Toml(_)),
+    ));
+    assert!(matches!(
+        import_broot_verbs("[[verbs]]\nkey = 3"),
+        Err(ImportError::Value { path }) if path == "verbs[0].key",
+    ));
+    assert!(matches!(
+        import_helix_keys("[keys.normal]\nC-nothing = \"x\""),
+        Err(ImportError::Key { path, error }) if path == "keys.normal.C-nothing" && error.raw == "C-nothing",
+    ));
+    let bindings = import_helix_keys(r#"keys.select.space.x = ["a", 'b\c']"#).unwrap();
+    assert_eq!(bindings[0].keys, KeySequence::new(vec![key!(space), key!(x)]));
+    assert_eq!(bindings[0].command, r"a b\c");
+}
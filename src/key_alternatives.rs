@@ -0,0 +1,187 @@
+use {
+    crate::{
+        key_sequence::{
+            parse_repeated,
+            write_keys,
+        },
+        *,
+    },
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+    std::{
+        fmt,
+        str::FromStr,
+    },
+};
+
+#[cfg(feature = "serde")]
+use {
+    crate::key_combination::FromStrVisitor,
+    serde::{
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+};
+
+/// A list of alternative key sequences, any of them triggering the same
+/// action, for example `ctrl-c` or `ctrl-q`.
+///
+/// In strings, the alternatives are separated with a `|` surrounded with spaces:
+///
+/// ```
+/// use crokey::*;
+/// let alternatives: KeyAlternatives = "ctrl-c | ctrl-x ctrl-c".parse().unwrap();
+/// assert_eq!(alternatives.sequences.len(), 2);
+/// assert!(alternatives.contains(&[key!(ctrl-c)]));
+/// assert!(alternatives.contains(&[key!(ctrl-x), key!(ctrl-c)]));
+/// assert_eq!(alternatives.to_string(), "Ctrl-c | Ctrl-x Ctrl-c");
+/// ```
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct KeyAlternatives {
+    pub sequences: Vec<KeySequence>,
+}
+
+impl KeyAlternatives {
+    pub fn new(sequences: Vec<KeySequence>) -> Self {
+        Self { sequences }
+    }
+    /// Tell whether the given keys are one of the alternatives
+    pub fn contains(&self, keys: &[KeyCombination]) -> bool {
+        self.sequences.iter().any(|sequence| sequence.keys == keys)
+    }
+}
+
+impl From<KeyCombination> for KeyAlternatives {
+    fn from(key: KeyCombination) -> Self {
+        Self { sequences: vec![key.into()] }
+    }
+}
+
+impl From<KeySequence> for KeyAlternatives {
+    fn from(sequence: KeySequence) -> Self {
+        Self { sequences: vec![sequence] }
+    }
+}
+
+impl FromStr for KeyAlternatives {
+    type Err = ParseKeyError;
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        let mut sequences = Vec::new();
+        let mut keys = Vec::new();
+        for token in s.split_whitespace() {
+            if token == "|" {
+                if keys.is_empty() {
+                    return Err(ParseKeyError::new(s));
+                }
+                sequences.push(KeySequence::new(std::mem::take(&mut keys)));
+            } else {
+                let (key, count) = parse_repeated(token)?;
+                keys.extend(std::iter::repeat(key).take(count));
+            }
+        }
+        if keys.is_empty() {
+            return Err(ParseKeyError::new(s));
+        }
+        sequences.push(KeySequence::new(keys));
+        Ok(Self { sequences })
+    }
+}
+
+/// The `|` key, which is written with its code point in alternatives so
+/// that it's not read as a separator
+const PIPE: KeyCombination = KeyCombination::one_key(KeyCode::Char('|'), KeyModifiers::NONE);
+
+impl fmt::Display for KeyAlternatives {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, sequence) in self.sequences.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write_keys(f, &sequence.keys, |f, key| {
+                if key == PIPE {
+                    write!(f, "0x7c")
+                } else {
+                    write!(f, "{}", key)
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyAlternatives {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FromStrVisitor::new("alternative key sequences"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeyAlternatives {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let format = KeyCombinationFormat::config_safe();
+        let sequences: Vec<String> = self
+            .sequences
+            .iter()
+            .map(|sequence| {
+                let keys: Vec<String> = sequence
+                    .keys
+                    .iter()
+                    .map(|&key| if key == PIPE { "0x7c".to_string() } else { format.to_string(key) })
+                    .collect();
+                keys.join(" ")
+            })
+            .collect();
+        serializer.serialize_str(&sequences.join(" | "))
+    }
+}
+
+#[test]
+fn check_key_alternatives_parsing() {
+    assert!("ctrl-q|ctrl-c".parse::<KeyAlternatives>().is_err()); // spaces are needed
+    let alternatives: KeyAlternatives = "shift-| | ctrl-|".parse().unwrap();
+    assert!(alternatives.contains(&[key!(ctrl-'|')]));
+    let alternatives: KeyAlternatives = "ctrl-q | ctrl-c".parse().unwrap();
+    assert_eq!(alternatives.sequences.len(), 2);
+    assert!("".parse::<KeyAlternatives>().is_err());
+    assert!("ctrl-q |".parse::<KeyAlternatives>().is_err());
+    assert!("| ctrl-q".parse::<KeyAlternatives>().is_err());
+    let alternatives = KeyAlternatives::new(vec![
+        KeySequence::from(key!('|')),
+        KeySequence::new(vec![key!(ctrl-x), key!('|')]),
+        KeySequence::from(key!(ctrl-'|')),
+    ]);
+    assert_eq!(alternatives.to_string().parse::<KeyAlternatives>().unwrap(), alternatives);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_key_alternatives_deserialization() {
+    let alternatives: KeyAlternatives = serde_json::from_str(r#""ctrl-q | ctrl-x ctrl-c""#).unwrap();
+    assert!(alternatives.contains(&[key!(ctrl-x), key!(ctrl-c)]));
+    assert!(serde_json::from_str::<KeyAlternatives>("3").is_err());
+    assert!(serde_json::from_str::<KeyAlternatives>(r#""ctrl-q |""#).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_key_alternatives_serialization() {
+    let alternatives = KeyAlternatives::new(vec![
+        KeySequence::new(vec![key!(ctrl-'"'), key!('|')]),
+        KeySequence::from(key!(alt-'#')),
+        KeySequence::from(key!(ctrl-'?')),
+    ]);
+    let json = serde_json::to_string(&alternatives).unwrap();
+    assert_eq!(json, r#""Ctrl-0x22 0x7c | Alt-0x23 | Ctrl-?""#);
+    assert_eq!(serde_json::from_str::<KeyAlternatives>(&json).unwrap(), alternatives);
+}
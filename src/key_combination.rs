@@ -6,7 +6,12 @@ use {
         KeyEventState,
     },
     std::{
+        cmp::Ordering,
         fmt,
+        hash::{
+            Hash,
+            Hasher,
+        },
         str::FromStr,
     },
     strict::OneToThree,
@@ -23,36 +28,87 @@ use serde::{
 
 /// A Key combination wraps from one to three standard keys with optional modifiers
 /// (ctrl, alt, shift).
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+///
+/// The alternate Debug format (`{:#?}`) shows the combination as it's
+/// displayed, then its fields, with the names of the modifiers:
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(
+///     format!("{:#?}", key!(ctrl-alt-x)),
+///     "KeyCombination {\n    key: Ctrl-Alt-x,\n    codes: [\n        Char(\n            'x',\n        ),\n    ],\n    modifiers: CONTROL | ALT,\n}",
+/// );
+/// ```
+///
+/// ## Normalization
+///
+/// Combinations are compared and hashed on their raw fields, so a lookup
+/// only finds a combination written the same way. The `From` and `TryFrom`
+/// conversions, [parse], the [key!] macro and deserialization all return
+/// [normalized](KeyCombination::normalized) combinations, which is the form
+/// to use as key in maps and sets.
+///
+/// [KeyCombination::new], [KeyCombination::one_key] and struct literals
+/// keep the fields as given. In debug builds, hashing a combination which
+/// isn't normalized panics, so that such lookup bugs show up during development.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct KeyCombination {
     pub codes: OneToThree<KeyCode>,
     pub modifiers: KeyModifiers,
 }
 
+/// Return the key which is usually typed to produce an ASCII control char,
+/// with the modifiers it implies, following the caret notation
+/// (e.g. `'\u{1c}'` is `ctrl-\\`).
+///
+/// `'\r'` and `'\n'` are Enter, `'\t'` is Tab, `'\u{1b}'` is Esc and
+/// `'\u{7f}'` is Backspace.
+pub(crate) fn fold_control_char(c: char) -> Option<(KeyCode, KeyModifiers)> {
+    let code = match c {
+        '\r' | '\n' => KeyCode::Enter,
+        '\t' => KeyCode::Tab,
+        '\u{1b}' => KeyCode::Esc,
+        '\u{7f}' => KeyCode::Backspace,
+        '\u{0}'..='\u{1f}' => {
+            let caret = (c as u8 + b'@') as char;
+            return Some((KeyCode::Char(caret.to_ascii_lowercase()), KeyModifiers::CONTROL));
+        }
+        _ => {
+            return None;
+        }
+    };
+    Some((code, KeyModifiers::NONE))
+}
+
 /// Change the char to uppercase when the modifier shift is present,
-/// otherwise if the char is uppercase, return true.
-/// If the key is the `\r' or '\n' char, change it to KeyCode::Enter.
-fn normalize_key_code(code: &mut KeyCode, modifiers: KeyModifiers) -> bool {
-    if matches!(code, KeyCode::Char('\r') | KeyCode::Char('\n')) {
-        *code = KeyCode::Enter;
-    } else if modifiers.contains(KeyModifiers::SHIFT) {
-        if let KeyCode::Char(c) = code {
-            if c.is_ascii_lowercase() {
-                *code = KeyCode::Char(c.to_ascii_uppercase());
-            }
+/// otherwise if the char is uppercase, return SHIFT as an implied modifier.
+/// If the key is an ASCII control char, change it to the key normally
+/// typed for it (e.g. `'\r'` becomes KeyCode::Enter, `'\u{0}'` becomes `@`
+/// with CONTROL implied).
+fn normalize_key_code(code: &mut KeyCode, modifiers: KeyModifiers) -> KeyModifiers {
+    let mut implied = KeyModifiers::NONE;
+    if let KeyCode::Char(c) = code {
+        if let Some((folded, folded_implied)) = fold_control_char(*c) {
+            *code = folded;
+            implied = folded_implied;
         }
-    } else if let KeyCode::Char(c) = code {
-        if c.is_ascii_uppercase() {
-            return true;
+    }
+    if let KeyCode::Char(c) = code {
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            *c = shift_policy::uppercase_char(*c);
+        } else if c.is_uppercase() {
+            implied |= KeyModifiers::SHIFT;
         }
     }
-    false
+    implied
 }
 
 impl KeyCombination {
     /// Create a new KeyCombination from one to three keycodes and a set of modifiers
-    pub fn new<C: Into<OneToThree<KeyCode>>>(codes: C, modifiers: KeyModifiers) -> Self {
+    /// (either a `KeyModifiers` or a [Mods])
+    pub fn new<C: Into<OneToThree<KeyCode>>, M: Into<KeyModifiers>>(codes: C, modifiers: M) -> Self {
         let codes = codes.into().sorted();
+        let modifiers = modifiers.into();
         Self { codes, modifiers }
     }
     /// Create a new KeyCombination from one keycode and a set of modifiers
@@ -77,19 +133,204 @@ impl KeyCombination {
     /// This allows direct comparisons with the fields of crossterm::event::KeyEvent
     /// whose code is uppercase when the shift modifier is present. And supports the
     /// case where the modifier isn't mentionned but the key is uppercase.
+    ///
+    /// ASCII control chars are changed to the key combination usually typed
+    /// to produce them, so that `Char('\u{1d}')` matches `ctrl-]`.
     pub fn normalized(mut self) -> Self {
-        let mut shift = normalize_key_code(self.codes.first_mut(), self.modifiers);
-        if let Some(ref mut code) = self.codes.get_mut(1) {
-            shift |= normalize_key_code(code, self.modifiers);
+        // when a code implies shift, a second pass uppercases the other ones
+        for _ in 0..2 {
+            let mut implied = normalize_key_code(self.codes.first_mut(), self.modifiers);
+            if let Some(ref mut code) = self.codes.get_mut(1) {
+                implied |= normalize_key_code(code, self.modifiers);
+            }
+            if let Some(ref mut code) = self.codes.get_mut(2) {
+                implied |= normalize_key_code(code, self.modifiers);
+            }
+            if self.modifiers.contains(implied) {
+                break;
+            }
+            self.modifiers |= implied;
         }
-        if let Some(ref mut code) = self.codes.get_mut(2) {
-            shift |= normalize_key_code(code, self.modifiers);
+        self.codes = self.codes.sorted();
+        self
+    }
+    /// Return the canonical form of the combination, the one stored in
+    /// binding maps and compared by [KeyCombination::eq_loose].
+    ///
+    /// It's the [normalized](KeyCombination::normalized) combination, i.e. the
+    /// one crossterm sends:
+    /// - a shifted char is uppercase and has the SHIFT modifier
+    /// - an ASCII control char is the key usually typed to produce it (e.g.
+    ///   `'\u{1d}'` is `ctrl-]`, `'\r'` is Enter)
+    /// - the codes are sorted
+    ///
+    /// ```
+    /// use crokey::*;
+    /// use crossterm::event::{KeyCode, KeyModifiers};
+    /// let upper = KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE);
+    /// assert_eq!(upper.canonical(), key!(shift-a));
+    /// ```
+    pub fn canonical(self) -> Self {
+        self.normalized()
+    }
+    /// Tell whether the combination is already [normalized](KeyCombination::normalized)
+    pub fn is_normalized(self) -> bool {
+        self.normalized() == self
+    }
+    /// Panic when the combination isn't [normalized](KeyCombination::normalized),
+    /// for example because it was built with a struct literal.
+    ///
+    /// ```should_panic
+    /// use crokey::*;
+    /// use crossterm::event::{KeyCode, KeyModifiers};
+    /// key!(shift-a).assert_normalized();
+    /// KeyCombination::new(KeyCode::Char('a'), KeyModifiers::SHIFT).assert_normalized();
+    /// ```
+    #[track_caller]
+    pub fn assert_normalized(self) {
+        let normalized = self.normalized();
+        assert!(
+            normalized == self,
+            "key combination {:?} isn't normalized, it should be {:?}",
+            self,
+            normalized,
+        );
+    }
+    /// Tell whether the combinations are the same once canonicalized,
+    /// ignoring whether shift is written as a modifier or as an uppercase
+    /// char, and how control chars are written.
+    ///
+    /// The `==` operator compares the raw fields.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// use crossterm::event::{KeyCode, KeyModifiers};
+    /// let upper = KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE);
+    /// let shifted = KeyCombination::new(KeyCode::Char('a'), KeyModifiers::SHIFT);
+    /// assert_ne!(upper, shifted);
+    /// assert!(upper.eq_loose(&shifted));
+    /// let escape = KeyCombination::new(KeyCode::Char('\u{1b}'), KeyModifiers::NONE);
+    /// assert!(escape.eq_loose(&key!(esc)));
+    /// ```
+    pub fn eq_loose(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+    /// Return the modifiers this combination has in addition to the ones of
+    /// the other combination, when both have the same keys and this one has
+    /// all the modifiers of the other one. Comparison is done on canonical forms.
+    ///
+    /// An extra shift is accepted when it changed the case of the chars, so
+    /// that `ctrl-shift-s` (i.e. `ctrl-S`) contains `ctrl-s`.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// use crossterm::event::KeyModifiers;
+    /// assert_eq!(key!(ctrl-alt-s).extra_modifiers(key!(ctrl-s)), Some(KeyModifiers::ALT));
+    /// assert_eq!(key!(ctrl-shift-s).extra_modifiers(key!(ctrl-s)), Some(KeyModifiers::SHIFT));
+    /// assert_eq!(key!(ctrl-s).extra_modifiers(key!(ctrl-s)), Some(KeyModifiers::NONE));
+    /// assert_eq!(key!(ctrl-s).extra_modifiers(key!(ctrl-alt-s)), None);
+    /// assert_eq!(key!(ctrl-alt-s).extra_modifiers(key!(ctrl-x)), None);
+    /// ```
+    pub fn extra_modifiers(self, other: Self) -> Option<KeyModifiers> {
+        let a = self.canonical();
+        let b = other.canonical();
+        if !a.modifiers.contains(b.modifiers) {
+            return None;
+        }
+        let extra = a.modifiers - b.modifiers;
+        let same_codes = if extra.contains(KeyModifiers::SHIFT) {
+            let unshifted = |key: KeyCombination| {
+                key.codes
+                    .map(|code| match code {
+                        KeyCode::Char(c) => KeyCode::Char(lowercase_char(c)),
+                        KeyCode::BackTab => KeyCode::Tab,
+                        code => code,
+                    })
+                    .sorted()
+            };
+            unshifted(a) == unshifted(b)
+        } else {
+            a.codes == b.codes
+        };
+        if same_codes {
+            Some(extra)
+        } else {
+            None
         }
-        if shift {
-            self.modifiers |= KeyModifiers::SHIFT;
+    }
+    /// Tell whether this combination, usually received from the terminal,
+    /// is a press of the other one, maybe with extra modifiers.
+    ///
+    /// It's a superset test: `ctrl-alt-s` is a press of `ctrl-s`, the
+    /// opposite isn't true. Use it for fallback dispatch when terminals
+    /// add spurious modifiers.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert!(key!(ctrl-alt-s).is_press_of(key!(ctrl-s)));
+    /// assert!(key!(ctrl-s).is_press_of(key!(ctrl-s)));
+    /// assert!(!key!(ctrl-s).is_press_of(key!(ctrl-alt-s)));
+    /// assert!(!key!(ctrl-alt-s).is_press_of(key!(alt-x)));
+    /// ```
+    pub fn is_press_of(self, other: Self) -> bool {
+        self.extra_modifiers(other).is_some()
+    }
+    /// Return the combination with ASCII control chars replaced by the keys
+    /// usually typed to produce them, without any other normalization.
+    pub(crate) fn with_folded_control_chars(mut self) -> Self {
+        for i in 0..3 {
+            if let Some(code) = self.codes.get_mut(i) {
+                if let KeyCode::Char(c) = *code {
+                    if let Some((folded, implied)) = fold_control_char(c) {
+                        *code = folded;
+                        self.modifiers |= implied;
+                    }
+                }
+            }
         }
         self
     }
+    /// Describe the combination in full detail, for logs and bug reports:
+    /// how it's displayed, its raw fields, and its normalized form when
+    /// it's different.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyModifiers},
+    /// };
+    /// assert_eq!(
+    ///     key!(ctrl-a-b).debug_verbose(),
+    ///     "Ctrl-a-b: codes [Char('a'), Char('b')], modifiers CONTROL (0x2), not ansi compatible",
+    /// );
+    /// let raw = KeyCombination::new(KeyCode::Char('\r'), KeyModifiers::NONE);
+    /// assert_eq!(
+    ///     raw.debug_verbose(),
+    ///     "Enter: codes [Char('\\r')], modifiers NONE (0x0), normalized Enter: codes [Enter]",
+    /// );
+    /// ```
+    pub fn debug_verbose(self) -> String {
+        let codes: Vec<KeyCode> = self.codes.iter().copied().collect();
+        let mut s = format!(
+            "{}: codes {:?}, modifiers {} ({:#x})",
+            self,
+            codes,
+            modifier_names(self.modifiers),
+            self.modifiers.bits(),
+        );
+        if !self.is_ansi_compatible() {
+            s.push_str(", not ansi compatible");
+        }
+        let normalized = self.normalized();
+        if normalized != self {
+            let codes: Vec<KeyCode> = normalized.codes.iter().copied().collect();
+            s.push_str(&format!(", normalized {}: codes {:?}", normalized, codes));
+            if normalized.modifiers != self.modifiers {
+                s.push_str(&format!(", modifiers {}", modifier_names(normalized.modifiers)));
+            }
+        }
+        s
+    }
     /// return the raw char if the combination is a letter event
     pub const fn as_letter(self) -> Option<char> {
         match self {
@@ -100,6 +341,78 @@ impl KeyCombination {
             _ => None,
         }
     }
+    /// Return the char and whether shift is pressed, if the combination
+    /// is a single char with no modifier other than shift.
+    ///
+    /// As the combination is usually normalized, the char is uppercase
+    /// when shifted.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(key!(shift-a).as_letter_with_case(), Some(('A', true)));
+    /// assert_eq!(key!(a).as_letter_with_case(), Some(('a', false)));
+    /// assert_eq!(key!(ctrl-a).as_letter_with_case(), None);
+    /// ```
+    pub const fn as_letter_with_case(self) -> Option<(char, bool)> {
+        match self {
+            Self {
+                codes: OneToThree::One(KeyCode::Char(l)),
+                modifiers: KeyModifiers::NONE,
+            } => Some((l, false)),
+            Self {
+                codes: OneToThree::One(KeyCode::Char(l)),
+                modifiers: KeyModifiers::SHIFT,
+            } => Some((l, true)),
+            _ => None,
+        }
+    }
+    /// Return the char if the combination is a single alphanumeric char
+    /// with no modifier other than shift.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(key!(shift-a).as_alphanumeric(), Some('A'));
+    /// assert_eq!(key!(7).as_alphanumeric(), Some('7'));
+    /// assert_eq!(key!('!').as_alphanumeric(), None);
+    /// ```
+    pub fn as_alphanumeric(self) -> Option<char> {
+        self.as_letter_with_case()
+            .map(|(c, _)| c)
+            .filter(|c| c.is_alphanumeric())
+    }
+}
+
+/// A serde visitor parsing a string with `FromStr`, without allocating
+/// when the deserializer can lend the string
+#[cfg(feature = "serde")]
+pub(crate) struct FromStrVisitor<T> {
+    expecting: &'static str,
+    _type: std::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> FromStrVisitor<T> {
+    pub(crate) fn new(expecting: &'static str) -> Self {
+        Self {
+            expecting,
+            _type: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> de::Visitor<'_> for FromStrVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+        s.parse().map_err(E::custom)
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -108,8 +421,7 @@ impl<'de> Deserialize<'de> for KeyCombination {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        FromStr::from_str(&s).map_err(de::Error::custom)
+        deserializer.deserialize_str(FromStrVisitor::new("a key combination"))
     }
 }
 
@@ -119,7 +431,7 @@ impl Serialize for KeyCombination {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.serialize_str(&KeyCombinationFormat::config_safe().to_string(*self))
     }
 }
 
@@ -130,9 +442,100 @@ impl FromStr for KeyCombination {
     }
 }
 
+/// Parse a key combination, so that code bounded on `TryInto<KeyCombination>`
+/// accepts strings as well as combinations.
+///
+/// ```
+/// use crokey::*;
+/// fn bind<K: TryInto<KeyCombination>>(key: K) -> Result<KeyCombination, K::Error> {
+///     key.try_into()
+/// }
+/// assert_eq!(bind("ctrl-s").unwrap(), key!(ctrl-s));
+/// assert_eq!(bind(key!(ctrl-s)).unwrap(), key!(ctrl-s));
+/// assert!(bind("ctrl-nothing").is_err());
+/// ```
+impl TryFrom<&str> for KeyCombination {
+    type Error = ParseKeyError;
+    fn try_from(s: &str) -> Result<Self, ParseKeyError> {
+        parse(s)
+    }
+}
+
+impl TryFrom<String> for KeyCombination {
+    type Error = ParseKeyError;
+    fn try_from(s: String) -> Result<Self, ParseKeyError> {
+        parse(&s)
+    }
+}
+
+impl Hash for KeyCombination {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        debug_assert!(
+            self.is_normalized(),
+            "hashing the key combination {:?} which isn't normalized",
+            self,
+        );
+        self.codes.hash(state);
+        self.modifiers.hash(state);
+    }
+}
+
+/// Combinations are ordered by modifiers, then by codes, so that e.g.
+/// `F2` comes before `F10` and the plain keys before the `ctrl` ones.
+impl Ord for KeyCombination {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.modifiers.bits().cmp(&other.modifiers.bits()).then_with(|| {
+            self.codes
+                .iter()
+                .partial_cmp(other.codes.iter())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+impl PartialOrd for KeyCombination {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for KeyCombination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !f.alternate() {
+            return f
+                .debug_struct("KeyCombination")
+                .field("codes", &self.codes)
+                .field("modifiers", &self.modifiers)
+                .finish();
+        }
+        f.debug_struct("KeyCombination")
+            .field("key", &format_args!("{}", self))
+            .field("codes", &self.codes.iter().collect::<Vec<_>>())
+            .field("modifiers", &format_args!("{}", modifier_names(self.modifiers)))
+            .finish()
+    }
+}
+
+/// The names of the modifiers, e.g. `CONTROL | ALT`, or `NONE`
+fn modifier_names(modifiers: KeyModifiers) -> String {
+    let names: Vec<&str> = modifiers.iter_names().map(|(name, _)| name).collect();
+    if names.is_empty() {
+        "NONE".to_string()
+    } else {
+        names.join(" | ")
+    }
+}
+
 impl fmt::Display for KeyCombination {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        STANDARD_FORMAT.format(*self).fmt(f)
+        #[cfg(feature = "global-format")]
+        {
+            STANDARD_FORMAT.format(*self).fmt(f)
+        }
+        #[cfg(not(feature = "global-format"))]
+        {
+            KeyCombinationFormat::default().format(*self).fmt(f)
+        }
     }
 }
 
@@ -146,18 +549,78 @@ impl From<KeyEvent> for KeyCombination {
     }
 }
 
+/// Error returned when key events can't be made into a key combination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCombinationError {
+    /// There's no key event
+    Empty,
+    /// There are more non-modifier keys than a combination can hold (3)
+    TooManyCodes { n: usize },
+    /// All key events are modifier keys
+    OnlyModifiers,
+}
+
+impl fmt::Display for KeyCombinationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no key to combine"),
+            Self::TooManyCodes { n } => write!(f, "too many keys to combine: {} (max is 3)", n),
+            Self::OnlyModifiers => write!(f, "only modifier keys to combine"),
+        }
+    }
+}
+
+impl std::error::Error for KeyCombinationError {}
+
 impl TryFrom<&[KeyEvent]> for KeyCombination {
-    type Error = &'static str;
-    /// Try to create a KeyCombination from a slice of key events,
-    /// will fail if and only if the slice is empty.
+    type Error = KeyCombinationError;
+    /// Try to create a KeyCombination from a slice of key events.
+    ///
+    /// The modifiers of all events are combined. Modifier keys
+    /// (e.g. `KeyCode::Modifier(LeftShift)`) aren't counted as codes.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers, ModifierKeyCode},
+    /// };
+    /// let a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+    /// let shift = KeyEvent::new(KeyCode::Modifier(ModifierKeyCode::LeftShift), KeyModifiers::SHIFT);
+    /// assert_eq!(KeyCombination::try_from(&[a][..]), Ok(key!(ctrl-a)));
+    /// assert_eq!(KeyCombination::try_from(&[][..]), Err(KeyCombinationError::Empty));
+    /// assert_eq!(KeyCombination::try_from(&[shift][..]), Err(KeyCombinationError::OnlyModifiers));
+    /// assert_eq!(
+    ///     KeyCombination::try_from(&[a, a, a, a][..]),
+    ///     Err(KeyCombinationError::TooManyCodes { n: 4 }),
+    /// );
+    /// ```
     fn try_from(key_events: &[KeyEvent]) -> Result<Self, Self::Error> {
+        if key_events.is_empty() {
+            return Err(KeyCombinationError::Empty);
+        }
+        // built without allocation as it's called for every combination
         let mut modifiers = KeyModifiers::empty();
-        let mut codes = Vec::new();
+        let mut codes: Option<OneToThree<KeyCode>> = None;
+        let mut n = 0;
         for key_event in key_events {
             modifiers |= key_event.modifiers;
-            codes.push(key_event.code);
+            if matches!(key_event.code, KeyCode::Modifier(_)) {
+                continue;
+            }
+            n += 1;
+            let code = key_event.code;
+            codes = match codes {
+                None => Some(OneToThree::One(code)),
+                Some(OneToThree::One(a)) => Some(OneToThree::Two(a, code)),
+                Some(OneToThree::Two(a, b)) => Some(OneToThree::Three(a, b, code)),
+                three => three,
+            };
         }
-        let codes: OneToThree<KeyCode> = codes.try_into()?;
+        let codes = match codes {
+            None => return Err(KeyCombinationError::OnlyModifiers),
+            Some(_) if n > 3 => return Err(KeyCombinationError::TooManyCodes { n }),
+            Some(codes) => codes,
+        };
         let raw = Self::new(codes, modifiers);
         Ok(raw.normalized())
     }
@@ -165,10 +628,11 @@ impl TryFrom<&[KeyEvent]> for KeyCombination {
 
 impl From<KeyCode> for KeyCombination {
     fn from(key_code: KeyCode) -> Self {
-        Self {
+        let raw = Self {
             codes: key_code.into(),
             modifiers: KeyModifiers::empty(),
-        }
+        };
+        raw.normalized()
     }
 }
 
@@ -184,3 +648,67 @@ impl Into<KeyEvent> for KeyCombination {
         }
     }
 }
+
+#[test]
+fn check_constructors_normalize() {
+    use {
+        crossterm::event::KeyModifiers,
+        std::collections::HashSet,
+    };
+    let codes = [
+        KeyCode::Char('a'),
+        KeyCode::Char('A'),
+        KeyCode::Char('é'),
+        KeyCode::Char('\r'),
+        KeyCode::Char('\u{1d}'),
+        KeyCode::Tab,
+        KeyCode::BackTab,
+        KeyCode::F(3),
+    ];
+    let modifiers = [
+        KeyModifiers::NONE,
+        KeyModifiers::SHIFT,
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+    ];
+    let mut set = HashSet::new();
+    for &code in &codes {
+        KeyCombination::from(code).assert_normalized();
+        for &modifiers in &modifiers {
+            let key_event = KeyEvent::new(code, modifiers);
+            let key = KeyCombination::from(key_event);
+            key.assert_normalized();
+            KeyCombination::try_from(&[key_event, key_event][..]).unwrap().assert_normalized();
+            // the written form is read back normalized
+            parse(&key.to_string()).unwrap().assert_normalized();
+            set.insert(key);
+        }
+    }
+    assert!(set.contains(&key!(shift-a)));
+    assert!(KeyCombination::new(KeyCode::Tab, KeyModifiers::SHIFT).is_normalized());
+    for raw in ["shift-tab", "ctrl-A", "alt-é", "shift-é", "return", "0x1d", "a-B"] {
+        parse(raw).unwrap().assert_normalized();
+        raw.parse::<KeyCombination>().unwrap().assert_normalized();
+    }
+}
+
+#[test]
+fn check_control_chars_normalize() {
+    use crossterm::event::KeyModifiers;
+    let modifiers = [
+        KeyModifiers::NONE,
+        KeyModifiers::SHIFT,
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+    ];
+    for c in ('\u{0}'..='\u{1f}').chain(['\u{7f}']) {
+        for &modifiers in &modifiers {
+            let key = KeyCombination::new(KeyCode::Char(c), modifiers);
+            assert!(key.normalized().is_normalized(), "{:?}", key);
+            KeyCombination::from(KeyEvent::new(KeyCode::Char(c), modifiers)).assert_normalized();
+        }
+    }
+    let key = KeyCombination::from(KeyEvent::new(KeyCode::Char('\u{1}'), KeyModifiers::SHIFT));
+    assert_eq!(key, key!(ctrl-shift-a));
+    let key = KeyCombination::new((KeyCode::Char('a'), KeyCode::Char('B')), KeyModifiers::NONE);
+    assert_eq!(key.normalized(), key!(shift-a-b));
+    assert!(key.normalized().is_normalized());
+}
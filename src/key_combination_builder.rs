@@ -0,0 +1,139 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyModifiers,
+        ModifierKeyCode,
+    },
+    std::iter::FromIterator,
+};
+
+/// The maximal number of non-modifier keys in a key combination
+const MAX_CODES: usize = 3;
+
+/// Where a [KeyCombinationBuilder] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildState {
+    /// No key has been received, or only modifier keys
+    Empty,
+    /// There are keys, more may be added
+    Partial,
+    /// The maximal number of keys is reached, other keys are ignored
+    Full,
+}
+
+/// Build a key combination from key events received one after the other,
+/// for custom event loops which don't use a [Combiner].
+///
+/// Modifiers of all events are accumulated, as well as the modifiers whose
+/// keys are pressed. Codes are deduplicated (repeats don't add a code) and
+/// limited to 3. The kind of the events isn't checked.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+/// };
+/// let mut builder = KeyCombinationBuilder::new();
+/// assert_eq!(builder.push(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)), BuildState::Partial);
+/// assert_eq!(builder.push(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL)), BuildState::Partial);
+/// assert_eq!(builder.take(), Some(key!(ctrl-a-b)));
+/// assert_eq!(builder.state(), BuildState::Empty);
+///
+/// let builder: KeyCombinationBuilder = [
+///     KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+///     KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+/// ].into_iter().collect();
+/// assert_eq!(builder.build(), Some(key!(alt-x-y)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombinationBuilder {
+    codes: Vec<KeyCode>,
+    modifiers: KeyModifiers,
+}
+
+impl Default for KeyCombinationBuilder {
+    fn default() -> Self {
+        Self {
+            codes: Vec::new(),
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+}
+
+impl KeyCombinationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Receive a key event, return the new state
+    pub fn push(&mut self, key: KeyEvent) -> BuildState {
+        if let KeyCode::Modifier(modifier) = key.code {
+            self.modifiers |= key.modifiers;
+            self.modifiers |= match modifier {
+                ModifierKeyCode::LeftShift | ModifierKeyCode::RightShift => KeyModifiers::SHIFT,
+                ModifierKeyCode::LeftControl | ModifierKeyCode::RightControl => KeyModifiers::CONTROL,
+                ModifierKeyCode::LeftAlt | ModifierKeyCode::RightAlt => KeyModifiers::ALT,
+                _ => KeyModifiers::NONE,
+            };
+            return self.state();
+        }
+        if self.codes.len() < MAX_CODES {
+            self.modifiers |= key.modifiers;
+            if !self.codes.contains(&key.code) {
+                self.codes.push(key.code);
+            }
+        }
+        self.state()
+    }
+    pub fn state(&self) -> BuildState {
+        match self.codes.len() {
+            0 => BuildState::Empty,
+            MAX_CODES => BuildState::Full,
+            _ => BuildState::Partial,
+        }
+    }
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+    /// Return the normalized key combination, if there's at least one code
+    pub fn build(&self) -> Option<KeyCombination> {
+        let codes: OneToThree<KeyCode> = self.codes.clone().try_into().ok()?;
+        Some(KeyCombination::new(codes, self.modifiers).normalized())
+    }
+    /// Return the key combination and reset the builder
+    pub fn take(&mut self) -> Option<KeyCombination> {
+        let key_combination = self.build();
+        self.clear();
+        key_combination
+    }
+    pub fn clear(&mut self) {
+        self.codes.clear();
+        self.modifiers = KeyModifiers::NONE;
+    }
+}
+
+impl FromIterator<KeyEvent> for KeyCombinationBuilder {
+    fn from_iter<I: IntoIterator<Item = KeyEvent>>(iter: I) -> Self {
+        let mut builder = Self::new();
+        for key in iter {
+            builder.push(key);
+        }
+        builder
+    }
+}
+
+#[test]
+fn check_builder_limits() {
+    let mut builder = KeyCombinationBuilder::new();
+    let shift = KeyEvent::new(KeyCode::Modifier(ModifierKeyCode::LeftShift), KeyModifiers::NONE);
+    assert_eq!(builder.push(shift), BuildState::Empty);
+    assert_eq!(builder.build(), None);
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT);
+    assert_eq!(builder.push(key('A')), BuildState::Partial);
+    assert_eq!(builder.push(key('A')), BuildState::Partial);
+    assert_eq!(builder.push(key('B')), BuildState::Partial);
+    assert_eq!(builder.push(key('C')), BuildState::Full);
+    assert_eq!(builder.push(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)), BuildState::Full);
+    assert_eq!(builder.take(), Some(key!(shift-a-b-c)));
+}
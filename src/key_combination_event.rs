@@ -0,0 +1,117 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyEvent,
+        KeyEventKind,
+        KeyEventState,
+    },
+    std::time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// A key combination with the kind and state of the key event which
+/// produced it.
+///
+/// Converting a [KeyCombination] into a `KeyEvent` makes a press without
+/// state, while a `KeyCombinationEvent` keeps them, so that a key event
+/// can be converted and converted back without loss (apart from the
+/// normalization of the key).
+///
+/// The events produced by a combiner also carry the instant at which the
+/// first key of the combination was pressed, for latency measurements or
+/// timing dependent features like double taps.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+/// };
+/// let key_event = KeyEvent {
+///     code: KeyCode::Char('a'),
+///     modifiers: KeyModifiers::CONTROL,
+///     kind: KeyEventKind::Repeat,
+///     state: KeyEventState::CAPS_LOCK,
+/// };
+/// let event = KeyCombinationEvent::from(key_event);
+/// assert_eq!(event.combination, key!(ctrl-a));
+/// assert!(event.is_repeat());
+/// assert_eq!(KeyEvent::from(event), key_event);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombinationEvent {
+    pub combination: KeyCombination,
+    pub kind: KeyEventKind,
+    pub state: KeyEventState,
+    /// When the first key of the combination was pressed, if known
+    pub pressed_at: Option<Instant>,
+}
+
+impl KeyCombinationEvent {
+    pub const fn new(
+        combination: KeyCombination,
+        kind: KeyEventKind,
+        state: KeyEventState,
+    ) -> Self {
+        Self {
+            combination,
+            kind,
+            state,
+            pressed_at: None,
+        }
+    }
+    pub const fn with_pressed_at(mut self, pressed_at: Instant) -> Self {
+        self.pressed_at = Some(pressed_at);
+        self
+    }
+    /// Make the event of a key combination being pressed, without state
+    pub const fn press(combination: KeyCombination) -> Self {
+        Self::new(combination, KeyEventKind::Press, KeyEventState::empty())
+    }
+    pub fn is_press(&self) -> bool {
+        self.kind == KeyEventKind::Press
+    }
+    pub fn is_repeat(&self) -> bool {
+        self.kind == KeyEventKind::Repeat
+    }
+    pub fn is_release(&self) -> bool {
+        self.kind == KeyEventKind::Release
+    }
+    /// Return the time elapsed since the first key of the combination
+    /// was pressed, if known
+    pub fn age(&self) -> Option<Duration> {
+        self.pressed_at.map(|pressed_at| pressed_at.elapsed())
+    }
+}
+
+impl From<KeyEvent> for KeyCombinationEvent {
+    fn from(key_event: KeyEvent) -> Self {
+        Self::new(key_event.into(), key_event.kind, key_event.state)
+    }
+}
+
+impl From<KeyCombination> for KeyCombinationEvent {
+    fn from(combination: KeyCombination) -> Self {
+        Self::press(combination)
+    }
+}
+
+impl From<KeyCombinationEvent> for KeyCombination {
+    fn from(event: KeyCombinationEvent) -> Self {
+        event.combination
+    }
+}
+
+/// Only the first code of the key combination is kept
+impl From<KeyCombinationEvent> for KeyEvent {
+    fn from(event: KeyCombinationEvent) -> Self {
+        let KeyCombinationEvent { combination, kind, state, .. } = event;
+        KeyEvent {
+            code: *combination.codes.first(),
+            modifiers: combination.modifiers,
+            kind,
+            state,
+        }
+    }
+}
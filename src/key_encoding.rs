@@ -0,0 +1,210 @@
+//! Encoding of key combinations into the bytes a terminal sends for them,
+//! to simulate a terminal in tests.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+};
+
+/// The way a terminal encodes the keys it sends to the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyEncoding {
+    /// The xterm encoding of terminals without keyboard enhancement:
+    /// plain chars, control bytes, `ESC` prefix for alt, and CSI
+    /// sequences for the functional keys
+    Legacy,
+    /// The kitty keyboard protocol with the flags pushed by crokey, which
+    /// reports presses and releases, so that multi-code combinations can
+    /// be sent
+    Kitty,
+}
+
+const ESC: u8 = 0x1b;
+
+/// Return the bytes a terminal sends when the key combination is typed,
+/// or `None` when the encoding can't express it (e.g. a combination of
+/// several codes or a control char other than a letter in legacy mode).
+///
+/// With the kitty encoding, all codes are pressed then released in reverse
+/// order, which is what a [CombinerCore] needs to produce the combination.
+/// The legacy encoding sends the numpad keys as the main keyboard ones.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(encode_key(key!(ctrl-a), KeyEncoding::Legacy), Some(vec![1]));
+/// assert_eq!(encode_key(key!(alt-x), KeyEncoding::Legacy), Some(b"\x1bx".to_vec()));
+/// assert_eq!(encode_key(key!(ctrl-up), KeyEncoding::Legacy), Some(b"\x1b[1;5A".to_vec()));
+/// assert_eq!(encode_key(key!(ctrl-a-b), KeyEncoding::Legacy), None);
+/// assert_eq!(
+///     encode_key(key!(ctrl-a), KeyEncoding::Kitty),
+///     Some(b"\x1b[97;5:1u\x1b[97;5:3u".to_vec()),
+/// );
+/// ```
+pub fn encode_key(key: KeyCombination, encoding: KeyEncoding) -> Option<Vec<u8>> {
+    let key = key.normalized();
+    match encoding {
+        KeyEncoding::Legacy => match key.without_keypad().codes {
+            OneToThree::One(code) => encode_legacy(code, key.modifiers),
+            _ => None,
+        },
+        KeyEncoding::Kitty => {
+            let codes: Vec<KeyCode> = key.codes.iter().copied().collect();
+            let mut bytes = Vec::new();
+            for &code in &codes {
+                bytes.extend(encode_kitty(code, key.modifiers, 1)?);
+            }
+            for &code in codes.iter().rev() {
+                bytes.extend(encode_kitty(code, key.modifiers, 3)?);
+            }
+            Some(bytes)
+        }
+    }
+}
+
+/// The modifier parameter of CSI sequences
+fn modifier_param(modifiers: KeyModifiers) -> u8 {
+    let mut param = 1;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        param += 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        param += 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        param += 4;
+    }
+    if modifiers.contains(KeyModifiers::SUPER) {
+        param += 8;
+    }
+    param
+}
+
+/// Return the final byte of the `CSI 1;m X` sequence of the key,
+/// or the number of its `CSI n;m ~` sequence
+fn functional_key(code: KeyCode) -> Option<Result<u8, u8>> {
+    let key = match code {
+        KeyCode::Up => Ok(b'A'),
+        KeyCode::Down => Ok(b'B'),
+        KeyCode::Right => Ok(b'C'),
+        KeyCode::Left => Ok(b'D'),
+        KeyCode::End => Ok(b'F'),
+        KeyCode::Home => Ok(b'H'),
+        KeyCode::F(1) => Ok(b'P'),
+        KeyCode::F(2) => Ok(b'Q'),
+        KeyCode::F(3) => Err(13), // CSI R is the cursor position report
+        KeyCode::F(4) => Ok(b'S'),
+        KeyCode::Insert => Err(2),
+        KeyCode::Delete => Err(3),
+        KeyCode::PageUp => Err(5),
+        KeyCode::PageDown => Err(6),
+        KeyCode::F(n @ 5) => Err(n + 10),
+        KeyCode::F(n @ 6..=10) => Err(n + 11),
+        KeyCode::F(n @ 11..=12) => Err(n + 12),
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn encode_legacy(code: KeyCode, modifiers: KeyModifiers) -> Option<Vec<u8>> {
+    let alt = modifiers.contains(KeyModifiers::ALT);
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+    if modifiers.contains(KeyModifiers::SUPER) {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    let plain = match code {
+        KeyCode::Char(c) if ctrl => match c.to_ascii_lowercase() {
+            c @ 'a'..='z' if !modifiers.contains(KeyModifiers::SHIFT) => Some(c as u8 & 0x1f),
+            ' ' => Some(0),
+            _ => return None,
+        },
+        KeyCode::Char(c) => {
+            if alt {
+                bytes.push(ESC);
+            }
+            let mut buf = [0; 4];
+            bytes.extend(c.encode_utf8(&mut buf).as_bytes());
+            return Some(bytes);
+        }
+        KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace | KeyCode::Esc if ctrl => return None,
+        KeyCode::Enter => Some(b'\r'),
+        KeyCode::Tab if !modifiers.contains(KeyModifiers::SHIFT) => Some(b'\t'),
+        KeyCode::Backspace => Some(0x7f),
+        KeyCode::Esc => Some(ESC),
+        _ => None,
+    };
+    if let Some(byte) = plain {
+        if alt {
+            bytes.push(ESC);
+        }
+        bytes.push(byte);
+        return Some(bytes);
+    }
+    if code == KeyCode::BackTab || code == KeyCode::Tab {
+        // shift-tab, the only tab left here
+        return if alt { None } else { Some(b"\x1b[Z".to_vec()) };
+    }
+    let param = modifier_param(modifiers);
+    let sequence = match functional_key(code)? {
+        Ok(final_byte) if param == 1 && matches!(code, KeyCode::F(_)) => {
+            format!("\x1bO{}", final_byte as char)
+        }
+        Ok(final_byte) if param == 1 => format!("\x1b[{}", final_byte as char),
+        Ok(final_byte) => format!("\x1b[1;{}{}", param, final_byte as char),
+        Err(n) if param == 1 => format!("\x1b[{}~", n),
+        Err(n) => format!("\x1b[{};{}~", n, param),
+    };
+    Some(sequence.into_bytes())
+}
+
+/// Encode a press (kind 1) or release (kind 3) of the key
+fn encode_kitty(code: KeyCode, modifiers: KeyModifiers, kind: u8) -> Option<Vec<u8>> {
+    let param = modifier_param(modifiers);
+    let codepoint = match code {
+        KeyCode::Char(c) => c.to_lowercase().next().unwrap_or(c) as u32,
+        KeyCode::Enter => 13,
+        KeyCode::Tab | KeyCode::BackTab => 9,
+        KeyCode::Backspace => 127,
+        KeyCode::Esc => 27,
+        _ => {
+            let sequence = match functional_key(code)? {
+                Ok(final_byte) => format!("\x1b[1;{}:{}{}", param, kind, final_byte as char),
+                Err(n) => format!("\x1b[{};{}:{}~", n, param, kind),
+            };
+            return Some(sequence.into_bytes());
+        }
+    };
+    Some(format!("\x1b[{};{}:{}u", codepoint, param, kind).into_bytes())
+}
+
+#[test]
+fn check_key_encoding() {
+    let legacy = |key| encode_key(key, KeyEncoding::Legacy).map(|b| String::from_utf8(b).unwrap());
+    assert_eq!(legacy(key!(a)).as_deref(), Some("a"));
+    assert_eq!(legacy(key!(shift-a)).as_deref(), Some("A"));
+    assert_eq!(legacy(key!(ctrl-space)).as_deref(), Some("\0"));
+    assert_eq!(legacy(key!(alt-enter)).as_deref(), Some("\x1b\r"));
+    assert_eq!(legacy(key!(shift-tab)).as_deref(), Some("\x1b[Z"));
+    assert_eq!(legacy(key!(f1)).as_deref(), Some("\x1bOP"));
+    assert_eq!(legacy(key!(shift-f3)).as_deref(), Some("\x1b[13;2~"));
+    assert_eq!(legacy(key!(f12)).as_deref(), Some("\x1b[24~"));
+    assert_eq!(legacy(key!(pagedown)).as_deref(), Some("\x1b[6~"));
+    assert_eq!(legacy(key!(home)).as_deref(), Some("\x1b[H"));
+    assert_eq!(legacy(key!(ctrl-enter)), None);
+    assert_eq!(legacy(key!(ctrl-'[')), None);
+    assert_eq!(legacy(key!(kpenter)).as_deref(), Some("\r"));
+    let kitty = |key| encode_key(key, KeyEncoding::Kitty).map(|b| String::from_utf8(b).unwrap());
+    assert_eq!(
+        kitty(key!(ctrl-shift-a)).as_deref(),
+        Some("\x1b[97;6:1u\x1b[97;6:3u"),
+    );
+    assert_eq!(
+        kitty(key!(alt-left-f5)).as_deref(),
+        Some("\x1b[1;3:1D\x1b[15;3:1~\x1b[15;3:3~\x1b[1;3:3D"),
+    );
+    assert_eq!(kitty(key!(kp1)).as_deref(), Some("\x1b[57400;1:1u\x1b[57400;1:3u"));
+    assert_eq!(kitty(key!(unknown)), None);
+}
@@ -15,3 +15,44 @@ pub const fn as_letter(key: KeyEvent) -> Option<char> {
         _ => None,
     }
 }
+
+/// Return the char typed with the crossterm key event, and whether
+/// shift was pressed, if the event is a char event without other modifier.
+///
+/// The char is returned as received: usually uppercase when shifted.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+/// };
+/// let key = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+/// assert_eq!(as_letter_with_case(key), Some(('A', true)));
+/// let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+/// assert_eq!(as_letter_with_case(key), Some(('a', false)));
+/// let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+/// assert_eq!(as_letter_with_case(key), None);
+/// ```
+pub const fn as_letter_with_case(key: KeyEvent) -> Option<(char, bool)> {
+    match key {
+        KeyEvent {
+            code: KeyCode::Char(l),
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => Some((l, false)),
+        KeyEvent {
+            code: KeyCode::Char(l),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        } => Some((l, true)),
+        _ => None,
+    }
+}
+
+/// Return the char typed with the crossterm key event if it's alphanumeric
+/// and typed with no modifier other than shift.
+pub fn as_alphanumeric(key: KeyEvent) -> Option<char> {
+    as_letter_with_case(key)
+        .map(|(c, _)| c)
+        .filter(|c| c.is_alphanumeric())
+}
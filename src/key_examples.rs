@@ -0,0 +1,99 @@
+use {
+    crate::{
+        parse::KEY_NAMES,
+        *,
+    },
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+};
+
+/// The modifiers combined in the examples
+const MODIFIERS: [KeyModifiers; 4] = [
+    KeyModifiers::CONTROL,
+    KeyModifiers::ALT,
+    KeyModifiers::SHIFT,
+    KeyModifiers::SUPER,
+];
+
+impl KeyCombination {
+    /// Iterate over a representative set of key combinations, e.g. to
+    /// render a gallery of a [KeyCombinationFormat] in the documentation
+    /// or to snapshot test it.
+    ///
+    /// The set, always in the same order and without duplicate, holds:
+    /// - every named key (e.g. `esc`, `f12`, `kp0`) without modifier
+    /// - chars: a letter, a digit, a punctuation char and a non-ASCII one
+    /// - `a` and `f1` with every combination of modifiers
+    /// - combinations of several keys
+    ///
+    /// All the combinations are normalized.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let format = KeyCombinationFormat::default();
+    /// let gallery: Vec<String> = KeyCombination::examples()
+    ///     .map(|key| format.to_string(key))
+    ///     .collect();
+    /// assert!(gallery.contains(&"Ctrl-Alt-Super-Shift-a".to_string()));
+    /// assert!(gallery.contains(&"Ctrl-a-b".to_string()));
+    /// ```
+    pub fn examples() -> impl Iterator<Item = KeyCombination> {
+        // the first name of each key, parsed so that e.g. backtab comes with shift
+        let mut codes: Vec<KeyCode> = Vec::new();
+        let mut named = Vec::new();
+        for (name, code) in KEY_NAMES {
+            if !codes.contains(code) {
+                codes.push(*code);
+                named.extend(parse(name).ok());
+            }
+        }
+        let chars = ['a', '1', '?', 'é']
+            .into_iter()
+            .map(|c| KeyCombination::new(KeyCode::Char(c), KeyModifiers::NONE));
+        let with_modifiers = [KeyCode::Char('a'), KeyCode::F(1)]
+            .into_iter()
+            .flat_map(|code| {
+                (1..16_usize).map(move |mask| {
+                    let modifiers = MODIFIERS
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| mask & (1 << i) != 0)
+                        .fold(KeyModifiers::NONE, |all, (_, modifier)| all | *modifier);
+                    KeyCombination::new(code, modifiers)
+                })
+            });
+        let multi_codes = [
+            KeyCombination::new((KeyCode::Char('a'), KeyCode::Char('b')), KeyModifiers::CONTROL),
+            KeyCombination::new((KeyCode::Char(' '), KeyCode::Char('x')), KeyModifiers::NONE),
+            KeyCombination::new(
+                (KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Char('c')),
+                KeyModifiers::NONE,
+            ),
+            KeyCombination::new((KeyCode::Left, KeyCode::Right), KeyModifiers::ALT),
+        ];
+        named
+            .into_iter()
+            .chain(chars)
+            .chain(with_modifiers)
+            .chain(multi_codes)
+            .map(KeyCombination::normalized)
+    }
+}
+
+#[test]
+fn check_key_combination_examples() {
+    let examples: Vec<KeyCombination> = KeyCombination::examples().collect();
+    for (i, key) in examples.iter().enumerate() {
+        assert!(key.is_normalized(), "{:?}", key);
+        assert!(!examples[..i].contains(key), "duplicate {:?}", key);
+    }
+    let format = KeyCombinationFormat::default();
+    for key in &examples {
+        assert_eq!(format.parse(&format.to_string(*key)).unwrap(), *key);
+    }
+    assert!(examples.contains(&key!(backtab)));
+    assert!(examples.contains(&key!(ctrl-alt-shift-super-f1)));
+    assert_eq!(KeyCombination::examples().count(), examples.len());
+}
@@ -0,0 +1,220 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyModifiers,
+    },
+    std::{
+        collections::VecDeque,
+        fmt::Write,
+        sync::Arc,
+        time::Instant,
+    },
+};
+
+/// What was recorded in a [KeyLog]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLogRecord {
+    /// A key event received from the terminal
+    Event(KeyEvent),
+    /// A key combination produced by the combiner
+    Combination(KeyCombination),
+}
+
+/// An entry of a [KeyLog]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLogEntry {
+    pub at: Instant,
+    pub record: KeyLogRecord,
+}
+
+/// A bounded log of the last key events and key combinations, to help
+/// diagnose terminal specific key issues.
+///
+/// Give it to a [Pipeline] with [Pipeline::with_key_log], or feed it
+/// yourself, then [dump](KeyLog::dump) it when the user wants to report
+/// a bug.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+/// };
+/// # #[cfg(feature = "terminal")] {
+/// let mut pipeline = Pipeline::default().with_key_log(100);
+/// pipeline.transform(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+/// pipeline.transform(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+/// let log = pipeline.key_log().unwrap();
+/// assert_eq!(log.len(), 4);
+/// let dump = log.dump();
+/// assert!(dump.contains("Ctrl-s"));
+/// assert!(!dump.contains("'p'")); // typed text is redacted
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyLog {
+    capacity: usize,
+    entries: VecDeque<KeyLogEntry>,
+    clock: Arc<dyn Clock>,
+}
+
+impl KeyLog {
+    /// Create a log keeping at most `capacity` entries, the oldest
+    /// ones being dropped first
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock to date the entries
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.set_shared_clock(Arc::new(clock));
+        self
+    }
+    pub(crate) fn set_shared_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    pub fn record_event(&mut self, key_event: KeyEvent) {
+        self.record(KeyLogRecord::Event(key_event));
+    }
+    pub fn record_combination(&mut self, key_combination: KeyCombination) {
+        self.record(KeyLogRecord::Combination(key_combination));
+    }
+    pub fn record(&mut self, record: KeyLogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(KeyLogEntry {
+            at: self.clock.now(),
+            record,
+        });
+    }
+    /// Iterate over the entries, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &KeyLogEntry> {
+        self.entries.iter()
+    }
+    /// Build a textual trace of the log, with a header describing the
+    /// terminal, for bug reports.
+    ///
+    /// The chars typed without ctrl, alt or super are redacted (only their
+    /// class is given) so that the trace doesn't disclose the user's text.
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        let _ = writeln!(
+            dump,
+            "crokey {} on {}, TERM={}, TERM_PROGRAM={}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::var("TERM").unwrap_or_default(),
+            std::env::var("TERM_PROGRAM").unwrap_or_default(),
+        );
+        let start = match self.entries.front() {
+            Some(entry) => entry.at,
+            None => return dump,
+        };
+        for entry in &self.entries {
+            let elapsed = entry.at.duration_since(start).as_secs_f64();
+            let _ = match entry.record {
+                KeyLogRecord::Event(e) => writeln!(
+                    dump,
+                    "{:>9.3}s event {:?} {} {} {}",
+                    elapsed,
+                    e.kind,
+                    redacted_code(e.code, e.modifiers),
+                    flag_names(e.modifiers.iter_names()),
+                    flag_names(e.state.iter_names()),
+                ),
+                KeyLogRecord::Combination(key) => writeln!(
+                    dump,
+                    "{:>9.3}s combination {}",
+                    elapsed,
+                    redacted_combination(key),
+                ),
+            };
+        }
+        dump
+    }
+}
+
+fn flag_names<'f, T>(names: impl Iterator<Item = (&'f str, T)>) -> String {
+    let names: Vec<&str> = names.map(|(name, _)| name).collect();
+    if names.is_empty() {
+        "NONE".to_string()
+    } else {
+        names.join("|")
+    }
+}
+
+/// Tell whether the key may be part of some text the user types
+fn is_text(code: KeyCode, modifiers: KeyModifiers) -> bool {
+    matches!(code, KeyCode::Char(c) if c != ' ')
+        && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
+}
+
+fn redacted_code(code: KeyCode, modifiers: KeyModifiers) -> String {
+    match code {
+        KeyCode::Char(c) if is_text(code, modifiers) => {
+            let class = if c.is_lowercase() {
+                "lowercase"
+            } else if c.is_uppercase() {
+                "uppercase"
+            } else if c.is_numeric() {
+                "digit"
+            } else {
+                "other"
+            };
+            format!("Char(<{}>)", class)
+        }
+        _ => format!("{:?}", code),
+    }
+}
+
+fn redacted_combination(key: KeyCombination) -> String {
+    if !key.codes.iter().any(|&code| is_text(code, key.modifiers)) {
+        return key.to_string();
+    }
+    let mut s = String::new();
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        s.push_str("Shift-");
+    }
+    for (i, &code) in key.codes.iter().enumerate() {
+        if i > 0 {
+            s.push('-');
+        }
+        s.push_str(&redacted_code(code, key.modifiers));
+    }
+    s
+}
+
+#[test]
+fn check_key_log_redaction() {
+    let mut log = KeyLog::new(3);
+    log.record_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+    log.record_combination(key!(shift-p));
+    log.record_combination(key!(alt-p));
+    log.record_event(KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE));
+    assert_eq!(log.len(), 3);
+    let dump = log.dump();
+    let lines: Vec<&str> = dump.lines().skip(1).collect();
+    assert!(lines[0].ends_with("combination Shift-Char(<uppercase>)"), "{}", lines[0]);
+    assert!(lines[1].ends_with("combination Alt-p"), "{}", lines[1]);
+    assert!(lines[2].contains("event Press Char(<digit>) NONE"), "{}", lines[2]);
+}
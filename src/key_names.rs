@@ -0,0 +1,60 @@
+use crossterm::event::KeyCode;
+
+/// Custom names of keys, used by a [KeyCombinationFormat](crate::KeyCombinationFormat)
+/// both to write keys and to parse them, so that display customizations
+/// are accepted as input.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::KeyCode,
+/// };
+/// let names = KeyNames::default()
+///     .with(KeyCode::Esc, "Escape")
+///     .with(KeyCode::PageUp, "PgUp");
+/// assert_eq!(names.name(KeyCode::PageUp), Some("PgUp"));
+/// assert_eq!(names.code("pgup"), Some(KeyCode::PageUp));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyNames {
+    names: Vec<(KeyCode, String)>,
+}
+
+impl KeyNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the name of a key, replacing its previous custom name
+    pub fn set<S: Into<String>>(&mut self, code: KeyCode, name: S) {
+        let name = name.into();
+        match self.names.iter_mut().find(|(c, _)| *c == code) {
+            Some(entry) => entry.1 = name,
+            None => self.names.push((code, name)),
+        }
+    }
+    pub fn with<S: Into<String>>(mut self, code: KeyCode, name: S) -> Self {
+        self.set(code, name);
+        self
+    }
+    /// Return the custom name of the key, if any
+    pub fn name(&self, code: KeyCode) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, name)| name.as_str())
+    }
+    /// Return the key having this custom name, ignoring the case
+    pub fn code(&self, name: &str) -> Option<KeyCode> {
+        self.names
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(name))
+            .map(|(code, _)| *code)
+    }
+    /// Iterate over the keys having a custom name, with their name
+    pub fn iter(&self) -> impl Iterator<Item = (KeyCode, &str)> {
+        self.names.iter().map(|(code, name)| (*code, name.as_str()))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
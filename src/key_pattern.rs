@@ -0,0 +1,179 @@
+use {
+    crate::{
+        match_mode::unshifted,
+        parse::modifier_prefix,
+        *,
+    },
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+    std::{
+        fmt,
+        str::FromStr,
+    },
+};
+
+#[cfg(feature = "serde")]
+use {
+    crate::key_combination::FromStrVisitor,
+    serde::{
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+};
+
+/// A binding matching key combinations by their modifiers, e.g. `*-enter`
+/// for "enter, whatever the modifiers", or `none-x` for "x, with no
+/// modifier at all".
+///
+/// Patterns are bound with [KeyBindingMap::insert_pattern], and in
+/// configuration files, next to the key combinations.
+///
+/// ```
+/// use crokey::*;
+/// let pattern: KeyPattern = "*-enter".parse().unwrap();
+/// assert!(pattern.matches(key!(enter)));
+/// assert!(pattern.matches(key!(ctrl-shift-enter)));
+/// assert!(!pattern.matches(key!(ctrl-j)));
+/// assert_eq!(pattern, "any-Enter".parse().unwrap());
+/// let pattern: KeyPattern = "none-x".parse().unwrap();
+/// assert!(pattern.matches(key!(x)));
+/// assert!(!pattern.matches(key!(shift-x)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPattern {
+    /// The keys, pressed with any modifiers (shift included), written
+    /// with a `*-` or `any-` prefix
+    AnyModifiers(OneToThree<KeyCode>),
+    /// The keys, pressed without any modifier, written with a `none-`
+    /// prefix.
+    ///
+    /// Unlike a plain binding, it's never matched by the tolerant
+    /// [match modes](MatchMode), e.g. [MatchMode::IgnoreShift].
+    NoModifiers(OneToThree<KeyCode>),
+}
+
+impl KeyPattern {
+    /// Tell whether the key combination is one of the pattern
+    pub fn matches(self, key: KeyCombination) -> bool {
+        match self {
+            Self::AnyModifiers(codes) => unshifted(key).codes == codes,
+            Self::NoModifiers(codes) => {
+                let key = key.canonical();
+                key.modifiers.is_empty() && key.codes == codes
+            }
+        }
+    }
+    /// Return the keys of the pattern
+    pub fn codes(self) -> OneToThree<KeyCode> {
+        match self {
+            Self::AnyModifiers(codes) | Self::NoModifiers(codes) => codes,
+        }
+    }
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::AnyModifiers(_) => "*-",
+            Self::NoModifiers(_) => "none-",
+        }
+    }
+    /// Write the pattern, its keys being written with the given format
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let pattern: KeyPattern = "any-enter".parse().unwrap();
+    /// assert_eq!(pattern.to_string_with(&KeyCombinationFormat::mac()), "*-↩");
+    /// ```
+    pub fn to_string_with(self, format: &KeyCombinationFormat) -> String {
+        let keys = KeyCombination::new(self.codes(), KeyModifiers::NONE);
+        format!("{}{}", self.prefix(), format.to_string(keys))
+    }
+}
+
+impl FromStr for KeyPattern {
+    type Err = ParseKeyError;
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        let lowercase = s.to_ascii_lowercase();
+        let (any, prefix_len) = [("*-", true), ("any-", true), ("none-", false)]
+            .iter()
+            .find(|(prefix, _)| lowercase.starts_with(prefix))
+            .map(|(prefix, any)| (*any, prefix.len()))
+            .ok_or_else(|| ParseKeyError::new(s))?;
+        let rest = &s[prefix_len..];
+        if modifier_prefix(&lowercase[prefix_len..], &ParseOptions::default()).is_some() {
+            return Err(ParseKeyError::new(s)); // modifiers would be meaningless
+        }
+        let key = parse(rest)?;
+        if any {
+            return Ok(Self::AnyModifiers(unshifted(key).codes));
+        }
+        let key = key.canonical();
+        if !key.modifiers.is_empty() {
+            return Err(ParseKeyError::new(s)); // e.g. `none-backtab`, which implies shift
+        }
+        Ok(Self::NoModifiers(key.codes))
+    }
+}
+
+impl TryFrom<&str> for KeyPattern {
+    type Error = ParseKeyError;
+    fn try_from(s: &str) -> Result<Self, ParseKeyError> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for KeyPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keys = KeyCombination::new(self.codes(), KeyModifiers::NONE);
+        write!(f, "{}{}", self.prefix(), keys)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyPattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FromStrVisitor::new("a key pattern"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeyPattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string_with(&KeyCombinationFormat::config_safe()))
+    }
+}
+
+#[test]
+fn check_key_pattern_parsing() {
+    let pattern = |raw: &str| raw.parse::<KeyPattern>();
+    assert_eq!(pattern("*-a").unwrap(), KeyPattern::AnyModifiers(OneToThree::One(KeyCode::Char('a'))));
+    assert_eq!(pattern("*-A").unwrap(), pattern("any-a").unwrap());
+    assert_eq!(pattern("ANY-Tab").unwrap(), pattern("*-backtab").unwrap());
+    assert_eq!(pattern("*-b-a").unwrap(), pattern("*-a-b").unwrap());
+    assert!(pattern("*-a-b").unwrap().matches(key!(alt-b-a)));
+    assert!(pattern("*--").unwrap().matches(key!(ctrl-'-')));
+    assert!(pattern("*-tab").unwrap().matches(key!(shift-tab)));
+    assert_eq!(pattern("NONE-x").unwrap(), KeyPattern::NoModifiers(OneToThree::One(KeyCode::Char('x'))));
+    assert_eq!(pattern("none-X").unwrap(), pattern("none-x").unwrap());
+    assert!(pattern("none-y-x").unwrap().matches(key!(x-y)));
+    assert!(!pattern("none-x").unwrap().matches(key!(ctrl-x)));
+    assert!(!pattern("none-x").unwrap().matches(KeyCombination::from(KeyCode::Char('X'))));
+    for wrong in [
+        "enter", "*-", "*-ctrl-enter", "any-nope", "any-shift-a", "none-", "none-alt-x",
+        "none-backtab",
+    ] {
+        assert!(pattern(wrong).is_err(), "{:?}", wrong);
+    }
+    for raw in ["*-enter", "*-f1", "*-a-b", "*-space", "*-?", "none-x", "none-tab"] {
+        let parsed = pattern(raw).unwrap();
+        assert_eq!(pattern(&parsed.to_string()).unwrap(), parsed);
+    }
+}
@@ -0,0 +1,233 @@
+use {
+    crate::*,
+    std::{
+        borrow::Borrow,
+        fmt,
+        ops::Deref,
+        str::FromStr,
+    },
+};
+
+#[cfg(feature = "serde")]
+use {
+    crate::key_combination::FromStrVisitor,
+    serde::{
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+};
+
+/// A sequence of key combinations, typed one after the other,
+/// for example `ctrl-x` then `ctrl-s`.
+///
+/// In strings, the combinations are separated with spaces:
+///
+/// ```
+/// use crokey::*;
+/// let sequence: KeySequence = "ctrl-x ctrl-s".parse().unwrap();
+/// assert_eq!(sequence, KeySequence::new(vec![key!(ctrl-x), key!(ctrl-s)]));
+/// assert_eq!(sequence.to_string(), "Ctrl-x Ctrl-s");
+/// ```
+///
+/// A combination typed several times may be given with a count, either
+/// as prefix (`3-ctrl-d`) or as suffix (`ctrl-d*3`). Runs of three or
+/// more identical combinations are written with the suffix:
+///
+/// ```
+/// use crokey::*;
+/// let down: KeySequence = "3-ctrl-d".parse().unwrap();
+/// assert_eq!(down, "ctrl-d ctrl-d ctrl-d".parse().unwrap());
+/// assert_eq!(down, "ctrl-d*3".parse().unwrap());
+/// assert_eq!(down, keyseq!(ctrl-d*3));
+/// assert_eq!(down.to_string(), "Ctrl-d*3");
+/// ```
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KeySequence {
+    pub keys: Vec<KeyCombination>,
+}
+
+impl KeySequence {
+    pub fn new(keys: Vec<KeyCombination>) -> Self {
+        Self { keys }
+    }
+    pub fn push(&mut self, key: KeyCombination) {
+        self.keys.push(key);
+    }
+    /// Return the sequence with all its combinations in canonical form
+    ///
+    /// See [KeyCombination::canonical].
+    pub fn canonical(mut self) -> Self {
+        for key in &mut self.keys {
+            *key = key.canonical();
+        }
+        self
+    }
+}
+
+impl Deref for KeySequence {
+    type Target = [KeyCombination];
+    fn deref(&self) -> &[KeyCombination] {
+        &self.keys
+    }
+}
+
+impl Borrow<[KeyCombination]> for KeySequence {
+    fn borrow(&self) -> &[KeyCombination] {
+        &self.keys
+    }
+}
+
+impl From<KeyCombination> for KeySequence {
+    fn from(key: KeyCombination) -> Self {
+        Self { keys: vec![key] }
+    }
+}
+
+impl From<Vec<KeyCombination>> for KeySequence {
+    fn from(keys: Vec<KeyCombination>) -> Self {
+        Self { keys }
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = ParseKeyError;
+    fn from_str(s: &str) -> Result<Self, ParseKeyError> {
+        let mut keys = Vec::new();
+        for token in s.split_whitespace() {
+            let (key, count) = parse_repeated(token)?;
+            keys.extend(std::iter::repeat(key).take(count));
+        }
+        if keys.is_empty() {
+            return Err(ParseKeyError::new(s));
+        }
+        Ok(Self { keys })
+    }
+}
+
+/// Parse a key combination, optionally repeated with a count given as
+/// prefix (`3-ctrl-d`) or suffix (`ctrl-d*3`), returning the count.
+///
+/// A string which parses as a combination isn't read as repeated, so
+/// that `1-2` stays the combination of `1` and `2`.
+pub(crate) fn parse_repeated(raw: &str) -> Result<(KeyCombination, usize), ParseKeyError> {
+    let error = match parse(raw) {
+        Ok(key) => return Ok((key, 1)),
+        Err(e) => e,
+    };
+    let split = raw
+        .rsplit_once('*')
+        .filter(|(key, count)| !key.is_empty() && is_count(count))
+        .map(|(key, count)| (count, key))
+        .or_else(|| {
+            raw.split_once('-')
+                .filter(|(count, key)| is_count(count) && !key.is_empty())
+        });
+    let (count, key) = split.ok_or(error)?;
+    match count.parse::<u8>() {
+        Ok(count) if count > 0 => Ok((parse(key)?, count as usize)),
+        _ => Err(ParseKeyError::new(raw)),
+    }
+}
+
+fn is_count(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Iterate over the runs of identical key combinations, with their lengths
+fn runs(keys: &[KeyCombination]) -> impl Iterator<Item = (KeyCombination, usize)> + '_ {
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        let key = *keys.get(i)?;
+        let len = keys[i..].iter().take_while(|k| **k == key).count();
+        i += len;
+        Some((key, len))
+    })
+}
+
+/// Write the key combinations separated with spaces, with a count
+/// for the runs of three or more identical combinations
+pub(crate) fn write_keys<F>(f: &mut fmt::Formatter, keys: &[KeyCombination], write_key: F) -> fmt::Result
+where
+    F: Fn(&mut fmt::Formatter, KeyCombination) -> fmt::Result,
+{
+    let mut first = true;
+    for (key, len) in runs(keys) {
+        let written = if len < 3 { len } else { 1 };
+        for _ in 0..written {
+            if !first {
+                write!(f, " ")?;
+            }
+            first = false;
+            write_key(f, key)?;
+        }
+        if len >= 3 {
+            write!(f, "*{}", len)?;
+        }
+    }
+    Ok(())
+}
+
+impl TryFrom<&str> for KeySequence {
+    type Error = ParseKeyError;
+    fn try_from(s: &str) -> Result<Self, ParseKeyError> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for KeySequence {
+    type Error = ParseKeyError;
+    fn try_from(s: String) -> Result<Self, ParseKeyError> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_keys(f, &self.keys, |f, key| write!(f, "{}", key))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeySequence {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FromStrVisitor::new("a key sequence"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeySequence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let format = KeyCombinationFormat::config_safe();
+        let keys: Vec<String> = self.keys.iter().map(|key| format.to_string(*key)).collect();
+        serializer.serialize_str(&keys.join(" "))
+    }
+}
+
+#[test]
+fn check_repeated_keys() {
+    let down = KeySequence::new(vec![key!(ctrl-d); 3]);
+    for raw in ["3-ctrl-d", "ctrl-d*3", "ctrl-d ctrl-d ctrl-d", "2-ctrl-d ctrl-d"] {
+        assert_eq!(raw.parse::<KeySequence>().unwrap(), down, "{raw:?}");
+    }
+    assert_eq!(keyseq!(3-ctrl-d), down);
+    assert_eq!(keyseq!(ctrl-d*3), down);
+    assert_eq!(keyseq!(g*2 '*'*3), "g g * * *".parse().unwrap());
+    assert_eq!(down.to_string(), "Ctrl-d*3");
+    assert_eq!(keyseq!(g g x*4 y).to_string(), "g g x*4 y");
+    // a combination isn't read as a count
+    assert_eq!("1-2".parse::<KeySequence>().unwrap(), keyseq!(1-2));
+    assert_eq!(keyseq!(1-2).len(), 1);
+    for raw in ["0-ctrl-d", "ctrl-d*0", "ctrl-d*256", "3-", "*3", "3-ctrl-d*3"] {
+        assert!(raw.parse::<KeySequence>().is_err(), "{raw:?}");
+    }
+    let alternatives: KeyAlternatives = "ctrl-d*3 | 0x7c*3".parse().unwrap();
+    assert_eq!(alternatives.to_string(), "Ctrl-d*3 | 0x7c*3");
+}
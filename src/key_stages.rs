@@ -0,0 +1,303 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+    std::{
+        collections::VecDeque,
+        fmt,
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+const ESC: KeyCombination = KeyCombination {
+    codes: OneToThree::One(KeyCode::Esc),
+    modifiers: KeyModifiers::NONE,
+};
+
+/// A stage folding an `esc` quickly followed by another key into this key
+/// with alt, as terminals configured to send alt as an escape prefix
+/// produce `esc` then `x` for `alt-x`.
+///
+/// The `esc` is held until the next key or until the timeout: when
+/// nothing follows, it's given by [Pipeline::poll].
+///
+/// ```
+/// use {
+///     crokey::{*, testing::MockClock},
+///     std::time::Duration,
+/// };
+/// let clock = MockClock::new();
+/// let mut pipeline = Pipeline::default()
+///     .with_stage(EscPrefixFolder::new(Duration::from_millis(20)).with_clock(clock.clone()));
+/// assert_eq!(pipeline.process(key!(esc)), None);
+/// assert_eq!(pipeline.process(key!(x)), Some(key!(alt-x)));
+/// assert_eq!(pipeline.process(key!(esc)), None);
+/// clock.advance(Duration::from_millis(20));
+/// assert_eq!(pipeline.poll(), Some(key!(esc)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EscPrefixFolder {
+    timeout: Duration,
+    /// when the held esc was received
+    esc_at: Option<Instant>,
+    /// a key received after a stale esc, given on the next flush
+    held: Option<(KeyCombination, Instant)>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for EscPrefixFolder {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(20))
+    }
+}
+
+impl EscPrefixFolder {
+    /// Create a folder taking as prefix an `esc` followed by a key less
+    /// than `timeout` later
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            esc_at: None,
+            held: None,
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock in [KeyStage::process]
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl KeyStage for EscPrefixFolder {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        let now = self.clock.now();
+        let esc_at = match self.esc_at.take() {
+            Some(esc_at) => esc_at,
+            None if key == ESC => {
+                self.esc_at = Some(now);
+                return None;
+            }
+            None => {
+                return Some(key);
+            }
+        };
+        let in_time = now.saturating_duration_since(esc_at) < self.timeout;
+        if in_time && key != ESC && !key.modifiers.contains(KeyModifiers::ALT) {
+            return Some(KeyCombination::new(key.codes, key.modifiers | KeyModifiers::ALT));
+        }
+        // the held esc is a real one, the new key comes after it
+        if key == ESC {
+            self.esc_at = Some(now);
+        } else {
+            self.held = Some((key, now));
+        }
+        Some(ESC)
+    }
+    fn deadline(&self) -> Option<Instant> {
+        match (self.held, self.esc_at) {
+            (Some((_, at)), _) => Some(at),
+            (None, Some(esc_at)) => Some(esc_at + self.timeout),
+            (None, None) => None,
+        }
+    }
+    fn flush(&mut self) -> Option<KeyCombination> {
+        if self.deadline()? > self.clock.now() {
+            return None;
+        }
+        if let Some((key, _)) = self.held.take() {
+            return Some(key);
+        }
+        self.esc_at.take().map(|_| ESC)
+    }
+}
+
+/// A stage dropping a key combination identical to the previous one and
+/// received less than an interval after it, e.g. to ignore the bounces
+/// of a worn keyboard or a double event sent by some terminals.
+///
+/// ```
+/// use {
+///     crokey::{*, testing::MockClock},
+///     std::time::Duration,
+/// };
+/// let clock = MockClock::new();
+/// let mut debouncer = Debouncer::new(Duration::from_millis(10)).with_clock(clock.clone());
+/// assert_eq!(debouncer.process(key!(a)), Some(key!(a)));
+/// assert_eq!(debouncer.process(key!(a)), None);
+/// assert_eq!(debouncer.process(key!(b)), Some(key!(b)));
+/// clock.advance(Duration::from_millis(10));
+/// assert_eq!(debouncer.process(key!(b)), Some(key!(b)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    interval: Duration,
+    last: Option<(KeyCombination, Instant)>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Debouncer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: None,
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock in [KeyStage::process]
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl KeyStage for Debouncer {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        let now = self.clock.now();
+        if let Some((last, at)) = self.last {
+            if last == key && now.saturating_duration_since(at) < self.interval {
+                return None;
+            }
+        }
+        self.last = Some((key, now));
+        Some(key)
+    }
+}
+
+/// A stage recognizing the sequences bound in a [SequenceMatcher]: the
+/// actions of the matched bindings are given to a callback, and the
+/// keys which aren't part of a bound sequence go to the next stage.
+///
+/// The keys of an incomplete sequence are held until the timeout, then
+/// handled by [Pipeline::poll] like with [SequenceMatcher::flush].
+///
+/// ```
+/// use {
+///     crokey::{*, testing::MockClock},
+///     std::{sync::mpsc, time::Duration},
+/// };
+/// let mut bindings = KeyBindingMap::new();
+/// bindings.insert("ctrl-k ctrl-c", "comment").unwrap();
+/// let (sender, actions) = mpsc::channel();
+/// let clock = MockClock::new();
+/// let stage = SequenceStage::new(
+///     SequenceMatcher::new(bindings),
+///     Duration::from_millis(500),
+///     move |matched| { sender.send(matched).unwrap(); },
+/// ).with_clock(clock.clone());
+/// let mut pipeline = Pipeline::default().with_stage(stage);
+/// assert_eq!(pipeline.process(key!(ctrl-k)), None);
+/// assert_eq!(pipeline.process(key!(ctrl-c)), None);
+/// assert_eq!(actions.try_recv(), Ok(SequenceMatch::Matched("comment")));
+/// assert_eq!(pipeline.process(key!(x)), Some(key!(x)));
+/// assert_eq!(pipeline.process(key!(ctrl-k)), None);
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(pipeline.poll(), Some(key!(ctrl-k)));
+/// ```
+pub struct SequenceStage<A, F> {
+    matcher: SequenceMatcher<A>,
+    timeout: Duration,
+    on_match: F,
+    /// the keys to give to the next stage
+    unmatched: VecDeque<KeyCombination>,
+    last_key_at: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<A: fmt::Debug, F> fmt::Debug for SequenceStage<A, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SequenceStage")
+            .field("matcher", &self.matcher)
+            .field("timeout", &self.timeout)
+            .field("unmatched", &self.unmatched)
+            .field("last_key_at", &self.last_key_at)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+impl<A, F> SequenceStage<A, F>
+where
+    A: Clone,
+    F: FnMut(SequenceMatch<A>),
+{
+    /// Create a stage giving the [Matched](SequenceMatch::Matched) and
+    /// [MatchedWithCount](SequenceMatch::MatchedWithCount) results of the
+    /// matcher to `on_match`, and flushing incomplete sequences when no
+    /// key is received during `timeout`
+    pub fn new(matcher: SequenceMatcher<A>, timeout: Duration, on_match: F) -> Self {
+        Self {
+            matcher,
+            timeout,
+            on_match,
+            unmatched: VecDeque::new(),
+            last_key_at: None,
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock in [KeyStage::process]
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub fn matcher(&self) -> &SequenceMatcher<A> {
+        &self.matcher
+    }
+    pub fn matcher_mut(&mut self) -> &mut SequenceMatcher<A> {
+        &mut self.matcher
+    }
+    fn handle(&mut self, matched: SequenceMatch<A>) {
+        match matched {
+            SequenceMatch::Pending => {}
+            SequenceMatch::Unmatched(keys) => self.unmatched.extend(keys.iter()),
+            matched => (self.on_match)(matched),
+        }
+    }
+}
+
+impl<A, F> KeyStage for SequenceStage<A, F>
+where
+    A: Clone,
+    F: FnMut(SequenceMatch<A>),
+{
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        self.last_key_at = Some(self.clock.now());
+        for matched in self.matcher.feed(key) {
+            self.handle(matched);
+        }
+        self.unmatched.pop_front()
+    }
+    fn deadline(&self) -> Option<Instant> {
+        let last_key_at = self.last_key_at?;
+        if !self.unmatched.is_empty() {
+            Some(last_key_at)
+        } else if self.matcher.is_pending() {
+            Some(last_key_at + self.timeout)
+        } else {
+            None
+        }
+    }
+    fn flush(&mut self) -> Option<KeyCombination> {
+        if self.deadline()? > self.clock.now() {
+            return None;
+        }
+        if self.unmatched.is_empty() {
+            if let Some(matched) = self.matcher.flush() {
+                self.handle(matched);
+            }
+        }
+        self.unmatched.pop_front()
+    }
+}
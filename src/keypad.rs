@@ -0,0 +1,108 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventState,
+    },
+};
+
+/// The numpad keys a combiner can tell apart from the main keyboard ones,
+/// when the terminal reports them (kitty keyboard protocol): the char
+/// representing the numpad key, its name, and the key of the main keyboard
+/// it falls back to.
+///
+/// The chars are the private use code points kitty assigns to those keys.
+pub static KEYPAD_KEYS: &[(char, &str, KeyCode)] = &[
+    ('\u{e037}', "kp0", KeyCode::Char('0')),
+    ('\u{e038}', "kp1", KeyCode::Char('1')),
+    ('\u{e039}', "kp2", KeyCode::Char('2')),
+    ('\u{e03a}', "kp3", KeyCode::Char('3')),
+    ('\u{e03b}', "kp4", KeyCode::Char('4')),
+    ('\u{e03c}', "kp5", KeyCode::Char('5')),
+    ('\u{e03d}', "kp6", KeyCode::Char('6')),
+    ('\u{e03e}', "kp7", KeyCode::Char('7')),
+    ('\u{e03f}', "kp8", KeyCode::Char('8')),
+    ('\u{e040}', "kp9", KeyCode::Char('9')),
+    ('\u{e041}', "kpdecimal", KeyCode::Char('.')),
+    ('\u{e042}', "kpdivide", KeyCode::Char('/')),
+    ('\u{e043}', "kpmultiply", KeyCode::Char('*')),
+    ('\u{e044}', "kpminus", KeyCode::Char('-')),
+    ('\u{e045}', "kpplus", KeyCode::Char('+')),
+    ('\u{e046}', "kpenter", KeyCode::Enter),
+    ('\u{e047}', "kpequal", KeyCode::Char('=')),
+];
+
+/// Return the displayed name of the numpad key, e.g. `Kp1`
+pub(crate) fn keypad_display_name_of(code: KeyCode) -> Option<String> {
+    let (_, name, _) = KEYPAD_KEYS.iter().find(|(c, _, _)| code == KeyCode::Char(*c))?;
+    let mut chars = name[2..].chars();
+    let first = chars.next()?.to_ascii_uppercase();
+    Some(format!("Kp{}{}", first, chars.as_str()))
+}
+
+/// Change the code of a key event reported as coming from the numpad
+/// to the char of the numpad key
+pub(crate) fn distinguish_keypad(mut key: KeyEvent) -> KeyEvent {
+    if key.state.contains(KeyEventState::KEYPAD) {
+        if let Some((c, _, _)) = KEYPAD_KEYS.iter().find(|(_, _, code)| *code == key.code) {
+            key.code = KeyCode::Char(*c);
+        }
+    }
+    key
+}
+
+/// Return the main keyboard key the code falls back to if it's a numpad one
+fn keypad_fallback(code: KeyCode) -> KeyCode {
+    match code {
+        KeyCode::Char(c) => KEYPAD_KEYS
+            .iter()
+            .find(|(k, _, _)| *k == c)
+            .map_or(code, |(_, _, fallback)| *fallback),
+        _ => code,
+    }
+}
+
+impl KeyCombination {
+    /// Tell whether one of the codes is a numpad key
+    pub fn is_keypad(self) -> bool {
+        self.codes
+            .iter()
+            .any(|code| keypad_fallback(*code) != *code)
+    }
+    /// Return the combination with the numpad keys replaced by the keys
+    /// of the main keyboard, which is what terminals send when they
+    /// don't report the numpad.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(key!(ctrl-kp1).without_keypad(), key!(ctrl-'1'));
+    /// assert_eq!(key!(kpenter).without_keypad(), key!(enter));
+    /// assert_eq!(key!(kpplus).to_string(), "KpPlus");
+    /// ```
+    pub fn without_keypad(self) -> Self {
+        let codes = self.codes.map(keypad_fallback);
+        Self::new(codes, self.modifiers).normalized()
+    }
+}
+
+#[test]
+fn check_keypad_keys() {
+    use crossterm::event::{KeyEventKind, KeyModifiers};
+    for (c, name, _) in KEYPAD_KEYS {
+        let key = parse(name).unwrap();
+        assert_eq!(key, KeyCombination::from(KeyCode::Char(*c)));
+        assert!(key.is_keypad());
+        assert_eq!(parse(&key.to_string()).unwrap(), key);
+    }
+    let event = KeyEvent::new_with_kind_and_state(
+        KeyCode::Char('7'),
+        KeyModifiers::NONE,
+        KeyEventKind::Press,
+        KeyEventState::KEYPAD,
+    );
+    assert_eq!(distinguish_keypad(event).code, KeyCode::Char('\u{e03e}'));
+    let event = KeyEvent::new(KeyCode::Char('7'), KeyModifiers::NONE);
+    assert_eq!(distinguish_keypad(event).code, KeyCode::Char('7'));
+    assert!(!key!(ctrl-'7').is_keypad());
+}
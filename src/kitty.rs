@@ -0,0 +1,209 @@
+//! Conversions from and to the key syntax of kitty's configuration
+//! (`map ctrl+shift+f5 ...`), including `kitty_mod` and multi-key
+//! sequences like `ctrl+x>ctrl+y`.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+};
+
+/// The default value of `kitty_mod` in kitty.conf
+pub const DEFAULT_KITTY_MOD: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::SHIFT);
+
+/// Parse a key, or a `>` separated sequence of keys, as written in
+/// kitty.conf, with `kitty_mod` being `ctrl+shift`.
+///
+/// A `>` starting a key, as in `ctrl+>`, is the key and not a separator.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(
+///     parse_kitty("kitty_mod+f5").unwrap(),
+///     KeySequence::from(key!(ctrl-shift-f5)),
+/// );
+/// assert_eq!(
+///     parse_kitty("ctrl+x>ctrl+y").unwrap(),
+///     KeySequence::new(vec![key!(ctrl-x), key!(ctrl-y)]),
+/// );
+/// ```
+pub fn parse_kitty(raw: &str) -> Result<KeySequence, ParseKeyError> {
+    parse_kitty_with_mod(raw, DEFAULT_KITTY_MOD)
+}
+
+/// Parse a key, or a `>` separated sequence of keys, as written in
+/// kitty.conf, with the given value of `kitty_mod`.
+pub fn parse_kitty_with_mod(
+    raw: &str,
+    kitty_mod: KeyModifiers,
+) -> Result<KeySequence, ParseKeyError> {
+    let keys = split_kitty_sequence(raw.trim())
+        .into_iter()
+        .map(|key| parse_kitty_combination(key, kitty_mod))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(KeySequence::new(keys))
+}
+
+/// Split a sequence on its `>` separators, a `>` starting a key
+/// (e.g. in `ctrl+>`) being the key itself
+fn split_kitty_sequence(raw: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut start = 0;
+    for (i, c) in raw.char_indices() {
+        let key = &raw[start..i];
+        if c == '>' && !key.is_empty() && !key.ends_with('+') {
+            keys.push(key);
+            start = i + 1;
+        }
+    }
+    keys.push(&raw[start..]);
+    keys
+}
+
+fn parse_kitty_combination(
+    raw: &str,
+    kitty_mod: KeyModifiers,
+) -> Result<KeyCombination, ParseKeyError> {
+    let lowercase = raw.trim().to_ascii_lowercase();
+    let mut modifiers = KeyModifiers::empty();
+    let mut parts: Vec<&str> = lowercase.split('+').collect();
+    let key = parts.pop().unwrap_or_default(); // split always yields one part
+    for part in parts {
+        let modifier = match part {
+            "kitty_mod" => kitty_mod,
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "shift" => KeyModifiers::SHIFT,
+            "alt" | "opt" | "option" => KeyModifiers::ALT,
+            "super" | "cmd" | "command" => KeyModifiers::SUPER,
+            _ => {
+                return Err(ParseKeyError::new(raw));
+            }
+        };
+        modifiers.insert(modifier);
+    }
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+    let code = match key {
+        "escape" => Esc,
+        "return" => Enter,
+        "page_up" => PageUp,
+        "page_down" => PageDown,
+        "plus" => Char('+'),
+        "greater" => Char('>'),
+        "" => {
+            return Err(ParseKeyError::new(raw));
+        }
+        _ => parse_key_code(key, shift).map_err(|_| ParseKeyError::new(raw))?,
+    };
+    if code == BackTab {
+        modifiers.insert(KeyModifiers::SHIFT);
+    }
+    Ok(KeyCombination::new(code, modifiers))
+}
+
+fn kitty_key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        Esc => "escape".to_string(),
+        Enter | Char('\r') | Char('\n') => "enter".to_string(),
+        Tab | BackTab => "tab".to_string(),
+        Backspace => "backspace".to_string(),
+        Delete => "delete".to_string(),
+        Insert => "insert".to_string(),
+        Home => "home".to_string(),
+        End => "end".to_string(),
+        PageUp => "page_up".to_string(),
+        PageDown => "page_down".to_string(),
+        Up => "up".to_string(),
+        Down => "down".to_string(),
+        Left => "left".to_string(),
+        Right => "right".to_string(),
+        F(n) => format!("f{n}"),
+        Char(' ') => "space".to_string(),
+        Char('+') => "plus".to_string(),
+        Char('>') => "greater".to_string(),
+        Char(c) => c.to_lowercase().collect(),
+        _ => {
+            return None;
+        }
+    };
+    Some(name)
+}
+
+fn to_kitty_combination(key: KeyCombination) -> Option<String> {
+    let OneToThree::One(code) = key.codes else {
+        return None; // kitty.conf only maps one non-modifier key at a time
+    };
+    let mut s = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        s.push_str("ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) || code == BackTab {
+        s.push_str("shift+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        s.push_str("alt+");
+    }
+    if key.modifiers.contains(KeyModifiers::SUPER) {
+        s.push_str("super+");
+    }
+    s.push_str(&kitty_key_name(code)?);
+    Some(s)
+}
+
+/// Build the kitty.conf description of a key combination or of a
+/// sequence of key combinations.
+///
+/// `kitty_mod` is never used in the output, modifiers are always explicit.
+/// Return `None` when one of the combinations can't be expressed in kitty.conf.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(to_kitty_string(&[key!(ctrl-shift-f5)]).unwrap(), "ctrl+shift+f5");
+/// assert_eq!(
+///     to_kitty_string(&[key!(ctrl-x), key!(ctrl-y)]).unwrap(),
+///     "ctrl+x>ctrl+y",
+/// );
+/// ```
+pub fn to_kitty_string(keys: &[KeyCombination]) -> Option<String> {
+    let keys = keys
+        .iter()
+        .map(|&key| to_kitty_combination(key))
+        .collect::<Option<Vec<_>>>()?;
+    if keys.is_empty() {
+        return None;
+    }
+    Some(keys.join(">"))
+}
+
+#[test]
+fn check_kitty_round_trip() {
+    for raw in [
+        "ctrl+shift+f5",
+        "ctrl+x>ctrl+y",
+        "alt+page_up",
+        "super+escape",
+        "ctrl+plus",
+        "shift+tab",
+        "ctrl+greater>greater",
+    ] {
+        let keys = parse_kitty(raw).unwrap();
+        assert_eq!(to_kitty_string(&keys).unwrap(), raw);
+    }
+    assert_eq!(
+        parse_kitty_with_mod("kitty_mod+t", KeyModifiers::ALT).unwrap(),
+        KeySequence::from(key!(alt-t)),
+    );
+    for (raw, key) in [("ctrl+>", key!(ctrl-'>')), (">", key!('>')), ("ctrl+greater", key!(ctrl-'>'))] {
+        assert_eq!(parse_kitty(raw).unwrap(), KeySequence::from(key));
+    }
+    assert_eq!(
+        parse_kitty("ctrl+>>>").unwrap(),
+        KeySequence::new(vec![key!(ctrl-'>'), key!('>')]),
+    );
+    let keys = KeySequence::new(vec![key!(ctrl-'>'), key!('>'), key!(x)]);
+    assert_eq!(parse_kitty(&to_kitty_string(&keys).unwrap()).unwrap(), keys);
+    assert_eq!(parse_kitty("ctrl+return").unwrap(), KeySequence::from(key!(ctrl-enter)));
+    assert!(parse_kitty("hyper+a").is_err());
+    assert!(parse_kitty("ctrl+").is_err());
+}
@@ -7,7 +7,9 @@
 //!
 //! ## The KeyCombination
 //!
-//! A `KeyCombination` is made of 1 to 3 "normal" keys with some optional modifiers (alt, shift, ctrl).
+//! A `KeyCombination` is made of 1 to 3 "normal" keys with some optional modifiers (alt, shift, ctrl, super).
+//! The `control`, `option`, `command` and `windows` synonyms of the modifiers are accepted
+//! by the parser and the macros (see [MODIFIER_SYNONYMS]).
 //!
 //! It can be parsed, ergonomically built with the `key!` macro, obtained from key events.
 //!
@@ -34,6 +36,7 @@
 //! #         terminal,
 //! #     },
 //! # };
+//! # #[cfg(feature = "terminal")] {
 //! let fmt = KeyCombinationFormat::default();
 //! let mut combiner = Combiner::default();
 //! let combines = combiner.enable_combining().unwrap();
@@ -64,6 +67,7 @@
 //!         _ => {}
 //!     }
 //! }
+//! # }
 //! ```
 //!
 //! ## Parse a string
@@ -112,6 +116,20 @@
 //! ```
 //! Complete example in `/examples/print_key`
 //!
+//! The macro also accepts the aliases defined in the `[aliases]` section of a
+//! `crokey.toml` file at the root of your crate (or in the file given by the
+//! `CROKEY_CONFIG` env var), for example `leader = "f12"`. The crate is rebuilt
+//! when this file changes, but a key using an alias can't be a pattern: compare
+//! it in a guard, e.g. `k if k == key!(ctrl-leader) => ...`.
+//! Give the same file to [ParseOptions::with_aliases_config] to have the runtime
+//! parser agree with the macro.
+//!
+//! Key sequences are written with the [keyseq!] macro, e.g. `keyseq!(ctrl-x ctrl-s)`,
+//! which checks each key combination the same way.
+//!
+//! Without macro, [parse_ascii_ok] parses a combination in a const context, e.g.
+//! `static QUIT: KeyCombination = parse_ascii_ok("ctrl-q");`.
+//!
 //! ## Display a string with a configurable format
 //!
 //! ```
@@ -140,11 +158,10 @@
 //!     crokey::*,
 //!     crossterm::event::KeyEvent,
 //!     serde::Deserialize,
-//!     std::collections::HashMap,
 //! };
 //! #[derive(Debug, Deserialize)]
 //! struct Config {
-//!     keybindings: HashMap<KeyCombination, String>,
+//!     keybindings: NormalizedKeyMap<String>,
 //! }
 //! static CONFIG_HJSON: &str = r#"
 //! {
@@ -159,37 +176,212 @@
 //! let config: Config = deser_hjson::from_str(CONFIG_HJSON).unwrap();
 //! let key: KeyCombination = key!(shift-b);
 //! assert_eq!(
-//!     config.keybindings.get(&key).unwrap(),
+//!     config.keybindings.get(key).unwrap(),
 //!     "babirussa",
 //! );
 //! ```
 //!
 //! Instead of Hjson, you can use any Serde compatible format such as JSON or TOML.
 //!
+//! Prefer a `NormalizedKeyMap` (or a `KeyBindingMap`) to a `HashMap<KeyCombination, _>`:
+//! a `HashMap` compares the raw combinations, so a lookup may fail when the
+//! configuration and the key events don't write a key the same way (e.g. `A` and `shift-a`).
+//!
+//! With the "serde-binary" feature enabled, the `serde_binary` module gives helpers
+//! encoding keys numerically in binary formats, with an encoding owned by crokey.
+//!
+//! With the "schemars" feature enabled, the key types and the binding maps implement
+//! `JsonSchema`, so that the schema of your configuration validates the keys.
+//!
+//! The "global-format" feature, enabled by default, provides the `STANDARD_FORMAT` static
+//! used to display key combinations. Disable it for environments where globals are
+//! forbidden: a default format is then built on each display (prefer passing your own `KeyCombinationFormat` in this case).
+//!
+//! The "terminal" feature, enabled by default, provides the types interacting with the
+//! terminal: `Combiner`, `Pipeline`, the keyboard enhancement flags and capabilities
+//! helpers, and the tty key settings. Without it, the parsing, formatting, bindings and
+//! serde parts still work (e.g. to validate configurations in a server) and the `libc`
+//! dependency is dropped (and `once_cell` too if "global-format" is disabled). Note that
+//! crossterm is still needed for its key types.
+//!
+//! With the "pty-test" feature enabled (unix only), `PtySession` runs a program in a
+//! pseudo terminal and sends it keys encoded with `encode_key`, as a legacy or kitty
+//! terminal would, for end-to-end tests of key handling in CI.
+//!
+//! With the "import" feature enabled, `import_broot_verbs` and `import_helix_keys` read
+//! the key bindings of the TOML configurations of broot and Helix, for migration tooling.
+//!
+//! With the "unicode-width" feature enabled, formatted keys can be measured and truncated
+//! in terminal cells, which helps laying out key hints using symbols like `⌘` or `⏎`.
+//!
+//! ## Diagnose with tracing
+//!
+//! With the "tracing" feature enabled, the combiner, the pipeline and the sequence matcher
+//! emit debug events telling what they receive and what they decide, which helps understand
+//! why a binding doesn't fire on a specific terminal.
+//!
 
+/// Emit a debug event when the `tracing` feature is enabled
+macro_rules! debug_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+#[path = "proc_macros/aliases_config.rs"]
+mod aliases_config;
+mod ascii_parse;
+mod binding_alias;
+mod binding_diff;
+mod binding_map;
+mod binding_profiles;
+#[cfg(feature = "terminal")]
+mod capabilities;
+#[cfg(feature = "terminal")]
 mod combiner;
+mod clock;
+mod combiner_core;
+#[cfg(feature = "terminal")]
+mod combining_support;
+mod compat;
+pub mod consts;
+mod enhancement_flags;
+mod enhancement_query;
+#[cfg(feature = "terminal")]
+mod enhancement_guard;
+mod equivalents;
+mod event_iter;
 mod format;
+#[cfg(feature = "serde")]
+mod format_serde;
+mod help;
+mod helix;
+#[cfg(feature = "import")]
+mod import;
+mod key_encoding;
 mod key_event;
+mod key_examples;
+mod key_pattern;
+#[cfg(feature = "terminal")]
+mod key_stages;
+mod key_log;
+mod key_names;
 mod parse;
+mod key_alternatives;
 mod key_combination;
+mod key_combination_builder;
+mod key_combination_event;
+mod key_sequence;
+mod keypad;
+mod kitty;
+mod long_press;
+mod match_mode;
+mod modifier_remap;
+mod mods;
+mod normalized_key_map;
+#[cfg(feature = "terminal")]
+mod pipeline;
+mod platform_modifiers;
+#[cfg(all(unix, feature = "pty-test"))]
+mod pty_test;
+mod remapper;
+mod repeat_detector;
+#[cfg(feature = "schemars")]
+mod schema;
+mod scoped_bindings;
+mod reserved_keys;
+mod sequence_matcher;
+#[cfg(feature = "serde-binary")]
+pub mod serde_binary;
+mod shift_policy;
+mod shifted_symbols;
+pub mod testing;
+mod tmux;
+mod tokenize;
+#[cfg(feature = "terminal")]
+mod tty_keys;
+mod validate;
+mod vscode;
 
 pub use {
-    combiner::*,
+    ascii_parse::*,
+    binding_alias::*,
+    binding_diff::*,
+    binding_map::*,
+    binding_profiles::*,
+    clock::*,
+    combiner_core::*,
+    compat::*,
     crossterm,
+    enhancement_flags::*,
+    enhancement_query::*,
+    equivalents::*,
+    event_iter::*,
     format::*,
+    help::*,
+    helix::*,
+    key_encoding::*,
     key_event::*,
+    key_log::*,
+    key_names::*,
     parse::*,
+    key_alternatives::*,
     key_combination::*,
+    key_combination_builder::*,
+    key_combination_event::*,
+    key_pattern::*,
+    key_sequence::*,
+    keypad::*,
+    kitty::*,
+    long_press::*,
+    match_mode::*,
+    modifier_remap::*,
+    mods::*,
+    normalized_key_map::*,
+    platform_modifiers::*,
+    remapper::*,
+    repeat_detector::*,
+    reserved_keys::*,
+    scoped_bindings::*,
+    sequence_matcher::*,
+    shift_policy::*,
+    shifted_symbols::*,
+    tmux::*,
+    tokenize::*,
+    validate::*,
+    vscode::*,
     strict::OneToThree,
 };
 
-use {
-    crossterm::event::{KeyCode, KeyModifiers},
-    once_cell::sync::Lazy,
+#[cfg(feature = "import")]
+pub use import::*;
+
+#[cfg(all(unix, feature = "pty-test"))]
+pub use pty_test::*;
+
+#[cfg(feature = "terminal")]
+pub use {
+    capabilities::*,
+    combiner::*,
+    combining_support::*,
+    enhancement_guard::*,
+    key_stages::*,
+    pipeline::*,
+    tty_keys::*,
 };
 
+use crossterm::event::{KeyCode, KeyModifiers};
+
+#[cfg(feature = "global-format")]
+use once_cell::sync::Lazy;
+
 /// A lazy initialized KeyCombinationFormat which can be considered as standard
 /// and which is used in the Display implementation of the [KeyCombination] type.
+///
+/// Without the "global-format" feature, there's no such static and a default
+/// format is built for each display.
+#[cfg(feature = "global-format")]
 pub static STANDARD_FORMAT: Lazy<KeyCombinationFormat> = Lazy::new(KeyCombinationFormat::default);
 
 
@@ -217,6 +409,29 @@ pub static STANDARD_FORMAT: Lazy<KeyCombinationFormat> = Lazy::new(KeyCombinatio
 /// let ke = key!(shift-'?');
 /// let ke = key!(alt-']');
 /// ```
+///
+/// Up to three key codes can be combined, they're sorted the way
+/// [KeyCombination::new] sorts them, so that the result can be matched
+/// against the combinations given by a combiner:
+/// ```
+/// # use crokey::*;
+/// # use crossterm::event::{KeyCode, KeyModifiers};
+/// assert_eq!(
+///     key!(ctrl-b-a),
+///     KeyCombination::new((KeyCode::Char('a'), KeyCode::Char('b')), KeyModifiers::CONTROL),
+/// );
+/// assert!(matches!(KeyCombination::from(KeyCode::F(4)), key!(f4)));
+/// ```
+///
+/// The macro reads keys like [parse], with the default [ParseOptions] and
+/// the aliases of the project's `crokey.toml`. It doesn't support the other
+/// options: keys depending on them must be parsed at runtime with [parse_with].
+/// ```
+/// # use crokey::*;
+/// let options = ParseOptions::default().with_shift_tab_as_backtab(true);
+/// assert_eq!(parse_with("shift-tab", &options).unwrap(), key!(backtab));
+/// assert_ne!(key!(shift-tab), key!(backtab));
+/// ```
 #[macro_export]
 macro_rules! key {
     ($($tt:tt)*) => {
@@ -224,12 +439,101 @@ macro_rules! key {
     };
 }
 
-// Not public API. This is internal and to be used only by `key!`.
+/// check and expand at compile-time the provided key combinations, separated
+/// by spaces, into a [KeySequence].
+///
+/// ```
+/// use crokey::*;
+/// let save = keyseq!(ctrl-x ctrl-s);
+/// assert_eq!(save, "ctrl-x ctrl-s".parse().unwrap());
+/// assert_eq!(keyseq!(g g).len(), 2);
+///
+/// let mut map = KeyBindingMap::new();
+/// map.insert(keyseq!(space f '/'), "search").unwrap();
+/// assert_eq!(map.get_sequence(&keyseq!(space f '/')), Some(&"search"));
+/// ```
+///
+/// The combinations are written as in [key!], so that keys which can't be
+/// identifiers must be put between simple quotes. Like in [KeySequence]
+/// strings, a combination may be repeated with a count: `keyseq!(3-ctrl-d)`
+/// and `keyseq!(ctrl-d*3)` are `keyseq!(ctrl-d ctrl-d ctrl-d)`.
+#[macro_export]
+macro_rules! keyseq {
+    ($($tt:tt)*) => {
+        $crate::__private::keyseq!(($crate) $($tt)*)
+    };
+}
+
+/// check and expand at compile-time the provided key into a crossterm
+/// `KeyEvent`, for the code which needs raw key events (e.g. tests feeding
+/// a combiner) rather than key combinations.
+///
+/// The syntax is the one of [key!], with a single key code, optionally
+/// preceded by the kind of the event: `press` (the default), `release`
+/// or `repeat`. The state of the event is empty.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+/// };
+/// assert_eq!(key_event!(ctrl-a), KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+/// assert_eq!(KeyCombination::from(key_event!(shift-tab)), key!(shift-tab));
+/// assert_eq!(
+///     key_event!(release alt-f4),
+///     KeyEvent::new_with_kind_and_state(
+///         KeyCode::F(4),
+///         KeyModifiers::ALT,
+///         KeyEventKind::Release,
+///         KeyEventState::NONE,
+///     ),
+/// );
+///
+/// // feeding a combiner, as a terminal with the kitty protocol would do
+/// let mut combiner = CombinerCore::default();
+/// combiner.set_combining(true);
+/// assert_eq!(combiner.transform(key_event!(press ctrl-a)), None);
+/// assert_eq!(combiner.transform(key_event!(press ctrl-b)), None);
+/// assert_eq!(combiner.transform(key_event!(release ctrl-a)), Some(key!(ctrl-a-b)));
+/// ```
+#[macro_export]
+macro_rules! key_event {
+    ($($tt:tt)*) => {
+        $crate::__private::key_event!(($crate) $($tt)*)
+    };
+}
+
+/// Convert a single token (punctuation, one char identifier, char literal
+/// or digit) to its char, at compile time.
+///
+/// This lets you build your own key macros on tokens, the way [key!] does.
+///
+/// ```
+/// use crokey::to_char;
+/// assert_eq!(to_char!(?), '?');
+/// assert_eq!(to_char!(a), 'a');
+/// assert_eq!(to_char!('/'), '/');
+/// assert_eq!(to_char!(7), '7');
+///
+/// macro_rules! ctrl_char {
+///     ($c:tt) => {
+///         crokey::KeyCombination::one_key(
+///             crossterm::event::KeyCode::Char(to_char!($c)),
+///             crossterm::event::KeyModifiers::CONTROL,
+///         )
+///     };
+/// }
+/// assert_eq!(ctrl_char!(^), crokey::key!(ctrl-'^'));
+/// ```
+pub use crokey_proc_macros::to_char;
+
+// Not public API. This is internal and to be used only by `key!`, `key_event!` and `keyseq!`.
 #[doc(hidden)]
 pub mod __private {
-    pub use crokey_proc_macros::key;
+    pub use crokey_proc_macros::{key, key_event, keyseq};
     pub use crossterm;
     pub use strict::OneToThree;
+    pub use std::vec;
 
     use crossterm::event::KeyModifiers;
     pub const MODS: KeyModifiers = KeyModifiers::NONE;
@@ -242,12 +546,20 @@ pub mod __private {
     pub const MODS_CTRL_ALT_SHIFT: KeyModifiers = KeyModifiers::CONTROL
         .union(KeyModifiers::ALT)
         .union(KeyModifiers::SHIFT);
+    pub const MODS_SUPER: KeyModifiers = KeyModifiers::SUPER;
+    pub const MODS_CTRL_SUPER: KeyModifiers = MODS_CTRL.union(KeyModifiers::SUPER);
+    pub const MODS_ALT_SUPER: KeyModifiers = MODS_ALT.union(KeyModifiers::SUPER);
+    pub const MODS_SHIFT_SUPER: KeyModifiers = MODS_SHIFT.union(KeyModifiers::SUPER);
+    pub const MODS_CTRL_ALT_SUPER: KeyModifiers = MODS_CTRL_ALT.union(KeyModifiers::SUPER);
+    pub const MODS_ALT_SHIFT_SUPER: KeyModifiers = MODS_ALT_SHIFT.union(KeyModifiers::SUPER);
+    pub const MODS_CTRL_SHIFT_SUPER: KeyModifiers = MODS_CTRL_SHIFT.union(KeyModifiers::SUPER);
+    pub const MODS_CTRL_ALT_SHIFT_SUPER: KeyModifiers = MODS_CTRL_ALT_SHIFT.union(KeyModifiers::SUPER);
 }
 
 #[cfg(test)]
 mod tests {
     use {
-        crate::{key, KeyCombination, OneToThree},
+        crate::{KeyCombination, OneToThree},
         crossterm::event::{KeyCode, KeyModifiers},
     };
 
@@ -260,6 +572,8 @@ mod tests {
         key!(alt - shift - f10);
         key!(ctrl - shift - f10);
         key!(ctrl - alt - shift - enter);
+        key!(super - s);
+        key!(ctrl - alt - shift - super - f1);
     };
 
     fn no_mod(code: KeyCode) -> KeyCombination {
@@ -293,6 +607,17 @@ mod tests {
         assert_eq!(key!(space), key!(' '));
         assert_eq!(key!(hyphen), key!('-'));
         assert_eq!(key!(minus), key!('-'));
+        assert_eq!(key!(0xe9), no_mod(KeyCode::Char('é')));
+        assert_eq!(key!(shift-0xe9), crate::parse("shift-0xE9").unwrap());
+        assert_eq!(key!(ctrl-a-0x62), key!(ctrl-a-b));
+        assert_eq!(
+            key!(shift-tab),
+            KeyCombination::new(KeyCode::Tab, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            key!(backtab),
+            KeyCombination::new(KeyCode::BackTab, KeyModifiers::SHIFT)
+        );
 
         assert_eq!(
             key!(ctrl-alt-a-b),
@@ -324,6 +649,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn control_chars() {
+        assert_eq!(no_mod(KeyCode::Char('\u{0}')).normalized(), key!(ctrl-'@'));
+        assert_eq!(no_mod(KeyCode::Char('\u{1c}')).normalized(), key!(ctrl-'\\'));
+        assert_eq!(no_mod(KeyCode::Char('\u{1d}')).normalized(), key!(ctrl-']'));
+        assert_eq!(no_mod(KeyCode::Char('\u{1e}')).normalized(), key!(ctrl-'^'));
+        assert_eq!(no_mod(KeyCode::Char('\u{1f}')).normalized(), key!(ctrl-'_'));
+        assert_eq!(no_mod(KeyCode::Char('\u{1}')).normalized(), key!(ctrl-a));
+        assert_eq!(no_mod(KeyCode::Char('\u{1b}')).normalized(), key!(esc));
+        assert_eq!(no_mod(KeyCode::Char('\t')).normalized(), key!(tab));
+        assert_eq!(no_mod(KeyCode::Char('\u{7f}')).normalized(), key!(backspace));
+        assert_eq!(crate::parse("ctrl-@").unwrap(), key!(ctrl-'@'));
+        assert_eq!(crate::parse("ctrl-^").unwrap(), key!(ctrl-'^'));
+    }
+
     #[test]
     fn format() {
         let format = crate::KeyCombinationFormat::default();
@@ -332,6 +672,70 @@ mod tests {
         assert_eq!(format.to_string(key!(alt-Space)), "Alt-Space");
         assert_eq!(format.to_string(key!(shift-' ')), "Shift-Space");
         assert_eq!(format.to_string(key!(alt-hyphen)), "Alt-Hyphen");
+        assert_eq!(format.to_string(KeyCode::Char('\u{1d}')), "Ctrl-]");
+        assert_eq!(format.to_string(KeyCode::Char('\u{0}')), "Ctrl-@");
+        assert_eq!(format.to_string(KeyCode::Char('\u{1b}')), "Esc");
+    }
+
+    #[test]
+    fn format_parse_round_trip() {
+        let mut swap = crate::ModifierRemap::new();
+        swap.insert(crate::Mods::alt(), crate::Mods::ctrl()).unwrap();
+        swap.insert(crate::Mods::ctrl(), crate::Mods::alt()).unwrap();
+        let formats = [
+            crate::KeyCombinationFormat::default(),
+            crate::KeyCombinationFormat::default().with_implicit_shift(),
+            crate::KeyCombinationFormat::default().with_lowercase_modifiers(),
+            crate::KeyCombinationFormat::default().with_backtab_as_shift_tab(),
+            "ctrl=^,alt=M-,sep=+,enter=Return".parse().unwrap(),
+            crate::KeyCombinationFormat::default().with_modifier_remap(swap),
+        ];
+        let keys = [
+            key!(ctrl-c),
+            key!(shift-a),
+            key!(alt-enter),
+            key!(ctrl-alt-f5),
+            key!(backtab),
+            key!(space),
+            key!(alt-hyphen),
+            key!(ctrl-a-b),
+            key!(pageup),
+        ];
+        for format in &formats {
+            for key in keys {
+                let s = format.to_string(key);
+                assert_eq!(format.parse(&s).unwrap(), key, "{s:?} doesn't round-trip");
+            }
+        }
+    }
+
+    #[test]
+    fn macro_and_parse_agree() {
+        let vectors = [
+            (key!(home), ["home", "HOME", "Home"]),
+            (key!(PageUp), ["pageup", "PAGEUP", "PgUp"]),
+            (key!(pgdn), ["pagedown", "PageDown", "PGDN"]),
+            (key!(RETURN), ["return", "Enter", "cr"]),
+            (key!(ctrl-Del), ["ctrl-delete", "Ctrl-Del", "CTRL-DEL"]),
+            (key!(Alt-Shift-F12), ["alt-shift-f12", "Shift-Alt-F12", "ALT-SHIFT-F12"]),
+            (key!(shift-Tab), ["shift-tab", "Shift-Tab", "SHIFT-TAB"]),
+            (key!(backtab), ["backtab", "BackTab", "BACKTAB"]),
+            (key!(ctrl-Space), ["ctrl-space", "ctrl- ", "Ctrl-SPACE"]),
+            (key!(alt-Minus), ["alt-minus", "alt-hyphen", "alt--"]),
+            (key!(ins), ["insert", "INS", "Ins"]),
+            (key!(PrintScreen), ["printscreen", "PRINTSCREEN", "PrintScreen"]),
+            (key!(ctrl-pause), ["ctrl-pause", "Ctrl-Pause", "CTRL-PAUSE"]),
+            (key!(shift-menu), ["shift-menu", "Shift-Menu", "SHIFT-MENU"]),
+            (key!(capslock), ["capslock", "CapsLock", "CAPSLOCK"]),
+            (key!(control-option-x), ["ctrl-alt-x", "control-option-x", "Option-Control-X"]),
+            (key!(super-s), ["super-s", "command-s", "Windows-s"]),
+            (key!(command-shift-z), ["super-shift-z", "shift-command-z", "Super-Shift-Z"]),
+        ];
+        for (key, raws) in vectors {
+            for raw in raws {
+                assert_eq!(crate::parse(raw).unwrap(), key, "{:?}", raw);
+            }
+        }
     }
 
     #[test]
@@ -0,0 +1,177 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+    },
+    std::{
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// A key combination held past the threshold of a [LongPressDetector]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LongPress(pub KeyCombination);
+
+/// Detect keys held without release for longer than a threshold.
+///
+/// This needs release events, so it's only meaningful when combining
+/// is enabled (i.e. with the kitty protocol).
+///
+/// As a held key doesn't always produce events, the detector must also
+/// be polled, for example when the event wait times out (see
+/// [LongPressDetector::deadline]).
+/// A long press is reported only once per press.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+///     std::time::{Duration, Instant},
+/// };
+/// let mut detector = LongPressDetector::new(Duration::from_millis(400));
+/// let start = Instant::now();
+/// let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+/// assert_eq!(detector.transform_at(&space, start), None);
+/// assert_eq!(detector.poll_at(start + Duration::from_millis(100)), None);
+/// assert_eq!(
+///     detector.poll_at(start + Duration::from_millis(400)),
+///     Some(LongPress(key!(space))),
+/// );
+/// assert_eq!(detector.poll_at(start + Duration::from_millis(500)), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LongPressDetector {
+    threshold: Duration,
+    held: Option<HeldKey>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HeldKey {
+    key: KeyCombination,
+    code: KeyCode,
+    since: Instant,
+    reported: bool,
+}
+
+impl Default for LongPressDetector {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500))
+    }
+}
+
+impl LongPressDetector {
+    /// Create a detector reporting the keys held at least `threshold`
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            held: None,
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock in [LongPressDetector::transform]
+    /// and [LongPressDetector::poll]
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.set_shared_clock(Arc::new(clock));
+        self
+    }
+    pub(crate) fn set_shared_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+    /// Forget the held key, if any
+    pub fn reset(&mut self) {
+        self.held = None;
+    }
+    /// Return the instant at which the held key, if any, becomes
+    /// a long press. It's the latest time the detector should be polled.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.held
+            .filter(|held| !held.reported)
+            .map(|held| held.since + self.threshold)
+    }
+    /// Receive a key event, return a long press if the event shows
+    /// the key has been held long enough
+    pub fn transform(&mut self, key: &KeyEvent) -> Option<LongPress> {
+        let now = self.clock.now();
+        self.transform_at(key, now)
+    }
+    /// Same as [LongPressDetector::transform], with the time the event
+    /// was received
+    pub fn transform_at(&mut self, key: &KeyEvent, now: Instant) -> Option<LongPress> {
+        if let KeyCode::Modifier(_) = key.code {
+            return None;
+        }
+        match key.kind {
+            KeyEventKind::Press => {
+                // a new press replaces the held key, if any
+                self.held = Some(HeldKey {
+                    key: KeyCombination::from(*key),
+                    code: key.code,
+                    since: now,
+                    reported: false,
+                });
+                None
+            }
+            KeyEventKind::Repeat => self.poll_at(now),
+            KeyEventKind::Release => {
+                if self.held.map_or(false, |held| held.code == key.code) {
+                    self.held = None;
+                }
+                None
+            }
+        }
+    }
+    /// Return a long press if the held key reached the threshold
+    pub fn poll(&mut self) -> Option<LongPress> {
+        let now = self.clock.now();
+        self.poll_at(now)
+    }
+    /// Same as [LongPressDetector::poll], at a given time
+    pub fn poll_at(&mut self, now: Instant) -> Option<LongPress> {
+        let held = self.held.as_mut()?;
+        if held.reported || now.saturating_duration_since(held.since) < self.threshold {
+            return None;
+        }
+        held.reported = true;
+        debug_event!(key = %held.key, "long press detected");
+        Some(LongPress(held.key))
+    }
+}
+
+#[test]
+fn check_long_press_detection() {
+    use crossterm::event::{KeyEventState, KeyModifiers};
+    let event = |code, kind| KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        kind,
+        state: KeyEventState::empty(),
+    };
+    let mut detector = LongPressDetector::new(Duration::from_millis(300));
+    let t0 = Instant::now();
+    let ms = |n| t0 + Duration::from_millis(n);
+    // a short press
+    assert_eq!(detector.transform_at(&event(KeyCode::Char('a'), KeyEventKind::Press), t0), None);
+    assert_eq!(detector.deadline(), Some(ms(300)));
+    assert_eq!(detector.transform_at(&event(KeyCode::Char('a'), KeyEventKind::Release), ms(100)), None);
+    assert_eq!(detector.deadline(), None);
+    assert_eq!(detector.poll_at(ms(400)), None);
+    // a long press, detected on a repeat event
+    assert_eq!(detector.transform_at(&event(KeyCode::Enter, KeyEventKind::Press), ms(500)), None);
+    assert_eq!(detector.transform_at(&event(KeyCode::Enter, KeyEventKind::Repeat), ms(700)), None);
+    assert_eq!(
+        detector.transform_at(&event(KeyCode::Enter, KeyEventKind::Repeat), ms(800)),
+        Some(LongPress(key!(enter))),
+    );
+    assert_eq!(detector.transform_at(&event(KeyCode::Enter, KeyEventKind::Repeat), ms(900)), None);
+    assert_eq!(detector.deadline(), None);
+}
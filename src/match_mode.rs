@@ -0,0 +1,133 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+};
+
+/// A way to compare a received key combination with the bound ones,
+/// used by [KeyBindingMap::resolve_with_fallbacks]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchMode {
+    /// The keys and modifiers must be the same (in canonical form). The
+    /// strict [KeyPattern::NoModifiers] bindings (e.g. `none-x`) are only
+    /// matched in this mode.
+    Exact,
+    /// The keys must be the same, shift being ignored, e.g. `ctrl-S` for
+    /// a binding on `ctrl-s`
+    IgnoreShift,
+    /// The received combination may have modifiers the bound one doesn't
+    /// have, e.g. `ctrl-alt-s` for a binding on `ctrl-s`. The binding with
+    /// the fewest extra modifiers is chosen.
+    IgnoreExtraModifiers,
+    /// The received combination matches a [KeyPattern] binding, e.g.
+    /// `alt-enter` for a binding on `*-enter`
+    Pattern,
+}
+
+impl MatchMode {
+    /// The usual order of fallbacks, from the most to the least strict
+    pub const DEFAULT_FALLBACKS: [MatchMode; 4] = [
+        Self::Exact,
+        Self::IgnoreShift,
+        Self::IgnoreExtraModifiers,
+        Self::Pattern,
+    ];
+}
+
+/// Return the combination without shift, its chars being lowercased
+pub(crate) fn unshifted(key: KeyCombination) -> KeyCombination {
+    let key = key.canonical();
+    KeyCombination {
+        codes: key.codes
+            .map(|code| match code {
+                KeyCode::Char(c) => KeyCode::Char(lowercase_char(c)),
+                KeyCode::BackTab => KeyCode::Tab,
+                code => code,
+            })
+            .sorted(),
+        modifiers: key.modifiers - KeyModifiers::SHIFT,
+    }
+}
+
+impl<A> KeyBindingMap<A> {
+    /// Return the action bound to a key combination, trying the match modes
+    /// in order, and the mode which matched.
+    ///
+    /// This makes it possible to tolerate terminals adding spurious
+    /// modifiers, while still preferring exact bindings.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut map = KeyBindingMap::new();
+    /// map.insert("ctrl-s", "save").unwrap();
+    /// map.insert("ctrl-alt-s", "save all").unwrap();
+    /// let modes = MatchMode::DEFAULT_FALLBACKS;
+    /// assert_eq!(
+    ///     map.resolve_with_fallbacks(key!(ctrl-alt-s), &modes),
+    ///     Some((&"save all", MatchMode::Exact)),
+    /// );
+    /// assert_eq!(
+    ///     map.resolve_with_fallbacks(key!(ctrl-shift-s), &modes),
+    ///     Some((&"save", MatchMode::IgnoreShift)),
+    /// );
+    /// assert_eq!(
+    ///     map.resolve_with_fallbacks(key!(ctrl-shift-alt-s), &[MatchMode::IgnoreExtraModifiers]),
+    ///     Some((&"save all", MatchMode::IgnoreExtraModifiers)),
+    /// );
+    /// assert_eq!(map.resolve_with_fallbacks(key!(ctrl-shift-s), &[MatchMode::Exact]), None);
+    ///
+    /// // a plain binding tolerates shift, a `none-` pattern doesn't
+    /// map.insert("x", "delete").unwrap();
+    /// map.insert_pattern("none-y", "yank").unwrap();
+    /// assert_eq!(map.resolve_with_fallbacks(key!(shift-x), &modes), Some((&"delete", MatchMode::IgnoreShift)));
+    /// assert_eq!(map.resolve_with_fallbacks(key!(y), &modes), Some((&"yank", MatchMode::Exact)));
+    /// assert_eq!(map.resolve_with_fallbacks(key!(shift-y), &modes), None);
+    /// ```
+    pub fn resolve_with_fallbacks(
+        &self,
+        key: KeyCombination,
+        modes: &[MatchMode],
+    ) -> Option<(&A, MatchMode)> {
+        for &mode in modes {
+            let action = match mode {
+                MatchMode::Exact => self.get(key).or_else(|| {
+                    self.patterns()
+                        .find(|(pattern, _)| {
+                            matches!(pattern, KeyPattern::NoModifiers(_)) && pattern.matches(key)
+                        })
+                        .map(|(_, action)| action)
+                }),
+                MatchMode::IgnoreShift => {
+                    let key = unshifted(key);
+                    self.single_key_bindings()
+                        .filter(|(bound, _)| unshifted(*bound) == key)
+                        .min_by_key(|(bound, _)| bound.modifiers.bits())
+                        .map(|(_, action)| action)
+                }
+                MatchMode::IgnoreExtraModifiers => {
+                    self.single_key_bindings()
+                        .filter_map(|(bound, action)| {
+                            key.extra_modifiers(bound)
+                                .map(|extra| ((extra.bits().count_ones(), extra.bits()), action))
+                        })
+                        // the bound modifiers differ, so there's no tie
+                        .min_by_key(|(extra, _)| *extra)
+                        .map(|(_, action)| action)
+                }
+                MatchMode::Pattern => self.get_pattern(key),
+            };
+            if let Some(action) = action {
+                debug_event!(?mode, "binding found with fallback");
+                return Some((action, mode));
+            }
+        }
+        None
+    }
+    fn single_key_bindings(&self) -> impl Iterator<Item = (KeyCombination, &A)> {
+        self.iter()
+            .filter(|(keys, _)| keys.len() == 1 && !self.is_disabled_canonical(keys))
+            .map(|(keys, action)| (keys[0], action))
+    }
+}
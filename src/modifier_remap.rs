@@ -0,0 +1,211 @@
+use {
+    crate::*,
+    crossterm::event::KeyModifiers,
+    std::fmt,
+};
+
+#[cfg(feature = "serde")]
+use {
+    serde::{
+        de,
+        ser::SerializeMap,
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+    std::collections::BTreeMap,
+};
+
+/// Error returned when the remapped modifiers aren't exactly one modifier
+#[derive(Debug)]
+pub struct ModifierRemapError {
+    pub from: Mods,
+}
+
+impl fmt::Display for ModifierRemapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} isn't one modifier and can't be remapped", self.from.to_string())
+    }
+}
+
+impl std::error::Error for ModifierRemapError {}
+
+/// Global remaps of modifiers, e.g. to treat super as alt, or to swap alt
+/// and ctrl, for users whose keyboards don't have the expected modifiers
+/// at the expected places.
+///
+/// The remaps are applied together, so that swaps are possible. Use it as
+/// a [Pipeline](crate::Pipeline) stage before the bindings are looked for,
+/// or call [ModifierRemap::apply].
+///
+/// In a configuration file, it's a map of modifier names:
+///
+/// ```
+/// use crokey::*;
+/// # #[cfg(feature = "serde")] {
+/// let remap: ModifierRemap = deser_hjson::from_str(r#"{
+///     super: alt
+///     alt: ctrl
+///     ctrl: alt
+/// }"#).unwrap();
+/// assert_eq!(remap.apply(key!(super-x)), key!(alt-x));
+/// assert_eq!(remap.apply(key!(ctrl-alt-shift-f1)), key!(ctrl-alt-shift-f1));
+/// assert_eq!(remap.apply(key!(alt-c)), key!(ctrl-c));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModifierRemap {
+    remaps: Vec<(KeyModifiers, KeyModifiers)>,
+}
+
+impl ModifierRemap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Remap a modifier to a set of modifiers (possibly empty, to ignore
+    /// the modifier), replacing any previous remap of this modifier.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::KeyModifiers,
+    /// };
+    /// let mut remap = ModifierRemap::new();
+    /// remap.insert(KeyModifiers::SUPER, Mods::alt()).unwrap();
+    /// remap.insert(Mods::ctrl(), Mods::ctrl_alt()).unwrap();
+    /// assert!(remap.insert(Mods::ctrl_alt(), Mods::shift()).is_err());
+    /// assert_eq!(remap.apply(key!(ctrl-super-a)), key!(ctrl-alt-a));
+    /// ```
+    pub fn insert<F: Into<Mods>, T: Into<Mods>>(
+        &mut self,
+        from: F,
+        to: T,
+    ) -> Result<(), ModifierRemapError> {
+        let from = from.into();
+        let to = to.into();
+        if from.0.bits().count_ones() != 1 {
+            return Err(ModifierRemapError { from });
+        }
+        self.remaps.retain(|(f, _)| *f != from.0);
+        self.remaps.push((from.0, to.0));
+        Ok(())
+    }
+    /// Return the modifiers the modifier is remapped to, if it's remapped
+    pub fn get(&self, from: KeyModifiers) -> Option<KeyModifiers> {
+        self.remaps
+            .iter()
+            .find(|(f, _)| *f == from)
+            .map(|(_, to)| *to)
+    }
+    /// Return the key combination with its modifiers remapped
+    pub fn apply(&self, key: KeyCombination) -> KeyCombination {
+        if self.remaps.is_empty() {
+            return key;
+        }
+        let mut modifiers = key.modifiers;
+        for (from, _) in &self.remaps {
+            modifiers.remove(*from);
+        }
+        for (from, to) in &self.remaps {
+            if key.modifiers.contains(*from) {
+                modifiers |= *to;
+            }
+        }
+        KeyCombination {
+            codes: key.codes,
+            modifiers,
+        }
+        .normalized()
+    }
+    /// Return the remaps in the other direction, e.g. to display the
+    /// modifiers the user presses to get the ones of a binding.
+    ///
+    /// Remaps to zero or several modifiers can't be inverted and are skipped.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut remap = ModifierRemap::new();
+    /// remap.insert(Mods::alt(), Mods::ctrl()).unwrap();
+    /// let format = KeyCombinationFormat::default().with_modifier_remap(remap.inverted());
+    /// assert_eq!(format.to_string(key!(ctrl-x)), "Alt-x");
+    /// ```
+    pub fn inverted(&self) -> Self {
+        let remaps = self
+            .remaps
+            .iter()
+            .filter(|(_, to)| to.bits().count_ones() == 1)
+            .map(|(from, to)| (*to, *from))
+            .collect();
+        Self { remaps }
+    }
+    pub fn len(&self) -> usize {
+        self.remaps.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.remaps.is_empty()
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl KeyStage for ModifierRemap {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        Some(self.apply(key))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ModifierRemap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let remaps = BTreeMap::<String, String>::deserialize(deserializer)?;
+        let mut remap = Self::new();
+        for (from, to) in remaps {
+            let from: Mods = from.parse().map_err(de::Error::custom)?;
+            let to: Mods = to.parse().map_err(de::Error::custom)?;
+            remap.insert(from, to).map_err(de::Error::custom)?;
+        }
+        Ok(remap)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ModifierRemap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut remaps: Vec<(String, String)> = self
+            .remaps
+            .iter()
+            .map(|(from, to)| (Mods(*from).to_string(), Mods(*to).to_string()))
+            .collect();
+        remaps.sort();
+        let mut map = serializer.serialize_map(Some(remaps.len()))?;
+        for (from, to) in &remaps {
+            map.serialize_entry(from, to)?;
+        }
+        map.end()
+    }
+}
+
+#[test]
+fn check_modifier_remap() {
+    let mut remap = ModifierRemap::new();
+    remap.insert(Mods::alt(), Mods::ctrl()).unwrap();
+    remap.insert(Mods::ctrl(), Mods::alt()).unwrap();
+    remap.insert(Mods::shift(), Mods::none()).unwrap();
+    assert_eq!(remap.len(), 3);
+    assert_eq!(remap.apply(key!(ctrl-x)), key!(alt-x));
+    assert_eq!(remap.apply(key!(ctrl-alt-x)), key!(ctrl-alt-x));
+    assert_eq!(remap.apply(key!(shift-f1)), key!(f1));
+    remap.insert(Mods::alt(), KeyModifiers::SUPER).unwrap();
+    assert_eq!(remap.len(), 3);
+    assert_eq!(remap.get(KeyModifiers::ALT), Some(KeyModifiers::SUPER));
+    assert_eq!(remap.apply(key!(alt-x)), key!(super-x));
+    assert_eq!(remap.inverted().len(), 2);
+    assert_eq!(remap.inverted().apply(key!(super-x)), key!(alt-x));
+    assert!(remap.insert(Mods::none(), Mods::alt()).is_err());
+}
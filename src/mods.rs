@@ -0,0 +1,140 @@
+use {
+    crate::*,
+    crossterm::event::KeyModifiers,
+    std::{
+        fmt,
+        ops::BitOr,
+        str::FromStr,
+    },
+};
+
+/// A set of key modifiers, with constructors making the building of
+/// key combinations without the [key!] macro less verbose.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::KeyCode,
+/// };
+/// assert_eq!(KeyCombination::new(KeyCode::Char('s'), Mods::ctrl()), key!(ctrl-s));
+/// assert_eq!(KeyCombination::new(KeyCode::Left, Mods::ctrl_shift()), key!(ctrl-shift-left));
+/// let mods: Mods = "ctrl+alt".parse().unwrap();
+/// assert_eq!(KeyCombination::new(KeyCode::Delete, mods), key!(ctrl-alt-delete));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mods(pub KeyModifiers);
+
+impl Mods {
+    pub const fn none() -> Self {
+        Self(KeyModifiers::NONE)
+    }
+    pub const fn ctrl() -> Self {
+        Self(KeyModifiers::CONTROL)
+    }
+    pub const fn alt() -> Self {
+        Self(KeyModifiers::ALT)
+    }
+    pub const fn shift() -> Self {
+        Self(KeyModifiers::SHIFT)
+    }
+    pub const fn ctrl_alt() -> Self {
+        Self(KeyModifiers::CONTROL.union(KeyModifiers::ALT))
+    }
+    pub const fn ctrl_shift() -> Self {
+        Self(KeyModifiers::CONTROL.union(KeyModifiers::SHIFT))
+    }
+    pub const fn alt_shift() -> Self {
+        Self(KeyModifiers::ALT.union(KeyModifiers::SHIFT))
+    }
+    pub const fn ctrl_alt_shift() -> Self {
+        Self(KeyModifiers::CONTROL.union(KeyModifiers::ALT).union(KeyModifiers::SHIFT))
+    }
+    pub const fn is_empty(self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Default for Mods {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl From<KeyModifiers> for Mods {
+    fn from(modifiers: KeyModifiers) -> Self {
+        Self(modifiers)
+    }
+}
+
+impl From<Mods> for KeyModifiers {
+    fn from(mods: Mods) -> Self {
+        mods.0
+    }
+}
+
+impl BitOr for Mods {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Parse modifiers separated by `+` or `-`, like `"ctrl+shift"`
+/// or `"Alt-Shift"`. An empty string gives no modifier.
+impl FromStr for Mods {
+    type Err = ParseKeyError;
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        if raw.is_empty() {
+            return Ok(Self(modifiers));
+        }
+        for name in raw.split(['+', '-']) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" | "command" | "windows" => KeyModifiers::SUPER,
+                _ => return Err(ParseKeyError::new(raw)),
+            };
+        }
+        Ok(Self(modifiers))
+    }
+}
+
+/// Write the modifiers the way [FromStr] reads them, like `"ctrl+shift"`
+impl fmt::Display for Mods {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+            (KeyModifiers::SUPER, "super"),
+        ] {
+            if self.0.contains(modifier) {
+                if !first {
+                    f.write_str("+")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn check_mods_parsing() {
+    assert_eq!("".parse::<Mods>().unwrap(), Mods::none());
+    assert_eq!("ctrl+shift".parse::<Mods>().unwrap(), Mods::ctrl_shift());
+    assert_eq!("Shift-Control".parse::<Mods>().unwrap(), Mods::ctrl_shift());
+    assert_eq!("alt+alt".parse::<Mods>().unwrap(), Mods::alt());
+    assert!("ctrl+".parse::<Mods>().is_err());
+    assert!("hyper".parse::<Mods>().is_err());
+    assert_eq!("command+option".parse::<Mods>().unwrap().0, KeyModifiers::SUPER | KeyModifiers::ALT);
+    let with_super = Mods(KeyModifiers::SHIFT | KeyModifiers::SUPER);
+    for mods in [Mods::none(), Mods::shift(), Mods::ctrl_alt(), Mods::ctrl_alt_shift(), with_super] {
+        assert_eq!(mods.to_string().parse::<Mods>().unwrap(), mods);
+    }
+    assert_eq!(Mods::ctrl() | Mods::alt(), Mods::ctrl_alt());
+}
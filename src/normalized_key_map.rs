@@ -0,0 +1,186 @@
+use {
+    crate::*,
+    std::collections::{
+        hash_map,
+        HashMap,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+/// A map whose keys are key combinations, normalized on insertion
+/// and on lookup.
+///
+/// As `KeyCombination` may hold unnormalized forms (e.g. `A` without
+/// the SHIFT modifier), a plain `HashMap<KeyCombination, V>` may fail to
+/// find a key when the config and the events don't write it the same way.
+/// A `NormalizedKeyMap` doesn't have this problem:
+///
+/// ```
+/// use crokey::*;
+/// use crossterm::event::{KeyCode, KeyModifiers};
+/// let mut map = NormalizedKeyMap::new();
+/// map.insert(key!(shift-a), "append");
+/// let upper = KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE);
+/// assert_eq!(map.get(upper), Some(&"append"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NormalizedKeyMap<V> {
+    map: HashMap<KeyCombination, V>,
+}
+
+impl<V> Default for NormalizedKeyMap<V> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<V> NormalizedKeyMap<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Insert a value, returning the one previously associated to
+    /// the same normalized key, if any
+    pub fn insert(&mut self, key: KeyCombination, value: V) -> Option<V> {
+        self.map.insert(key.normalized(), value)
+    }
+    pub fn get(&self, key: KeyCombination) -> Option<&V> {
+        self.map.get(&key.normalized())
+    }
+    pub fn get_mut(&mut self, key: KeyCombination) -> Option<&mut V> {
+        self.map.get_mut(&key.normalized())
+    }
+    pub fn contains_key(&self, key: KeyCombination) -> bool {
+        self.map.contains_key(&key.normalized())
+    }
+    pub fn remove(&mut self, key: KeyCombination) -> Option<V> {
+        self.map.remove(&key.normalized())
+    }
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+    /// Iterate over the entries, with normalized keys, in no specific order
+    pub fn iter(&self) -> hash_map::Iter<'_, KeyCombination, V> {
+        self.map.iter()
+    }
+}
+
+impl<V> FromIterator<(KeyCombination, V)> for NormalizedKeyMap<V> {
+    fn from_iter<I: IntoIterator<Item = (KeyCombination, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<V> From<HashMap<KeyCombination, V>> for NormalizedKeyMap<V> {
+    fn from(map: HashMap<KeyCombination, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// Return the groups of distinct key combinations which are the same
+/// once normalized, e.g. `A` and `shift-a`.
+///
+/// This helps detecting the entries of a `HashMap<KeyCombination, _>`
+/// which can't all be reached, or duplicates in a configuration.
+///
+/// ```
+/// use crokey::*;
+/// use crossterm::event::{KeyCode, KeyModifiers};
+/// let upper = KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE);
+/// let collisions = normalization_collisions([upper, key!(shift-a), key!(b)]);
+/// assert_eq!(collisions, vec![vec![upper, key!(shift-a)]]);
+/// ```
+pub fn normalization_collisions<I>(keys: I) -> Vec<Vec<KeyCombination>>
+where
+    I: IntoIterator<Item = KeyCombination>,
+{
+    let mut groups: Vec<(KeyCombination, Vec<KeyCombination>)> = Vec::new();
+    for key in keys {
+        let normalized = key.normalized();
+        match groups.iter_mut().find(|(n, _)| *n == normalized) {
+            Some((_, group)) => {
+                if !group.contains(&key) {
+                    group.push(key);
+                }
+            }
+            None => groups.push((normalized, vec![key])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+struct NormalizedKeyMapVisitor<V> {
+    _value: std::marker::PhantomData<V>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: Deserialize<'de>> de::Visitor<'de> for NormalizedKeyMapVisitor<V> {
+    type Value = NormalizedKeyMap<V>;
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a map of key combinations")
+    }
+    fn visit_map<M: de::MapAccess<'de>>(self, mut access: M) -> Result<Self::Value, M::Error> {
+        let mut map = NormalizedKeyMap::new();
+        while let Some((key, value)) = access.next_entry::<KeyCombination, V>()? {
+            if map.insert(key, value).is_some() {
+                return Err(de::Error::custom(format!("{} is given twice", key)));
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Reading a map fails when two entries are the same key once normalized
+#[cfg(feature = "serde")]
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for NormalizedKeyMap<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(NormalizedKeyMapVisitor {
+            _value: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<V: Serialize> Serialize for NormalizedKeyMap<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.map.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn check_normalized_key_map_deserialization() {
+    let map: NormalizedKeyMap<String> = serde_json::from_str(r#"{"shift-A": "up", "0x13": "s"}"#).unwrap();
+    assert_eq!(map.get(key!(shift-a)).unwrap(), "up");
+    assert_eq!(map.get(key!(ctrl-s)).unwrap(), "s");
+    for colliding in [r#"{"shift-a": "up", "Shift-A": "top"}"#, r#"{"ctrl-s": "a", "0x13": "b"}"#] {
+        assert!(serde_json::from_str::<NormalizedKeyMap<String>>(colliding).is_err(), "{}", colliding);
+    }
+}
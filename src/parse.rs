@@ -5,14 +5,24 @@
 
 use {
     crate::{
-        OneToThree,
+        aliases_config::parse_aliases_config,
+        shift_policy::uppercase_char,
         KeyCombination,
+        KeyNames,
+        OneToThree,
+        PlatformModifiers,
+        ShiftPolicy,
+        ShiftedSymbolPolicy,
     },
     crossterm::event::{
         KeyCode::{self, *},
         KeyModifiers,
     },
-    std::fmt,
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        fmt,
+    },
 };
 
 #[derive(Debug)]
@@ -35,47 +45,274 @@ impl fmt::Display for ParseKeyError {
 
 impl std::error::Error for ParseKeyError {}
 
+/// Allow generic code to handle conversions which can't fail,
+/// like the ones from a KeyCombination, and conversions from strings
+impl From<std::convert::Infallible> for ParseKeyError {
+    fn from(e: std::convert::Infallible) -> Self {
+        match e {}
+    }
+}
+
+/// The modifiers, as prefixes of the key combinations
+pub(crate) const MODIFIER_NAMES: &[(&str, KeyModifiers)] = &[
+    ("ctrl-", KeyModifiers::CONTROL),
+    ("alt-", KeyModifiers::ALT),
+    ("shift-", KeyModifiers::SHIFT),
+    ("super-", KeyModifiers::SUPER),
+];
+
+/// The default key names, read by [parse] and [parse_ascii](crate::parse_ascii)
+pub(crate) const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("esc", Esc),
+    ("enter", Enter),
+    ("return", Enter),
+    ("cr", Enter),
+    ("lf", Enter),
+    ("left", Left),
+    ("right", Right),
+    ("up", Up),
+    ("down", Down),
+    ("home", Home),
+    ("end", End),
+    ("pageup", PageUp),
+    ("pgup", PageUp),
+    ("pagedown", PageDown),
+    ("pgdn", PageDown),
+    ("backtab", BackTab),
+    ("unknown", Null),
+    ("backspace", Backspace),
+    ("del", Delete),
+    ("delete", Delete),
+    ("insert", Insert),
+    ("ins", Insert),
+    ("capslock", CapsLock),
+    ("scrolllock", ScrollLock),
+    ("numlock", NumLock),
+    ("printscreen", PrintScreen),
+    ("pause", Pause),
+    ("menu", Menu),
+    ("f1", F(1)),
+    ("f2", F(2)),
+    ("f3", F(3)),
+    ("f4", F(4)),
+    ("f5", F(5)),
+    ("f6", F(6)),
+    ("f7", F(7)),
+    ("f8", F(8)),
+    ("f9", F(9)),
+    ("f10", F(10)),
+    ("f11", F(11)),
+    ("f12", F(12)),
+    ("kp0", Char('\u{e037}')), // the numpad keys, see KEYPAD_KEYS
+    ("kp1", Char('\u{e038}')),
+    ("kp2", Char('\u{e039}')),
+    ("kp3", Char('\u{e03a}')),
+    ("kp4", Char('\u{e03b}')),
+    ("kp5", Char('\u{e03c}')),
+    ("kp6", Char('\u{e03d}')),
+    ("kp7", Char('\u{e03e}')),
+    ("kp8", Char('\u{e03f}')),
+    ("kp9", Char('\u{e040}')),
+    ("kpdecimal", Char('\u{e041}')),
+    ("kpdivide", Char('\u{e042}')),
+    ("kpmultiply", Char('\u{e043}')),
+    ("kpminus", Char('\u{e044}')),
+    ("kpplus", Char('\u{e045}')),
+    ("kpenter", Char('\u{e046}')),
+    ("kpequal", Char('\u{e047}')),
+    ("space", Char(' ')),
+    ("hyphen", Char('-')),
+    ("minus", Char('-')),
+    ("tab", Tab),
+];
+
+/// Other names of the modifiers, accepted by [parse] unless
+/// [ParseOptions::modifier_synonyms] is false, and by the [key!](crate::key!) macro
+pub const MODIFIER_SYNONYMS: &[(&str, KeyModifiers)] = &[
+    ("control-", KeyModifiers::CONTROL),
+    ("option-", KeyModifiers::ALT),
+    ("command-", KeyModifiers::SUPER),
+    ("windows-", KeyModifiers::SUPER),
+];
+
+/// The names of the modifiers standing for the [PlatformModifiers],
+/// primary then secondary
+pub(crate) const PLATFORM_MODIFIER_NAMES: [&str; 2] = ["primary-", "secondary-"];
+
+/// Options of [parse_with] and [parse_key_code_with]
+///
+/// The [key!](crate::key!) macro doesn't take options: it always parses
+/// with the default ones, plus the aliases of the project's `crokey.toml`.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// how shift is expressed in the parsed combinations
+    pub shift_policy: ShiftPolicy,
+    /// extra names of keys, lowercase
+    pub aliases: HashMap<String, KeyCode>,
+    /// whether the [MODIFIER_SYNONYMS] (e.g. `command` for `super`) are accepted
+    pub modifier_synonyms: bool,
+    /// what the `primary` and `secondary` modifiers stand for
+    pub platform_modifiers: PlatformModifiers,
+    /// how shift is handled with the ASCII punctuation chars
+    pub shifted_symbol_policy: ShiftedSymbolPolicy,
+    /// whether `shift-tab` is read as BackTab, like crossterm sends it
+    pub shift_tab_as_backtab: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            shift_policy: ShiftPolicy::default(),
+            aliases: HashMap::new(),
+            modifier_synonyms: true,
+            platform_modifiers: PlatformModifiers::current(),
+            shifted_symbol_policy: ShiftedSymbolPolicy::default(),
+            shift_tab_as_backtab: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn with_shift_policy(mut self, shift_policy: ShiftPolicy) -> Self {
+        self.shift_policy = shift_policy;
+        self
+    }
+    /// Set how shift is handled with the ASCII punctuation chars, which
+    /// should be the policy of the [Combiner](crate::Combiner)
+    pub fn with_shifted_symbol_policy(mut self, policy: ShiftedSymbolPolicy) -> Self {
+        self.shifted_symbol_policy = policy;
+        self
+    }
+    /// Set whether `shift-tab` is read as BackTab with SHIFT, which is
+    /// what crossterm sends, instead of Tab with SHIFT, which is what
+    /// some other backends send.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{KeyCode, KeyModifiers},
+    /// };
+    /// let tab = KeyCombination::new(KeyCode::Tab, KeyModifiers::SHIFT);
+    /// assert_eq!(parse("shift-tab").unwrap(), tab);
+    /// let options = ParseOptions::default().with_shift_tab_as_backtab(true);
+    /// assert_eq!(parse_with("shift-tab", &options).unwrap(), key!(backtab));
+    /// ```
+    pub fn with_shift_tab_as_backtab(mut self, shift_tab_as_backtab: bool) -> Self {
+        self.shift_tab_as_backtab = shift_tab_as_backtab;
+        self
+    }
+    /// Set whether the [MODIFIER_SYNONYMS] are accepted, e.g. to only
+    /// accept `ctrl`, `alt`, `shift` and `super` in a strict configuration.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(parse("control-option-x").unwrap(), key!(ctrl-alt-x));
+    /// assert_eq!(parse("command-s").unwrap(), key!(super-s));
+    /// let strict = ParseOptions::default().with_modifier_synonyms(false);
+    /// assert!(parse_with("command-s", &strict).is_err());
+    /// assert_eq!(parse_with("super-s", &strict).unwrap(), key!(super-s));
+    /// ```
+    pub fn with_modifier_synonyms(mut self, modifier_synonyms: bool) -> Self {
+        self.modifier_synonyms = modifier_synonyms;
+        self
+    }
+    /// Set what the `primary` and `secondary` modifiers stand for, instead
+    /// of the ones of the current platform
+    pub fn with_platform_modifiers(mut self, platform_modifiers: PlatformModifiers) -> Self {
+        self.platform_modifiers = platform_modifiers;
+        self
+    }
+    /// Add an extra name for a key, e.g. `"return"` for Enter.
+    ///
+    /// The name can't contain `-`, and it's case insensitive.
+    pub fn with_alias<S: AsRef<str>>(mut self, name: S, code: KeyCode) -> Self {
+        self.aliases.insert(name.as_ref().to_lowercase(), code);
+        self
+    }
+    /// Add the aliases defined in the `[aliases]` section of a crokey.toml
+    /// file, whose lines are like `leader = "f12"`.
+    ///
+    /// The `key!` macro reads the `crokey.toml` file of the crate being
+    /// compiled (or the file given by the `CROKEY_CONFIG` env var), so
+    /// giving its content to this function makes the runtime parser accept
+    /// the same keys as the macro:
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let config = r#"
+    /// [aliases]
+    /// leader = "f12"
+    /// ret = "enter" # a comment
+    /// "#; // usually include_str!("../crokey.toml")
+    /// let options = ParseOptions::default().with_aliases_config(config).unwrap();
+    /// assert_eq!(parse_with("ctrl-leader", &options).unwrap(), key!(ctrl-f12));
+    /// assert_eq!(parse_with("ret", &options).unwrap(), key!(enter));
+    /// ```
+    ///
+    /// The keys using an alias make cargo rebuild the crate when the file or
+    /// the `CROKEY_CONFIG` env var change, no build script is needed.
+    pub fn with_aliases_config(mut self, content: &str) -> Result<Self, ParseKeyError> {
+        for (name, value) in parse_aliases_config(content).map_err(ParseKeyError::new)? {
+            let code = parse_key_code(&value, false)?;
+            self.aliases.insert(name, code);
+        }
+        Ok(self)
+    }
+    /// Accept the custom names of a table, for example the names of
+    /// keys in the user's language, as aliases
+    pub fn with_key_names(mut self, key_names: &KeyNames) -> Self {
+        for (code, name) in key_names.iter() {
+            self.aliases.insert(name.to_lowercase(), code);
+        }
+        self
+    }
+}
+
+/// Parse a key code, which may be given as an alias, as a default key
+/// name, as a single char, or as the hexadecimal code point of a char
+/// prefixed with `0x` (e.g. `0xe9` for `é`).
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::KeyCode,
+/// };
+/// let options = ParseOptions::default().with_alias("Return", KeyCode::Enter);
+/// assert_eq!(parse_key_code_with("return", &options).unwrap(), KeyCode::Enter);
+/// assert_eq!(parse_key_code_with("0xe9", &options).unwrap(), KeyCode::Char('é'));
+/// assert_eq!(parse_key_code_with("PageUp", &options).unwrap(), KeyCode::PageUp);
+/// ```
+pub fn parse_key_code_with(raw: &str, options: &ParseOptions) -> Result<KeyCode, ParseKeyError> {
+    if raw.chars().count() == 1 {
+        return parse_key_code(raw, false);
+    }
+    let raw = raw.to_lowercase();
+    if let Some(code) = options.aliases.get(&raw) {
+        return Ok(*code);
+    }
+    parse_key_code(&raw, false)
+}
+
 pub fn parse_key_code(raw: &str, shift: bool) -> Result<KeyCode, ParseKeyError> {
+    if let Some((_, code)) = KEY_NAMES.iter().find(|(name, _)| *name == raw) {
+        return Ok(*code);
+    }
     let code = match raw {
-        "esc" => Esc,
-        "enter" => Enter,
-        "left" => Left,
-        "right" => Right,
-        "up" => Up,
-        "down" => Down,
-        "home" => Home,
-        "end" => End,
-        "pageup" => PageUp,
-        "pagedown" => PageDown,
-        "backtab" => BackTab,
-        "backspace" => Backspace,
-        "del" => Delete,
-        "delete" => Delete,
-        "insert" => Insert,
-        "ins" => Insert,
-        "f1" => F(1),
-        "f2" => F(2),
-        "f3" => F(3),
-        "f4" => F(4),
-        "f5" => F(5),
-        "f6" => F(6),
-        "f7" => F(7),
-        "f8" => F(8),
-        "f9" => F(9),
-        "f10" => F(10),
-        "f11" => F(11),
-        "f12" => F(12),
-        "space" => Char(' '),
-        "hyphen" => Char('-'),
-        "minus" => Char('-'),
-        "tab" => Tab,
-        c if c.len() == 1 => {
+        "\r" | "\n" => Enter, // as the key events some terminals send
+        c if c.chars().count() == 1 => {
             let mut c = c.chars().next().unwrap();
             if shift {
-                c = c.to_ascii_uppercase();
+                c = uppercase_char(c);
             }
             Char(c)
         }
+        hex if hex.starts_with("0x") => {
+            let c = u32::from_str_radix(&hex[2..], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| ParseKeyError::new(raw))?;
+            Char(if shift { uppercase_char(c) } else { c })
+        }
         _ => {
             return Err(ParseKeyError::new(raw));
         }
@@ -83,6 +320,23 @@ pub fn parse_key_code(raw: &str, shift: bool) -> Result<KeyCode, ParseKeyError>
     Ok(code)
 }
 
+/// Return the modifier whose name starts the lowercase string, and the
+/// length of this name, including the `-`
+pub(crate) fn modifier_prefix(raw: &str, options: &ParseOptions) -> Option<(KeyModifiers, usize)> {
+    let synonyms: &[_] = if options.modifier_synonyms { MODIFIER_SYNONYMS } else { &[] };
+    let [primary, secondary] = PLATFORM_MODIFIER_NAMES;
+    let platform_modifiers = [
+        (primary, options.platform_modifiers.primary),
+        (secondary, options.platform_modifiers.secondary),
+    ];
+    MODIFIER_NAMES
+        .iter()
+        .chain(synonyms)
+        .chain(&platform_modifiers)
+        .find(|(name, _)| raw.starts_with(name))
+        .map(|(name, modifier)| (*modifier, name.len()))
+}
+
 /// parse a string as a keyboard key combination definition.
 ///
 /// About the case:
@@ -90,22 +344,35 @@ pub fn parse_key_code(raw: &str, shift: bool) -> Result<KeyCode, ParseKeyError>
 /// but uppercase when it was typed with shift (i.e. we receive
 /// "g" for a lowercase, and "shift-G" for an uppercase)
 pub fn parse(raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    parse_with(raw, &ParseOptions::default())
+}
+
+/// Parse a string as a key combination, with options.
+///
+/// ```
+/// use crokey::*;
+/// use crossterm::event::{KeyCode, KeyModifiers};
+/// let options = ParseOptions::default()
+///     .with_shift_policy(ShiftPolicy::PreferUppercaseChar)
+///     .with_alias("leader", KeyCode::F(12));
+/// assert_eq!(
+///     parse_with("shift-é", &options).unwrap(),
+///     KeyCombination::new(KeyCode::Char('É'), KeyModifiers::NONE),
+/// );
+/// assert_eq!(parse_with("ctrl-leader", &options).unwrap(), key!(ctrl-f12));
+/// ```
+pub fn parse_with(raw: &str, options: &ParseOptions) -> Result<KeyCombination, ParseKeyError> {
     let mut modifiers = KeyModifiers::empty();
-    let raw = raw.to_ascii_lowercase();
+    // we avoid allocating when the string is already lowercase
+    let raw: Cow<str> = if raw.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(raw.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(raw)
+    };
     let mut raw: &str = raw.as_ref();
-    loop {
-        if let Some(end) = raw.strip_prefix("ctrl-") {
-            raw = end;
-            modifiers.insert(KeyModifiers::CONTROL);
-        } else if let Some(end) = raw.strip_prefix("alt-") {
-            raw = end;
-            modifiers.insert(KeyModifiers::ALT);
-        } else if let Some(end) = raw.strip_prefix("shift-") {
-            raw = end;
-            modifiers.insert(KeyModifiers::SHIFT);
-        } else {
-            break;
-        }
+    while let Some((modifier, len)) = modifier_prefix(raw, options) {
+        raw = &raw[len..];
+        modifiers.insert(modifier);
     }
     let codes = if raw == "-" {
         OneToThree::One(Char('-'))
@@ -113,7 +380,14 @@ pub fn parse(raw: &str) -> Result<KeyCombination, ParseKeyError> {
         let mut codes = Vec::new();
         let shift =  modifiers.contains(KeyModifiers::SHIFT);
         for raw in raw.split('-') {
-            let code = parse_key_code(raw, shift)?;
+            let mut code = parse_key_code_with(raw, options)?;
+            if shift {
+                code = match code {
+                    Char(c) => Char(uppercase_char(c)),
+                    Tab if options.shift_tab_as_backtab => BackTab,
+                    code => code,
+                };
+            }
             if code == BackTab {
                 // Crossterm always sends SHIFT with backtab
                 modifiers.insert(KeyModifiers::SHIFT);
@@ -122,7 +396,12 @@ pub fn parse(raw: &str) -> Result<KeyCombination, ParseKeyError> {
         }
         codes.try_into().map_err(|_| ParseKeyError::new("".to_string()))?
     };
-    Ok(KeyCombination::new(codes, modifiers))
+    let key = KeyCombination::new(codes, modifiers);
+    let key = match options.shift_policy {
+        ShiftPolicy::Both => key.normalized(), // e.g. `0x1d` is `ctrl-]`
+        policy => key.with_shift_policy(policy),
+    };
+    Ok(key.with_shifted_symbol_policy(options.shifted_symbol_policy))
 }
 
 #[test]
@@ -141,11 +420,25 @@ fn check_key_parsing() {
         "backtab",
         KeyCombination::new(KeyCode::BackTab, KeyModifiers::SHIFT),
     );
+    check_ok(
+        "shift-tab",
+        KeyCombination::new(KeyCode::Tab, KeyModifiers::SHIFT),
+    );
     check_ok("f1", KeyCombination::from(F(1)));
     check_ok("F2", KeyCombination::from(F(2)));
     check_ok("Enter", KeyCombination::from(Enter));
     check_ok("alt-enter", KeyCombination::new(Enter, KeyModifiers::ALT));
     check_ok("insert", KeyCombination::from(Insert));
+    check_ok("PrintScreen", KeyCombination::from(PrintScreen));
+    check_ok("ctrl-pause", KeyCombination::new(Pause, KeyModifiers::CONTROL));
+    check_ok("shift-menu", key!(shift-menu));
+    for code in [CapsLock, ScrollLock, NumLock, PrintScreen, Pause, Menu] {
+        let key = KeyCombination::from(code);
+        let formatted = KeyCombinationFormat::default().to_string(key);
+        check_ok(&formatted, key);
+        assert_eq!(parse_ascii(&formatted.to_ascii_lowercase()), Ok(key));
+    }
+    assert_eq!(key!(alt-scrolllock-numlock), parse("alt-ScrollLock-NumLock").unwrap());
     check_ok(
         "ctrl-q",
         KeyCombination::new(Char('q'), KeyModifiers::CONTROL),
@@ -215,3 +508,51 @@ fn check_key_parsing() {
         ),
     );
 }
+
+#[test]
+fn check_enter_forms() {
+    use {
+        crate::*,
+        crossterm::event::KeyEvent,
+    };
+    // depending on the terminal and its mode, enter may come as
+    // Enter, '\r' or '\n'
+    let events = [
+        KeyEvent::new(Enter, KeyModifiers::NONE),
+        KeyEvent::new(Char('\r'), KeyModifiers::NONE),
+        KeyEvent::new(Char('\n'), KeyModifiers::NONE),
+    ];
+    for raw in ["enter", "Return", "cr", "lf", "\r", "\n"] {
+        let parsed = parse(raw).unwrap();
+        assert_eq!(parsed, key!(enter));
+        for event in events {
+            assert_eq!(KeyCombination::from(event), parsed);
+        }
+    }
+    assert_eq!(parse("alt-return").unwrap(), key!(alt-enter));
+    assert_eq!(KeyCombination::new(Char('\r'), KeyModifiers::ALT).normalized(), key!(alt-enter));
+}
+
+#[test]
+fn check_aliases_config() {
+    let config = r##"
+# other sections are ignored
+[package]
+name = "leader"
+
+[aliases]
+leader = "f12"
+"Ret" = "Enter"
+hash = "#" # a comment
+'sharp' = '#'
+home = "end" # aliases come before the default names
+"##;
+    let options = ParseOptions::default().with_aliases_config(config).unwrap();
+    assert_eq!(parse_with("alt-leader", &options).unwrap(), crate::key!(alt-f12));
+    assert_eq!(parse_with("ctrl-ret", &options).unwrap(), crate::key!(ctrl-enter));
+    assert_eq!(parse_with("ctrl-hash", &options).unwrap(), crate::key!(ctrl-'#'));
+    assert_eq!(parse_with("ctrl-sharp", &options).unwrap(), crate::key!(ctrl-'#'));
+    assert_eq!(parse_with("home", &options).unwrap(), crate::key!(end));
+    assert!(ParseOptions::default().with_aliases_config("[aliases]\nleader = f12").is_err());
+    assert!(ParseOptions::default().with_aliases_config("[aliases]\nleader = \"nope\"").is_err());
+}
@@ -0,0 +1,402 @@
+use {
+    crate::*,
+    crossterm::event::KeyEvent,
+    std::{
+        fmt,
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// A step in a [Pipeline], receiving key combinations and deciding
+/// what is passed to the next step.
+///
+/// Closures `FnMut(KeyCombination) -> Option<KeyCombination>` are stages too.
+pub trait KeyStage {
+    /// Receive a key combination, return the one which should be given
+    /// to the next stage or `None` when the combination is consumed or
+    /// filtered out.
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination>;
+    /// Return the instant at which the stage should be flushed, when it
+    /// holds keys, e.g. waiting for the end of a sequence
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+    /// Return a key combination held by the stage whose deadline is
+    /// passed, if any. Called by [Pipeline::poll] until it returns `None`.
+    fn flush(&mut self) -> Option<KeyCombination> {
+        None
+    }
+}
+
+impl<F> KeyStage for F
+where
+    F: FnMut(KeyCombination) -> Option<KeyCombination>,
+{
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        self(key)
+    }
+}
+
+/// A stage normalizing the key combinations it receives.
+///
+/// See [KeyCombination::normalized].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Normalizer;
+
+impl KeyStage for Normalizer {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        Some(key.normalized())
+    }
+}
+
+/// A chain of stages: key events are first given to a [Combiner], then
+/// the produced key combinations go through the stages, in order.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+/// };
+/// let mut pipeline = Pipeline::default()
+///     .with_stage(Normalizer)
+///     .with_stage(|key: KeyCombination| {
+///         // ctrl-h is sent by some terminals for backspace
+///         if key == key!(ctrl-h) {
+///             Some(key!(backspace))
+///         } else {
+///             Some(key)
+///         }
+///     });
+/// assert_eq!(
+///     pipeline.transform(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)),
+///     Some(key!(backspace)),
+/// );
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    combiner: Combiner,
+    stages: Vec<Box<dyn KeyStage>>,
+    long_press_detector: Option<LongPressDetector>,
+    long_press: Option<LongPress>,
+    key_log: Option<KeyLog>,
+    /// the clock given to the combiner, long press detector and key log,
+    /// the system's one when `None`
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("combiner", &self.combiner)
+            .field("stages", &self.stages.len())
+            .field("long_press_detector", &self.long_press_detector)
+            .field("key_log", &self.key_log.as_ref().map(KeyLog::len))
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+impl Pipeline {
+    /// Create a pipeline with the given combiner and no stage
+    pub fn new(combiner: Combiner) -> Self {
+        Self {
+            combiner,
+            stages: Vec::new(),
+            long_press_detector: None,
+            long_press: None,
+            key_log: None,
+            clock: None,
+        }
+    }
+    /// Use the given clock in the combiner, the long press detector
+    /// and the key log, e.g. a [MockClock](crate::testing::MockClock)
+    /// in tests.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::{*, testing::MockClock},
+    ///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    ///     std::time::Duration,
+    /// };
+    /// let clock = MockClock::new();
+    /// let mut pipeline = Pipeline::default()
+    ///     .with_key_log(10)
+    ///     .with_clock(clock.clone());
+    /// pipeline.transform(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+    /// clock.advance(Duration::from_secs(3));
+    /// pipeline.transform(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE));
+    /// assert!(pipeline.key_log().unwrap().dump().contains("3.000s"));
+    /// ```
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        self.combiner.set_shared_clock(clock.clone());
+        if let Some(detector) = self.long_press_detector.as_mut() {
+            detector.set_shared_clock(clock.clone());
+        }
+        if let Some(key_log) = self.key_log.as_mut() {
+            key_log.set_shared_clock(clock.clone());
+        }
+        self.clock = Some(clock);
+        self
+    }
+    /// Add a stage at the end of the pipeline
+    pub fn with_stage<S: KeyStage + 'static>(mut self, stage: S) -> Self {
+        self.push_stage(stage);
+        self
+    }
+    /// Add a stage at the end of the pipeline
+    pub fn push_stage<S: KeyStage + 'static>(&mut self, stage: S) {
+        self.stages.push(Box::new(stage));
+    }
+    /// Detect the keys held past the given duration.
+    ///
+    /// The long presses aren't returned by [Pipeline::transform] but by
+    /// [Pipeline::poll_long_press], which should be called after every
+    /// event and when the wait for events times out. The held key is
+    /// still returned by the combiner on release (or on press for simple keys).
+    ///
+    /// Long presses are only detected when combining is enabled, as
+    /// there's no release event otherwise.
+    ///
+    /// ```
+    /// use {
+    ///     crokey::*,
+    ///     crossterm::event::{self, Event},
+    ///     std::time::{Duration, Instant},
+    /// };
+    /// # fn run() -> std::io::Result<()> {
+    /// let mut pipeline = Pipeline::default()
+    ///     .with_long_press(Duration::from_millis(600));
+    /// pipeline.combiner_mut().enable_combining()?;
+    /// loop {
+    ///     let timeout = pipeline
+    ///         .long_press_deadline()
+    ///         .map_or(Duration::from_secs(60), |d| d.saturating_duration_since(Instant::now()));
+    ///     if event::poll(timeout)? {
+    ///         if let Event::Key(key_event) = event::read()? {
+    ///             if let Some(key) = pipeline.transform(key_event) {
+    ///                 // handle the key
+    ///             }
+    ///         }
+    ///     }
+    ///     if let Some(LongPress(key)) = pipeline.poll_long_press() {
+    ///         // show a preview
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn with_long_press(mut self, threshold: Duration) -> Self {
+        let mut detector = LongPressDetector::new(threshold);
+        if let Some(clock) = &self.clock {
+            detector.set_shared_clock(clock.clone());
+        }
+        self.long_press_detector = Some(detector);
+        self
+    }
+    /// Return the instant at which the held key, if any, becomes
+    /// a long press
+    pub fn long_press_deadline(&self) -> Option<Instant> {
+        self.long_press_detector.as_ref()?.deadline()
+    }
+    /// Return a long press if a key has been held past the threshold
+    /// given to [Pipeline::with_long_press]
+    pub fn poll_long_press(&mut self) -> Option<LongPress> {
+        self.long_press
+            .take()
+            .or_else(|| self.long_press_detector.as_mut()?.poll())
+    }
+    /// Record the last key events and the key combinations produced by
+    /// the combiner (before the stages) in a [KeyLog] of the given capacity
+    pub fn with_key_log(mut self, capacity: usize) -> Self {
+        let mut key_log = KeyLog::new(capacity);
+        if let Some(clock) = &self.clock {
+            key_log.set_shared_clock(clock.clone());
+        }
+        self.key_log = Some(key_log);
+        self
+    }
+    pub fn key_log(&self) -> Option<&KeyLog> {
+        self.key_log.as_ref()
+    }
+    pub fn key_log_mut(&mut self) -> Option<&mut KeyLog> {
+        self.key_log.as_mut()
+    }
+    pub fn combiner(&self) -> &Combiner {
+        &self.combiner
+    }
+    pub fn combiner_mut(&mut self) -> &mut Combiner {
+        &mut self.combiner
+    }
+    /// Receive a key event and return a key combination if one went
+    /// through all the stages.
+    pub fn transform(&mut self, key: KeyEvent) -> Option<KeyCombination> {
+        self.transform_event(key).map(KeyCombination::from)
+    }
+    /// Receive a key event and return a key combination event if a key
+    /// combination went through all the stages.
+    ///
+    /// The kind and state are the ones given by [Combiner::transform_event].
+    pub fn transform_event(&mut self, key: KeyEvent) -> Option<KeyCombinationEvent> {
+        if self.combiner.is_combining() {
+            if let Some(detector) = self.long_press_detector.as_mut() {
+                // a long press seen here is kept for poll_long_press
+                if let Some(long_press) = detector.transform(&key) {
+                    self.long_press = Some(long_press);
+                }
+            }
+        }
+        if let Some(key_log) = self.key_log.as_mut() {
+            key_log.record_event(key);
+        }
+        let event = self.combiner.transform_event(key)?;
+        if let Some(key_log) = self.key_log.as_mut() {
+            key_log.record_combination(event.combination);
+        }
+        let combination = self.process(event.combination)?;
+        Some(KeyCombinationEvent { combination, ..event })
+    }
+    /// Inject a synthetic key combination, going through the combiner
+    /// then the stages like a real one.
+    ///
+    /// See [Combiner::simulate].
+    pub fn simulate(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        let key = self.combiner.simulate(key)?;
+        self.process(key)
+    }
+    /// Give a key combination to the stages, skipping the combiner
+    pub fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        self.process_from(0, key)
+    }
+    /// Return the earliest instant at which a stage holding keys should
+    /// be flushed by [Pipeline::poll]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.stages.iter().filter_map(|stage| stage.deadline()).min()
+    }
+    /// Flush the stages whose deadline is passed, and return the first
+    /// key combination going through the following stages.
+    ///
+    /// This should be called when the wait for events times out, and
+    /// again as long as it returns a key combination.
+    pub fn poll(&mut self) -> Option<KeyCombination> {
+        for i in 0..self.stages.len() {
+            while let Some(key) = self.stages[i].flush() {
+                if let Some(key) = self.process_from(i + 1, key) {
+                    return Some(key);
+                }
+            }
+        }
+        None
+    }
+    fn process_from(&mut self, first_stage: usize, key: KeyCombination) -> Option<KeyCombination> {
+        let mut key = key;
+        for stage in &mut self.stages[first_stage..] {
+            let processed = stage.process(key);
+            #[cfg(feature = "tracing")]
+            if processed.is_none() {
+                tracing::debug!(%key, "key combination dropped by a stage");
+            }
+            key = processed?;
+        }
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{*, testing::MockClock},
+        crossterm::event::{KeyCode, KeyModifiers},
+        std::{
+            sync::mpsc,
+            time::Duration,
+        },
+    };
+
+    #[test]
+    fn stages_are_applied_in_order() {
+        let mut pipeline = Pipeline::default()
+            .with_stage(|key: KeyCombination| {
+                if key == key!(a) { None } else { Some(key) }
+            })
+            .with_stage(Normalizer);
+        assert_eq!(pipeline.process(key!(a)), None);
+        assert_eq!(
+            pipeline.process(KeyCombination::new(KeyCode::Char('b'), KeyModifiers::SHIFT)),
+            Some(key!(shift-b)),
+        );
+    }
+
+    #[test]
+    fn esc_prefix_is_folded() {
+        let clock = MockClock::new();
+        let folder = EscPrefixFolder::new(Duration::from_millis(20)).with_clock(clock.clone());
+        let mut pipeline = Pipeline::default()
+            .with_stage(folder)
+            .with_stage(Debouncer::new(Duration::from_millis(10)).with_clock(clock.clone()));
+        assert_eq!(pipeline.process(key!(esc)), None);
+        assert_eq!(pipeline.process(key!(ctrl-x)), Some(key!(ctrl-alt-x)));
+        assert_eq!(pipeline.process(key!(esc)), None);
+        assert_eq!(pipeline.process(key!(esc)), Some(key!(esc)));
+        assert!(pipeline.deadline().is_some());
+        // a stale esc isn't a prefix, the key is held until polled
+        clock.advance(Duration::from_millis(30));
+        assert_eq!(pipeline.process(key!(a)), Some(key!(esc)));
+        assert_eq!(pipeline.poll(), Some(key!(a)));
+        assert_eq!(pipeline.poll(), None);
+        assert_eq!(pipeline.deadline(), None);
+    }
+
+    #[test]
+    fn identical_keys_are_debounced() {
+        let clock = MockClock::new();
+        let mut pipeline = Pipeline::default()
+            .with_stage(Debouncer::new(Duration::from_millis(10)).with_clock(clock.clone()));
+        assert_eq!(pipeline.process(key!(a)), Some(key!(a)));
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(pipeline.process(key!(a)), None);
+        assert_eq!(pipeline.process(key!(shift-a)), Some(key!(shift-a)));
+        assert_eq!(pipeline.process(key!(a)), Some(key!(a)));
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(pipeline.process(key!(a)), Some(key!(a)));
+    }
+
+    #[test]
+    fn sequences_are_matched_in_a_stage() {
+        let mut bindings = KeyBindingMap::new();
+        bindings.insert("ctrl-k ctrl-c", "comment").unwrap();
+        bindings.insert("ctrl-k", "kill").unwrap();
+        bindings.insert("g g", "top").unwrap();
+        let (sender, actions) = mpsc::channel();
+        let clock = MockClock::new();
+        let stage = SequenceStage::new(
+            SequenceMatcher::new(bindings),
+            Duration::from_millis(500),
+            move |matched| sender.send(matched).unwrap(),
+        ).with_clock(clock.clone());
+        let mut pipeline = Pipeline::default()
+            .with_stage(stage)
+            .with_stage(Normalizer);
+        assert_eq!(pipeline.process(key!(g)), None);
+        // the keys of an interrupted sequence go to the next stages
+        assert_eq!(pipeline.process(key!(x)), Some(key!(g)));
+        assert_eq!(pipeline.poll(), Some(key!(x)));
+        assert_eq!(pipeline.poll(), None);
+        assert_eq!(pipeline.process(key!(ctrl-k)), None);
+        assert_eq!(pipeline.poll(), None);
+        assert!(actions.try_recv().is_err());
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(pipeline.poll(), None);
+        assert_eq!(actions.try_recv(), Ok(SequenceMatch::Matched("kill")));
+        assert_eq!(pipeline.deadline(), None);
+    }
+
+    #[test]
+    fn combiner_is_a_stage() {
+        let mut pipeline = Pipeline::default().with_stage(Combiner::default());
+        assert_eq!(pipeline.process(key!(ctrl-a)), Some(key!(ctrl-a)));
+    }
+}
@@ -0,0 +1,126 @@
+use {
+    crate::*,
+    crossterm::event::KeyModifiers,
+};
+
+/// The modifiers the `primary` and `secondary` names stand for, so that
+/// a single configuration serves all platforms: `primary-s` is `command-s`
+/// on macOS and `ctrl-s` elsewhere.
+///
+/// This follows the `CtrlCmd` and `WinCtrl` conventions of VS Code.
+/// Those names are resolved by the parser, when the configuration is loaded,
+/// and aren't accepted by the [key!](crate::key!) macro.
+///
+/// ```
+/// use crokey::*;
+/// let options = ParseOptions::default().with_platform_modifiers(PlatformModifiers::MAC);
+/// assert_eq!(parse_with("primary-s", &options).unwrap(), key!(command-s));
+/// let options = options.with_platform_modifiers(PlatformModifiers::OTHER);
+/// assert_eq!(parse_with("primary-s", &options).unwrap(), key!(ctrl-s));
+/// assert_eq!(parse_with("secondary-x", &options).unwrap(), key!(super-x));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlatformModifiers {
+    /// What `primary` stands for
+    pub primary: KeyModifiers,
+    /// What `secondary` stands for
+    pub secondary: KeyModifiers,
+}
+
+impl PlatformModifiers {
+    /// The modifiers on macOS: command is primary, control secondary
+    pub const MAC: Self = Self {
+        primary: KeyModifiers::SUPER,
+        secondary: KeyModifiers::CONTROL,
+    };
+    /// The modifiers on other platforms: control is primary, super secondary
+    pub const OTHER: Self = Self {
+        primary: KeyModifiers::CONTROL,
+        secondary: KeyModifiers::SUPER,
+    };
+    /// Return the modifiers of the platform the application is compiled for
+    pub const fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MAC
+        } else {
+            Self::OTHER
+        }
+    }
+}
+
+impl Default for PlatformModifiers {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl KeyCombination {
+    /// Exchange the control and super (command) modifiers, which converts
+    /// a combination between macOS and the other platforms, e.g. `command-c`
+    /// to `ctrl-c`.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// assert_eq!(key!(command-c).swap_ctrl_cmd(), key!(ctrl-c));
+    /// assert_eq!(key!(ctrl-command-f).swap_ctrl_cmd(), key!(ctrl-command-f));
+    /// assert_eq!(key!(alt-x).swap_ctrl_cmd(), key!(alt-x));
+    /// ```
+    pub fn swap_ctrl_cmd(self) -> Self {
+        self.with_platform_modifiers(PlatformModifiers::MAC, PlatformModifiers::OTHER)
+    }
+    /// Convert a combination written with the primary and secondary modifiers
+    /// of a platform to the ones of another platform.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let key = key!(command-shift-z);
+    /// assert_eq!(
+    ///     key.with_platform_modifiers(PlatformModifiers::MAC, PlatformModifiers::OTHER),
+    ///     key!(ctrl-shift-z),
+    /// );
+    /// ```
+    pub fn with_platform_modifiers(self, from: PlatformModifiers, to: PlatformModifiers) -> Self {
+        let mut modifiers = self.modifiers - from.primary - from.secondary;
+        if self.modifiers.contains(from.primary) {
+            modifiers |= to.primary;
+        }
+        if self.modifiers.contains(from.secondary) {
+            modifiers |= to.secondary;
+        }
+        Self {
+            codes: self.codes,
+            modifiers,
+        }
+    }
+}
+
+impl KeySequence {
+    /// Convert the keys of the sequence between platforms,
+    /// see [KeyCombination::with_platform_modifiers]
+    pub fn with_platform_modifiers(&self, from: PlatformModifiers, to: PlatformModifiers) -> Self {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| key.with_platform_modifiers(from, to))
+            .collect();
+        Self::new(keys)
+    }
+}
+
+#[test]
+fn check_platform_modifiers() {
+    let options = ParseOptions::default().with_platform_modifiers(PlatformModifiers::MAC);
+    assert_eq!(parse_with("primary-secondary-a", &options).unwrap(), key!(ctrl-command-a));
+    assert_eq!(parse_with("Primary-Shift-Left", &options).unwrap(), key!(command-shift-left));
+    let strict = options.with_modifier_synonyms(false);
+    assert_eq!(parse_with("primary-q", &strict).unwrap(), key!(super-q));
+    assert_eq!(
+        key!(ctrl-command-a).with_platform_modifiers(PlatformModifiers::MAC, PlatformModifiers::MAC),
+        key!(ctrl-command-a),
+    );
+    let sequence: KeySequence = "command-k command-s".parse().unwrap();
+    assert_eq!(
+        sequence.with_platform_modifiers(PlatformModifiers::MAC, PlatformModifiers::OTHER),
+        "ctrl-k ctrl-s".parse().unwrap(),
+    );
+}
@@ -0,0 +1,70 @@
+//! Reading of the `[aliases]` section of crokey.toml files.
+//!
+//! This file is used by both crokey and its proc macros, so that the
+//! runtime parser and the `key!` macro read the same aliases.
+
+/// Parse the `[aliases]` section of a crokey.toml file, whose lines
+/// are like `leader = "f12"`, returning the lowercase names and values
+pub fn parse_aliases_config(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut aliases = Vec::new();
+    let mut in_aliases = false;
+    for line in content.lines() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_aliases = line == "[aliases]";
+            continue;
+        }
+        if !in_aliases {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("invalid line in crokey.toml: {:?}", line))?;
+        let name = name.trim();
+        let name = unquote(name).unwrap_or_else(|| name.to_string());
+        let value = unquote(value.trim())
+            .ok_or_else(|| format!("alias values must be quoted in crokey.toml: {:?}", line))?;
+        aliases.push((name.to_lowercase(), value.to_lowercase()));
+    }
+    Ok(aliases)
+}
+
+/// Remove the comment ending the line, if any, a `#` in a string
+/// not starting a comment
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), _) if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            (None, _) => {}
+        }
+    }
+    line
+}
+
+/// Return the content of a basic (`"..."`) or literal (`'...'`) string
+fn unquote(s: &str) -> Option<String> {
+    if let Some(literal) = s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        return Some(literal.to_string());
+    }
+    let basic = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(basic.len());
+    let mut chars = basic.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.push(chars.next()?);
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Some(unescaped)
+}
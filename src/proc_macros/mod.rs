@@ -1,11 +1,19 @@
+mod aliases_config;
+
 use {
+    aliases_config::parse_aliases_config,
     crossterm::event::KeyCode,
     proc_macro::TokenStream as TokenStream1,
-    proc_macro2::{Group, Span, TokenStream},
+    proc_macro2::{Group, Span, TokenStream, TokenTree},
     quote::quote,
+    std::{
+        env,
+        fs,
+        path::PathBuf,
+    },
     strict::OneToThree,
     syn::{
-        parse::{Error, Parse, ParseStream, Result},
+        parse::{discouraged::Speculative, Error, Parse, ParseStream, Result},
         parse_macro_input, Ident, LitChar, LitInt, Token,
     },
 };
@@ -15,7 +23,10 @@ struct KeyCombinationKey {
     pub ctrl: bool,
     pub alt: bool,
     pub shift: bool,
+    pub super_: bool,
     pub codes: OneToThree<TokenStream>,
+    /// whether a project alias was used
+    pub aliased: bool,
 }
 
 
@@ -27,9 +38,29 @@ struct KeyCombinationKey {
 // [ ] sort it
 // [ ] then map it to a OneToThree<TokenStream> using the function KeyCode->TokenStream
 
+/// Parse a key code, the project aliases being checked before the
+/// default key names, like in crokey::parse_key_code_with.
+///
+/// `aliased` is set when a project alias is used.
+fn parse_key_code(
+    raw: &str,
+    shift: bool,
+    code_span: Span,
+    aliased: &mut bool,
+) -> Result<KeyCode> {
+    if raw.chars().count() > 1 {
+        let alias = project_alias(raw).map_err(|e| Error::new(code_span, e))?;
+        if let Some(value) = alias {
+            *aliased = true;
+            return parse_default_key_code(&value, shift, code_span);
+        }
+    }
+    parse_default_key_code(raw, shift, code_span)
+}
+
 // must be kept identical to crokey::parse_key_code
 // (and yes, this duplication isn't ideal)
-fn parse_key_code(
+fn parse_default_key_code(
     raw: &str,
     shift: bool,
     code_span: Span,
@@ -38,6 +69,10 @@ fn parse_key_code(
     let code = match raw {
         "esc" => Esc,
         "enter" => Enter,
+        "return" => Enter,
+        "cr" => Enter,
+        "lf" => Enter,
+        "\r" | "\n" => Enter, // as the key events some terminals send
         "left" => Left,
         "right" => Right,
         "up" => Up,
@@ -45,13 +80,22 @@ fn parse_key_code(
         "home" => Home,
         "end" => End,
         "pageup" => PageUp,
+        "pgup" => PageUp,
         "pagedown" => PageDown,
+        "pgdn" => PageDown,
         "backtab" => BackTab,
+        "unknown" => Null,
         "backspace" => Backspace,
         "del" => Delete,
         "delete" => Delete,
         "insert" => Insert,
         "ins" => Insert,
+        "capslock" => CapsLock,
+        "scrolllock" => ScrollLock,
+        "numlock" => NumLock,
+        "printscreen" => PrintScreen,
+        "pause" => Pause,
+        "menu" => Menu,
         "f1" => F(1),
         "f2" => F(2),
         "f3" => F(3),
@@ -64,6 +108,23 @@ fn parse_key_code(
         "f10" => F(10),
         "f11" => F(11),
         "f12" => F(12),
+        "kp0" => Char('\u{e037}'), // the numpad keys, see KEYPAD_KEYS
+        "kp1" => Char('\u{e038}'),
+        "kp2" => Char('\u{e039}'),
+        "kp3" => Char('\u{e03a}'),
+        "kp4" => Char('\u{e03b}'),
+        "kp5" => Char('\u{e03c}'),
+        "kp6" => Char('\u{e03d}'),
+        "kp7" => Char('\u{e03e}'),
+        "kp8" => Char('\u{e03f}'),
+        "kp9" => Char('\u{e040}'),
+        "kpdecimal" => Char('\u{e041}'),
+        "kpdivide" => Char('\u{e042}'),
+        "kpmultiply" => Char('\u{e043}'),
+        "kpminus" => Char('\u{e044}'),
+        "kpplus" => Char('\u{e045}'),
+        "kpenter" => Char('\u{e046}'),
+        "kpequal" => Char('\u{e047}'),
         "space" => Char(' '),
         "hyphen" => Char('-'),
         "minus" => Char('-'),
@@ -71,7 +132,23 @@ fn parse_key_code(
         c if c.chars().count() == 1 => {
             let mut c = c.chars().next().unwrap();
             if shift {
-                c = c.to_ascii_uppercase();
+                let mut upper = c.to_uppercase();
+                if let (Some(u), None) = (upper.next(), upper.next()) {
+                    c = u;
+                }
+            }
+            Char(c)
+        }
+        hex if hex.starts_with("0x") => {
+            let mut c = u32::from_str_radix(&hex[2..], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| Error::new(code_span, format_args!("invalid char code {:?}", raw)))?;
+            if shift {
+                let mut upper = c.to_uppercase();
+                if let (Some(u), None) = (upper.next(), upper.next()) {
+                    c = u;
+                }
             }
             Char(c)
         }
@@ -85,6 +162,52 @@ fn parse_key_code(
     Ok(code)
 }
 
+/// Return the path of the file defining the project's aliases: the
+/// `CROKEY_CONFIG` env var, or `crokey.toml` in the crate's directory
+fn aliases_config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("CROKEY_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let dir = env::var_os("CARGO_MANIFEST_DIR")?;
+    Some(PathBuf::from(dir).join("crokey.toml"))
+}
+
+thread_local! {
+    static PROJECT_ALIASES: std::result::Result<Vec<(String, String)>, String> = aliases_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse_aliases_config(&content))
+        .unwrap_or_else(|| Ok(Vec::new()));
+}
+
+/// Return the key code name a project alias stands for, or the
+/// error found in the aliases file
+fn project_alias(name: &str) -> std::result::Result<Option<String>, String> {
+    PROJECT_ALIASES.with(|aliases| {
+        let aliases = aliases.as_ref().map_err(|e| e.clone())?;
+        Ok(aliases
+            .iter()
+            .find(|(alias, _)| alias == name)
+            .map(|(_, value)| value.clone()))
+    })
+}
+
+/// Produce the items making cargo rebuild the crate when the aliases file
+/// or the `CROKEY_CONFIG` env var change.
+///
+/// Those items need an expression position, so they're only emitted when
+/// an alias is used, the other keys staying usable in patterns.
+fn aliases_tracking_tokens() -> TokenStream {
+    let include = aliases_config_path()
+        .filter(|path| path.is_file())
+        .map(|path| fs::canonicalize(&path).unwrap_or(path))
+        .and_then(|path| path.to_str().map(str::to_owned))
+        .map(|path| quote! { const _: &[u8] = include_bytes!(#path); });
+    quote! {
+        const _: Option<&str> = option_env!("CROKEY_CONFIG");
+        #include
+    }
+}
+
 
 fn key_code_to_token_stream(key_code: KeyCode, code_span: Span) -> Result<TokenStream> {
     let ts = match key_code {
@@ -125,100 +248,189 @@ fn key_code_to_token_stream(key_code: KeyCode, code_span: Span) -> Result<TokenS
     Ok(ts)
 }
 
+/// Return the key code written as an integer: either a digit, or
+/// a char code written in hexadecimal (e.g. `0xe9`)
+fn int_key_code(int: &LitInt) -> Result<String> {
+    let raw = int.to_string().to_lowercase();
+    if raw.starts_with("0x") {
+        return Ok(raw);
+    }
+    let digits = int.base10_digits();
+    if digits.len() > 1 {
+        return Err(Error::new(int.span(), "invalid key; must be between 0-9 or a 0x prefixed char code"));
+    }
+    Ok(digits.to_owned())
+}
+
+/// Parse a key code which isn't the first one
+fn parse_next_code(input: ParseStream<'_>) -> Result<(String, Span)> {
+    if input.peek(LitInt) {
+        let int = input.parse::<LitInt>()?;
+        return Ok((int_key_code(&int)?, int.span()));
+    }
+    let ident = input.parse::<Ident>()?;
+    Ok((ident.to_string().to_lowercase(), ident.span()))
+}
+
 impl Parse for KeyCombinationKey {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let crate_path = input.parse::<Group>()?.stream();
+        parse_key_combination(input, crate_path)
+    }
+}
 
-        let mut ctrl = false;
-        let mut alt = false;
-        let mut shift = false;
-
-        let (code, code_span) = loop {
-            let lookahead = input.lookahead1();
-
-            if lookahead.peek(LitChar) {
-                let lit = input.parse::<LitChar>()?;
-                break (lit.value().to_lowercase().collect(), lit.span());
-            }
+/// Parse a key combination, stopping at the first token which can't extend it
+fn parse_key_combination(input: ParseStream<'_>, crate_path: TokenStream) -> Result<KeyCombinationKey> {
+    let mut ctrl = false;
+    let mut alt = false;
+    let mut shift = false;
+    let mut super_ = false;
 
-            if lookahead.peek(LitInt) {
-                let int = input.parse::<LitInt>()?;
-                let digits = int.base10_digits();
-                if digits.len() > 1 {
-                    return Err(Error::new(int.span(), "invalid key; must be between 0-9"));
-                }
-                break (digits.to_owned(), int.span());
-            }
+    let (code, code_span) = loop {
+        let lookahead = input.lookahead1();
 
-            if !lookahead.peek(Ident) {
-                return Err(lookahead.error());
-            }
+        if lookahead.peek(LitChar) {
+            let lit = input.parse::<LitChar>()?;
+            break (lit.value().to_lowercase().collect(), lit.span());
+        }
 
-            let ident = input.parse::<Ident>()?;
-            let ident_value = ident.to_string().to_lowercase();
-            let modifier = match &*ident_value {
-                "ctrl" => &mut ctrl,
-                "alt" => &mut alt,
-                "shift" => &mut shift,
-                _ => break (ident_value, ident.span()),
-            };
-            if *modifier {
-                return Err(Error::new(
-                    ident.span(),
-                    format_args!("duplicate modifier {}", ident_value),
-                ));
-            }
-            *modifier = true;
+        if lookahead.peek(LitInt) {
+            let int = input.parse::<LitInt>()?;
+            break (int_key_code(&int)?, int.span());
+        }
 
-            input.parse::<Token![-]>()?;
+        // `super` is a keyword, not an identifier
+        let ident = if input.peek(Token![super]) {
+            let keyword = input.parse::<Token![super]>()?;
+            Ident::new("super", keyword.span)
+        } else if lookahead.peek(Ident) {
+            input.parse::<Ident>()?
+        } else {
+            return Err(lookahead.error());
+        };
+        let ident_value = ident.to_string().to_lowercase();
+        // the synonyms must be kept identical to crokey::MODIFIER_SYNONYMS
+        let modifier = match &*ident_value {
+            "ctrl" | "control" => &mut ctrl,
+            "alt" | "option" => &mut alt,
+            "shift" => &mut shift,
+            "super" | "command" | "windows" => &mut super_,
+            _ => break (ident_value, ident.span()),
         };
+        if *modifier {
+            return Err(Error::new(
+                ident.span(),
+                format_args!("duplicate modifier {}", ident_value),
+            ));
+        }
+        *modifier = true;
 
-        // parse the key codes
-        let first_code = parse_key_code(&code, shift, code_span)?;
-        let codes = if input.parse::<Token![-]>().is_ok() {
-            let ident = input.parse::<Ident>()?;
-            let second_code = parse_key_code(&ident.to_string().to_lowercase(), shift, ident.span())?;
-            if input.parse::<Token![-]>().is_ok() {
-                let ident = input.parse::<Ident>()?;
-                let third_code = parse_key_code(&ident.to_string().to_lowercase(), shift, ident.span())?;
-                OneToThree::Three(first_code, second_code, third_code)
-            } else {
-                OneToThree::Two(first_code, second_code)
-            }
+        input.parse::<Token![-]>()?;
+    };
+
+    // parse the key codes
+    let mut aliased = false;
+    let first_code = parse_key_code(&code, shift, code_span, &mut aliased)?;
+    let codes = if input.parse::<Token![-]>().is_ok() {
+        let (code, span) = parse_next_code(input)?;
+        let second_code = parse_key_code(&code, shift, span, &mut aliased)?;
+        if input.parse::<Token![-]>().is_ok() {
+            let (code, span) = parse_next_code(input)?;
+            let third_code = parse_key_code(&code, shift, span, &mut aliased)?;
+            OneToThree::Three(first_code, second_code, third_code)
         } else {
-            OneToThree::One(first_code)
-        };
+            OneToThree::Two(first_code, second_code)
+        }
+    } else {
+        OneToThree::One(first_code)
+    };
 
-        // sort according to key codes because comparing with pattern matching
-        // received key combinations with parsed ones requires code ordering to
-        // be consistent
-        let codes = codes.sorted();
-
-        // Produce the token stream which will build pattern matching comparable initializers
-        let codes = codes.try_map(|key_code| key_code_to_token_stream(key_code, input.span()))?;
-
-        Ok(KeyCombinationKey {
-            crate_path,
-            ctrl,
-            alt,
-            shift,
-            codes,
-        })
+    // Crossterm always sends SHIFT with backtab
+    if codes.iter().any(|code| *code == KeyCode::BackTab) {
+        shift = true;
     }
-}
 
-// Not public API. This is internal and to be used only by `key!`.
-#[doc(hidden)]
-#[proc_macro]
-pub fn key(input: TokenStream1) -> TokenStream1 {
-    let KeyCombinationKey {
+    // sort according to key codes because comparing with pattern matching
+    // received key combinations with parsed ones requires code ordering to
+    // be consistent
+    let codes = codes.sorted();
+
+    // Produce the token stream which will build pattern matching comparable initializers
+    let codes = codes.try_map(|key_code| key_code_to_token_stream(key_code, input.span()))?;
+
+    Ok(KeyCombinationKey {
         crate_path,
         ctrl,
         alt,
         shift,
+        super_,
         codes,
-    } = parse_macro_input!(input);
+        aliased,
+    })
+}
+
+struct KeySequenceKeys {
+    pub crate_path: TokenStream,
+    /// the struct literals of the key combinations
+    pub keys: Vec<TokenStream>,
+}
+
+impl Parse for KeySequenceKeys {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let crate_path = input.parse::<Group>()?.stream();
+        let mut keys = Vec::new();
+        while !input.is_empty() {
+            let (key, count) = parse_repeated_key_combination(input, crate_path.clone())?;
+            let key = key_combination_tokens(key);
+            keys.resize(keys.len() + count, key);
+        }
+        if keys.is_empty() {
+            return Err(Error::new(Span::call_site(), "empty key sequence"));
+        }
+        Ok(KeySequenceKeys { crate_path, keys })
+    }
+}
 
+/// Parse a key combination, optionally repeated with a count given as
+/// prefix (`3-ctrl-d`) or suffix (`ctrl-d*3`), like crokey::KeySequence
+/// does: a prefix is only a count when the tokens can't be read as a
+/// combination, so that `1-2` stays the combination of `1` and `2`
+fn parse_repeated_key_combination(
+    input: ParseStream<'_>,
+    crate_path: TokenStream,
+) -> Result<(KeyCombinationKey, usize)> {
+    let fork = input.fork();
+    let mut count = None;
+    let key = match parse_key_combination(&fork, crate_path.clone()) {
+        Ok(key) => {
+            input.advance_to(&fork);
+            key
+        }
+        Err(e) if input.peek(LitInt) && input.peek2(Token![-]) => {
+            count = Some(parse_count(input).map_err(|_| e)?);
+            input.parse::<Token![-]>()?;
+            parse_key_combination(input, crate_path)?
+        }
+        Err(e) => return Err(e),
+    };
+    if count.is_none() && input.peek(Token![*]) && input.peek2(LitInt) {
+        input.parse::<Token![*]>()?;
+        count = Some(parse_count(input)?);
+    }
+    Ok((key, count.unwrap_or(1)))
+}
+
+/// Parse the number of times a key combination is repeated
+fn parse_count(input: ParseStream<'_>) -> Result<usize> {
+    let int = input.parse::<LitInt>()?;
+    match int.base10_parse::<u8>() {
+        Ok(count) if count > 0 => Ok(count as usize),
+        _ => Err(Error::new(int.span(), "invalid count; must be between 1 and 255")),
+    }
+}
+
+/// Return the name of the constant of `crokey::__private` holding the modifiers
+fn modifier_constant(ctrl: bool, alt: bool, shift: bool, super_: bool) -> Ident {
     let mut modifier_constant = "MODS".to_owned();
     if ctrl {
         modifier_constant.push_str("_CTRL");
@@ -229,9 +441,27 @@ pub fn key(input: TokenStream1) -> TokenStream1 {
     if shift {
         modifier_constant.push_str("_SHIFT");
     }
-    let modifier_constant = Ident::new(&modifier_constant, Span::call_site());
+    if super_ {
+        modifier_constant.push_str("_SUPER");
+    }
+    Ident::new(&modifier_constant, Span::call_site())
+}
+
+/// Produce the struct literal of the key combination, in a block
+/// tracking the aliases file when an alias is used
+fn key_combination_tokens(key: KeyCombinationKey) -> TokenStream {
+    let KeyCombinationKey {
+        crate_path,
+        ctrl,
+        alt,
+        shift,
+        super_,
+        codes,
+        aliased,
+    } = key;
+    let modifier_constant = modifier_constant(ctrl, alt, shift, super_);
 
-    match codes {
+    let literal = match codes {
         OneToThree::One(code) => {
             quote! {
                 #crate_path::KeyCombination {
@@ -265,6 +495,143 @@ pub fn key(input: TokenStream1) -> TokenStream1 {
                 }
             }
         }
+    };
+    if aliased {
+        let tracking = aliases_tracking_tokens();
+        quote! {{ #tracking #literal }}
+    } else {
+        literal
+    }
+}
+
+// Not public API. This is internal and to be used only by `key!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn key(input: TokenStream1) -> TokenStream1 {
+    let key = parse_macro_input!(input as KeyCombinationKey);
+    key_combination_tokens(key).into()
+}
+
+struct KeyEventKey {
+    pub kind: Ident,
+    pub key: KeyCombinationKey,
+}
+
+impl Parse for KeyEventKey {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let crate_path = input.parse::<Group>()?.stream();
+        // the kind, when given, is separated from the key by a space
+        let mut kind = Ident::new("Press", Span::call_site());
+        if input.peek(Ident) && (input.peek2(Ident) || input.peek2(LitChar) || input.peek2(LitInt)) {
+            let ident = input.parse::<Ident>()?;
+            let name = match ident.to_string().to_lowercase().as_str() {
+                "press" => "Press",
+                "release" => "Release",
+                "repeat" => "Repeat",
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "expected press, release or repeat",
+                    ));
+                }
+            };
+            kind = Ident::new(name, ident.span());
+        }
+        let key = parse_key_combination(input, crate_path)?;
+        Ok(KeyEventKey { kind, key })
+    }
+}
+
+// Not public API. This is internal and to be used only by `key_event!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn key_event(input: TokenStream1) -> TokenStream1 {
+    let KeyEventKey { kind, key } = parse_macro_input!(input);
+    let KeyCombinationKey {
+        crate_path,
+        ctrl,
+        alt,
+        shift,
+        super_,
+        codes,
+        aliased,
+    } = key;
+    let code = match codes {
+        OneToThree::One(code) => code,
+        _ => {
+            return Error::new(Span::call_site(), "a key event has only one key code")
+                .to_compile_error()
+                .into();
+        }
+    };
+    let modifier_constant = modifier_constant(ctrl, alt, shift, super_);
+    let literal = quote! {
+        #crate_path::__private::crossterm::event::KeyEvent {
+            code: #crate_path::__private::crossterm::event::KeyCode::#code,
+            modifiers: #crate_path::__private::#modifier_constant,
+            kind: #crate_path::__private::crossterm::event::KeyEventKind::#kind,
+            state: #crate_path::__private::crossterm::event::KeyEventState::NONE,
+        }
+    };
+    if aliased {
+        let tracking = aliases_tracking_tokens();
+        quote! {{ #tracking #literal }}.into()
+    } else {
+        literal.into()
+    }
+}
+
+// Not public API. This is internal and to be used only by `keyseq!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn keyseq(input: TokenStream1) -> TokenStream1 {
+    let KeySequenceKeys { crate_path, keys } = parse_macro_input!(input);
+    quote! {
+        #crate_path::KeySequence {
+            keys: #crate_path::__private::vec![#(#keys),*],
+        }
     }
     .into()
 }
+
+/// Return the char a single token stands for
+fn token_char(token: TokenTree) -> Result<char> {
+    let span = token.span();
+    let c = match token {
+        TokenTree::Punct(punct) => Some(punct.as_char()),
+        TokenTree::Ident(ident) => {
+            let name = ident.to_string();
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        }
+        TokenTree::Literal(literal) => {
+            let literal = syn::Lit::new(literal);
+            match literal {
+                syn::Lit::Char(lit) => Some(lit.value()),
+                syn::Lit::Int(int) if int.base10_digits().len() == 1 && int.suffix().is_empty() => {
+                    int.base10_digits().chars().next()
+                }
+                _ => None,
+            }
+        }
+        TokenTree::Group(_) => None,
+    };
+    c.ok_or_else(|| Error::new(span, "expected a single char token"))
+}
+
+#[proc_macro]
+pub fn to_char(input: TokenStream1) -> TokenStream1 {
+    let mut tokens = TokenStream::from(input).into_iter();
+    let c = match (tokens.next(), tokens.next()) {
+        (Some(token), None) => token_char(token),
+        (None, _) => Err(Error::new(Span::call_site(), "expected a single char token")),
+        (Some(_), Some(extra)) => Err(Error::new(extra.span(), "unexpected token")),
+    };
+    match c {
+        Ok(c) => quote! { #c }.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,199 @@
+//! Helpers running a program in a pseudo terminal, to write end-to-end
+//! tests of key handling without a real terminal.
+
+use {
+    crate::*,
+    std::{
+        fs::File,
+        io::{
+            self,
+            Read,
+            Write,
+        },
+        os::unix::{
+            io::{
+                AsRawFd,
+                FromRawFd,
+            },
+            process::CommandExt,
+        },
+        process::{
+            Child,
+            Command,
+            Stdio,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// A program running in a pseudo terminal, to which keys are sent as a
+/// terminal would send them, and whose output is read back.
+///
+/// The program is given the slave side of the PTY as stdin, stdout, stderr
+/// and controlling terminal, so that crossterm reads its events there.
+/// It's killed when the session is dropped.
+///
+/// ```no_run
+/// use {
+///     crokey::*,
+///     std::{process::Command, time::Duration},
+/// };
+/// # fn run() -> std::io::Result<()> {
+/// let mut session = PtySession::spawn(&mut Command::new("target/debug/print_key"))?;
+/// session.expect("Type any key combination", Duration::from_secs(5))?;
+/// session.write_key(key!(ctrl-a), KeyEncoding::Legacy)?;
+/// session.expect("Ctrl-a", Duration::from_secs(1))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PtySession {
+    master: File,
+    child: Child,
+    /// what the program wrote, lossily decoded
+    output: String,
+    /// the length of the output already matched by [PtySession::expect]
+    consumed: usize,
+}
+
+impl PtySession {
+    /// Start the command in a new 80x24 pseudo terminal
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        let mut master = -1;
+        let mut slave = -1;
+        let mut size = libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let res = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::addr_of_mut!(size), // *const on Linux, *mut on BSDs
+            )
+        };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { File::from_raw_fd(slave) };
+        unsafe {
+            command.pre_exec(|| {
+                // make the PTY the controlling terminal, so that /dev/tty is it
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave))
+            .spawn()?;
+        Ok(Self {
+            master,
+            child,
+            output: String::new(),
+            consumed: 0,
+        })
+    }
+    /// Send raw bytes, as if the terminal sent them
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.master.write_all(bytes)?;
+        self.master.flush()
+    }
+    /// Send the key combination as a terminal with the given encoding would
+    /// send it.
+    ///
+    /// Fail with `InvalidInput` when the encoding can't express the key.
+    pub fn write_key(&mut self, key: KeyCombination, encoding: KeyEncoding) -> io::Result<()> {
+        let bytes = encode_key(key, encoding).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} can't be encoded with {:?}", key, encoding),
+            )
+        })?;
+        self.write_bytes(&bytes)
+    }
+    /// Return everything the program wrote so far
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+    /// Wait until the program writes the given text, after what a previous
+    /// call already matched, and return the output up to the end of the text.
+    ///
+    /// Fail with `TimedOut` when the text isn't written in time, and with
+    /// `UnexpectedEof` when the program closes the terminal before.
+    pub fn expect(&mut self, text: &str, timeout: Duration) -> io::Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(idx) = self.output[self.consumed..].find(text) {
+                let end = self.consumed + idx + text.len();
+                let matched = self.output[self.consumed..end].to_string();
+                self.consumed = end;
+                return Ok(matched);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{:?} not found in {:?}", text, &self.output[self.consumed..]),
+                ));
+            }
+            if !self.read_available(remaining)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("terminal closed before {:?} was written", text),
+                ));
+            }
+        }
+    }
+    /// Wait at most `timeout` for output, returning false if the terminal
+    /// is closed
+    fn read_available(&mut self, timeout: Duration) -> io::Result<bool> {
+        let mut fds = libc::pollfd {
+            fd: self.master.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let n = unsafe { libc::poll(&mut fds, 1, millis) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+        let mut buf = [0; 1024];
+        match self.master.read(&mut buf) {
+            Ok(0) => Ok(false),
+            Ok(n) => {
+                self.output.push_str(&String::from_utf8_lossy(&buf[..n]));
+                Ok(true)
+            }
+            // Linux reports the closing of the slave side as EIO
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+    /// Kill the program and wait for its end
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()?;
+        self.child.wait()?;
+        Ok(())
+    }
+}
+
+impl Drop for PtySession {
+    fn drop(&mut self) {
+        let _ = self.kill();
+    }
+}
@@ -0,0 +1,167 @@
+use {
+    crate::*,
+    std::{
+        collections::HashMap,
+        fmt,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+/// Error returned when a remap can't be added
+#[derive(Debug)]
+pub enum RemapError {
+    /// One of the keys isn't valid
+    Parse(ParseKeyError),
+    /// The remaps would rewrite a key into itself, through the given chain
+    Cycle(Vec<KeyCombination>),
+}
+
+impl From<ParseKeyError> for RemapError {
+    fn from(e: ParseKeyError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl fmt::Display for RemapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Cycle(keys) => {
+                write!(f, "key remaps loop:")?;
+                for (i, key) in keys.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { " -> " } else { " " }, key)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RemapError {}
+
+/// A stage rewriting key combinations before they're looked for in
+/// the bindings, so that users may declare remaps like `ctrl-h = backspace`.
+///
+/// Remaps are chained: with `ctrl-h = backspace` and `backspace = delete`,
+/// `ctrl-h` becomes `delete`. Remaps making a loop are rejected.
+///
+/// ```
+/// use crokey::*;
+/// let mut remapper = Remapper::new();
+/// remapper.insert("ctrl-h", "backspace").unwrap();
+/// remapper.insert("backspace", "delete").unwrap();
+/// assert!(remapper.insert("delete", "ctrl-h").is_err());
+/// assert_eq!(remapper.resolve(key!(ctrl-h)), key!(delete));
+/// assert_eq!(remapper.resolve(key!(ctrl-g)), key!(ctrl-g));
+///
+/// # #[cfg(feature = "terminal")] {
+/// let remapper: Remapper = deser_hjson::from_str(r#"{ "ctrl-[": "esc" }"#).unwrap();
+/// let mut pipeline = Pipeline::default().with_stage(remapper);
+/// assert_eq!(pipeline.process(key!(ctrl-'[')), Some(key!(esc)));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Remapper {
+    remaps: HashMap<KeyCombination, KeyCombination>,
+}
+
+impl Remapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Remap a key to another one, replacing any previous remap of the key.
+    ///
+    /// Fail without change if the remap would make a loop.
+    pub fn insert<F, T>(&mut self, from: F, to: T) -> Result<(), RemapError>
+    where
+        F: TryInto<KeyCombination>,
+        T: TryInto<KeyCombination>,
+        ParseKeyError: From<F::Error> + From<T::Error>,
+    {
+        let from = from.try_into().map_err(ParseKeyError::from)?.normalized();
+        let to = to.try_into().map_err(ParseKeyError::from)?.normalized();
+        let mut chain = vec![from, to];
+        let mut current = to;
+        while current != from {
+            match self.remaps.get(&current) {
+                Some(&next) => {
+                    chain.push(next);
+                    current = next;
+                }
+                None => {
+                    self.remaps.insert(from, to);
+                    return Ok(());
+                }
+            }
+        }
+        Err(RemapError::Cycle(chain))
+    }
+    /// Remove the remap of a key, returning its direct target
+    pub fn remove(&mut self, from: KeyCombination) -> Option<KeyCombination> {
+        self.remaps.remove(&from.normalized())
+    }
+    /// Return the key a combination is remapped to, following chained remaps,
+    /// or the combination itself when it's not remapped
+    pub fn resolve(&self, key: KeyCombination) -> KeyCombination {
+        let mut key = key.normalized();
+        while let Some(&next) = self.remaps.get(&key) {
+            key = next;
+        }
+        key
+    }
+    pub fn len(&self) -> usize {
+        self.remaps.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.remaps.is_empty()
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl KeyStage for Remapper {
+    fn process(&mut self, key: KeyCombination) -> Option<KeyCombination> {
+        Some(self.resolve(key))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Remapper {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let remaps = HashMap::<KeyCombination, KeyCombination>::deserialize(deserializer)?;
+        let mut remapper = Self::new();
+        for (from, to) in remaps {
+            remapper.insert(from, to).map_err(de::Error::custom)?;
+        }
+        Ok(remapper)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Remapper {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.remaps.serialize(serializer)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+#[test]
+fn check_remap_cycle_is_refused_on_deserialization() {
+    let res: Result<Remapper, _> = deser_hjson::from_str("{\na: b\nb: c\nc: a\n}");
+    assert!(res.unwrap_err().to_string().contains("loop"));
+    let remapper: Remapper = deser_hjson::from_str("{\na: b\nb: c\n}").unwrap();
+    assert_eq!(remapper.resolve(key!(a)), key!(c));
+}
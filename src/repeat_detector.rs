@@ -0,0 +1,129 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        KeyModifiers,
+    },
+    std::{
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// Guess which key presses are auto-repeats, for terminals which don't
+/// report them (i.e. without the kitty protocol).
+///
+/// When a key is held, ANSI terminals send the same press event again and
+/// again. The detector marks as [KeyEventKind::Repeat] a press identical to
+/// the previous one and received less than a threshold after it, so that
+/// downstream code sees the same `Press/Repeat` kinds as in kitty mode.
+///
+/// As the first repetition comes after the initial delay of the keyboard,
+/// which is much longer than the repeat interval, it's usually seen as a
+/// new press: the threshold should stay short enough not to take a quick
+/// double tap for a repetition.
+///
+/// Events which aren't presses are left unchanged.
+///
+/// ```
+/// use {
+///     crokey::*,
+///     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+///     std::time::{Duration, Instant},
+/// };
+/// let mut detector = RepeatDetector::default();
+/// let event = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+/// let start = Instant::now();
+/// let kind = |e: KeyEvent| e.kind;
+/// assert_eq!(kind(detector.transform_at(event, start)), KeyEventKind::Press);
+/// let later = start + Duration::from_millis(30);
+/// assert_eq!(kind(detector.transform_at(event, later)), KeyEventKind::Repeat);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RepeatDetector {
+    threshold: Duration,
+    last_press: Option<(KeyCode, KeyModifiers, Instant)>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for RepeatDetector {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(80))
+    }
+}
+
+impl RepeatDetector {
+    /// Create a detector considering as repetitions the identical presses
+    /// received less than `threshold` after the previous one.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            last_press: None,
+            clock: system_clock(),
+        }
+    }
+    /// Use the given clock in [RepeatDetector::transform]
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+    /// Forget the last press, so that the next one isn't a repetition
+    pub fn reset(&mut self) {
+        self.last_press = None;
+    }
+    /// Return the event, with the `Repeat` kind if it looks like
+    /// a repetition of the previous press
+    pub fn transform(&mut self, key: KeyEvent) -> KeyEvent {
+        let now = self.clock.now();
+        self.transform_at(key, now)
+    }
+    /// Same as [RepeatDetector::transform], with the time the event
+    /// was received
+    pub fn transform_at(&mut self, mut key: KeyEvent, now: Instant) -> KeyEvent {
+        if key.kind != KeyEventKind::Press {
+            if key.kind == KeyEventKind::Release {
+                self.last_press = None;
+            }
+            return key;
+        }
+        let is_repeat = matches!(
+            self.last_press,
+            Some((code, modifiers, instant))
+                if code == key.code
+                && modifiers == key.modifiers
+                && now.saturating_duration_since(instant) < self.threshold
+        );
+        if is_repeat {
+            debug_event!(?key.code, "key press detected as a repetition");
+            key.kind = KeyEventKind::Repeat;
+        }
+        self.last_press = Some((key.code, key.modifiers, now));
+        key
+    }
+}
+
+#[test]
+fn check_repeat_detection() {
+    let mut detector = RepeatDetector::new(Duration::from_millis(50));
+    let a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+    let b = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE);
+    let t0 = Instant::now();
+    let ms = |n| t0 + Duration::from_millis(n);
+    let mut kind = |key, t| detector.transform_at(key, t).kind;
+    assert_eq!(kind(a, t0), KeyEventKind::Press);
+    // the initial delay of the keyboard
+    assert_eq!(kind(a, ms(500)), KeyEventKind::Press);
+    assert_eq!(kind(a, ms(530)), KeyEventKind::Repeat);
+    assert_eq!(kind(a, ms(560)), KeyEventKind::Repeat);
+    assert_eq!(kind(b, ms(570)), KeyEventKind::Press);
+    assert_eq!(kind(a, ms(580)), KeyEventKind::Press);
+    assert_eq!(kind(a, ms(700)), KeyEventKind::Press);
+}
@@ -0,0 +1,145 @@
+use {
+    crate::*,
+    std::collections::HashMap,
+};
+
+/// A set of key combinations which applications shouldn't bind,
+/// usually because the terminal or the OS intercepts them, with
+/// the reason for each one.
+///
+/// ```
+/// use crokey::*;
+/// let mut bindings = KeyBindingMap::new();
+/// bindings.insert("ctrl-s", "save").unwrap();
+/// bindings.insert("ctrl-o", "open").unwrap();
+/// let mut reserved = ReservedKeys::new();
+/// reserved.insert(key!(ctrl-s), "flow control (XOFF)");
+/// let warnings = bindings.validate_against(&reserved);
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!(warnings[0].key, key!(ctrl-s));
+/// assert_eq!(warnings[0].to_string(), "Ctrl-s: flow control (XOFF)");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReservedKeys {
+    keys: HashMap<KeyCombination, String>,
+}
+
+impl ReservedKeys {
+    /// Return an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Return the keys commonly intercepted on the current platform
+    /// when the terminal isn't in raw mode, or by the terminal itself.
+    pub fn platform_default() -> Self {
+        let mut reserved = Self::new();
+        #[cfg(unix)]
+        {
+            reserved.insert(key!(ctrl-s), "flow control: XOFF stops the output");
+            reserved.insert(key!(ctrl-q), "flow control: XON resumes the output");
+            reserved.insert(key!(ctrl-z), "job control: suspends the application");
+            reserved.insert(key!(ctrl-'\\'), "sends SIGQUIT");
+        }
+        #[cfg(windows)]
+        {
+            reserved.insert(key!(ctrl-c), "interrupts the application");
+            reserved.insert(key!(ctrl-v), "paste in most terminals");
+        }
+        #[cfg(target_os = "macos")]
+        {
+            reserved.insert(key!(ctrl-y), "job control: delayed suspend");
+        }
+        reserved
+    }
+    /// Add a key to the set, with the reason it shouldn't be bound
+    pub fn insert<S: Into<String>>(&mut self, key: KeyCombination, reason: S) {
+        self.keys.insert(key.normalized(), reason.into());
+    }
+    pub fn remove(&mut self, key: KeyCombination) {
+        self.keys.remove(&key.normalized());
+    }
+    pub fn contains(&self, key: KeyCombination) -> bool {
+        self.keys.contains_key(&key.normalized())
+    }
+    /// Return why the key shouldn't be bound, if it's reserved
+    pub fn reason(&self, key: KeyCombination) -> Option<&str> {
+        self.keys.get(&key.normalized()).map(String::as_str)
+    }
+}
+
+/// A binding involving a reserved key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservedKeyWarning {
+    /// the keys of the binding
+    pub keys: BindingKeys,
+    /// the reserved key
+    pub key: KeyCombination,
+    /// why the key shouldn't be bound
+    pub reason: String,
+}
+
+impl std::fmt::Display for ReservedKeyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.keys, self.reason)
+    }
+}
+
+impl<A> KeyBindingMap<A> {
+    /// Return a warning for each binding using a reserved key, or
+    /// [pattern](KeyBindingMap::insert_pattern) matching one, sorted
+    /// by formatted keys
+    pub fn validate_against(&self, reserved: &ReservedKeys) -> Vec<ReservedKeyWarning> {
+        let mut warnings = Vec::new();
+        for (keys, _) in self.iter() {
+            for &key in keys.iter() {
+                if let Some(reason) = reserved.reason(key) {
+                    warnings.push(ReservedKeyWarning {
+                        keys: keys.clone().into(),
+                        key,
+                        reason: reason.to_string(),
+                    });
+                }
+            }
+        }
+        for (pattern, _) in self.patterns() {
+            for (&key, reason) in &reserved.keys {
+                if pattern.matches(key) {
+                    warnings.push(ReservedKeyWarning {
+                        keys: (*pattern).into(),
+                        key,
+                        reason: reason.clone(),
+                    });
+                }
+            }
+        }
+        warnings.sort_by_cached_key(|warning| warning.to_string());
+        warnings
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn check_platform_reserved_keys() {
+    let mut bindings = KeyBindingMap::new();
+    bindings.insert("ctrl-x ctrl-s", "save").unwrap();
+    bindings.insert("ctrl-x ctrl-z", "suspend").unwrap();
+    bindings.insert("ctrl-x ctrl-f", "find").unwrap();
+    let warnings = bindings.validate_against(&ReservedKeys::platform_default());
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].key, key!(ctrl-s));
+    assert_eq!(warnings[1].key, key!(ctrl-z));
+}
+
+#[test]
+fn check_reserved_keys_of_patterns() {
+    let mut bindings = KeyBindingMap::new();
+    bindings.insert_pattern("*-s", "save").unwrap();
+    bindings.insert_pattern("none-q", "quit").unwrap();
+    let mut reserved = ReservedKeys::new();
+    reserved.insert(key!(ctrl-s), "flow control (XOFF)");
+    reserved.insert(key!(ctrl-q), "flow control (XON)");
+    let warnings = bindings.validate_against(&reserved);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].key, key!(ctrl-s));
+    assert_eq!(warnings[0].to_string(), "*-s: flow control (XOFF)");
+}
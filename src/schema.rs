@@ -0,0 +1,195 @@
+//! JSON schemas of the key types, with the "schemars" feature, so that
+//! applications generating the schema of their configuration get the
+//! validation of keys.
+
+use {
+    crate::{
+        parse::{
+            KEY_NAMES,
+            MODIFIER_NAMES,
+            PLATFORM_MODIFIER_NAMES,
+        },
+        *,
+    },
+    schemars::{
+        json_schema,
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+    },
+};
+
+/// The names of the modifiers accepted by the parser, without the `-`
+fn modifier_names() -> Vec<&'static str> {
+    MODIFIER_NAMES
+        .iter()
+        .chain(MODIFIER_SYNONYMS)
+        .map(|(name, _)| *name)
+        .chain(PLATFORM_MODIFIER_NAMES)
+        .map(|name| name.trim_end_matches('-'))
+        .collect()
+}
+
+/// The names of keys accepted by the parser, besides single chars
+/// and code points
+fn key_names() -> Vec<&'static str> {
+    KEY_NAMES.iter().map(|(name, _)| *name).collect()
+}
+
+/// Make a regex matching the given lowercase word whatever its case,
+/// as JSON schema regexes have no case insensitive flag
+fn any_case(word: &str) -> String {
+    word.chars()
+        .map(|c| {
+            if c.is_ascii_lowercase() {
+                format!("[{}{}]", c, c.to_ascii_uppercase())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// The regex matching a key combination, without anchors
+fn key_combination_pattern() -> String {
+    let modifiers: Vec<String> = modifier_names().into_iter().map(any_case).collect();
+    let mut names = key_names();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len())); // longest first
+    let names: Vec<String> = names.into_iter().map(any_case).collect();
+    let key = format!("(?:{}|0[xX][0-9a-fA-F]+|.)", names.join("|"));
+    let modifiers = modifiers.join("|");
+    format!("(?:(?:{})-)*{}(?:-{}){{0,2}}", modifiers, key, key)
+}
+
+fn key_description() -> String {
+    format!(
+        "A key combination like \"ctrl-s\" or \"alt-shift-f5\": optional modifiers ({}) \
+        then one to three keys separated by dashes. A key is a single char, the code point \
+        of a char (e.g. 0x22), or one of {}.",
+        modifier_names().join(", "),
+        key_names().join(", "),
+    )
+}
+
+impl JsonSchema for KeyCombination {
+    fn schema_name() -> Cow<'static, str> {
+        "KeyCombination".into()
+    }
+    fn schema_id() -> Cow<'static, str> {
+        "crokey::KeyCombination".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": format!("^{}$", key_combination_pattern()),
+            "description": key_description(),
+        })
+    }
+}
+
+impl JsonSchema for KeySequence {
+    fn schema_name() -> Cow<'static, str> {
+        "KeySequence".into()
+    }
+    fn schema_id() -> Cow<'static, str> {
+        "crokey::KeySequence".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let key = key_combination_pattern();
+        json_schema!({
+            "type": "string",
+            "pattern": format!("^\\s*{}(?:\\s+{})*\\s*$", key, key),
+            "description": format!(
+                "One or several key combinations, separated with spaces, \
+                typed one after the other. {}",
+                key_description(),
+            ),
+        })
+    }
+}
+
+impl<A: JsonSchema> JsonSchema for KeyBindingMap<A> {
+    fn inline_schema() -> bool {
+        true
+    }
+    fn schema_name() -> Cow<'static, str> {
+        format!("KeyBindingMap_of_{}", A::schema_name()).into()
+    }
+    fn schema_id() -> Cow<'static, str> {
+        format!("crokey::KeyBindingMap<{}>", A::schema_id()).into()
+    }
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        HashMap::<KeySequence, A>::json_schema(generator)
+    }
+}
+
+impl<V: JsonSchema> JsonSchema for NormalizedKeyMap<V> {
+    fn inline_schema() -> bool {
+        true
+    }
+    fn schema_name() -> Cow<'static, str> {
+        format!("NormalizedKeyMap_of_{}", V::schema_name()).into()
+    }
+    fn schema_id() -> Cow<'static, str> {
+        format!("crokey::NormalizedKeyMap<{}>", V::schema_id()).into()
+    }
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        HashMap::<KeyCombination, V>::json_schema(generator)
+    }
+}
+
+impl JsonSchema for Remapper {
+    fn inline_schema() -> bool {
+        true
+    }
+    fn schema_name() -> Cow<'static, str> {
+        "Remapper".into()
+    }
+    fn schema_id() -> Cow<'static, str> {
+        "crokey::Remapper".into()
+    }
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        HashMap::<KeyCombination, KeyCombination>::json_schema(generator)
+    }
+}
+
+#[test]
+fn check_key_combination_pattern() {
+    use regex_lite::Regex;
+    let regex = Regex::new(&format!("^{}$", key_combination_pattern())).unwrap();
+    for raw in ["ctrl-s", "Ctrl-Alt-F5", "alt--", "a-b-c", "shift-Tab", "PageUp", "é"] {
+        assert!(regex.is_match(raw), "{raw} should match");
+        assert!(parse(raw).is_ok(), "{raw} should parse");
+    }
+    for raw in ["ctrl-", "ctrl-abc", "a-b-c-d", ""] {
+        assert!(!regex.is_match(raw), "{raw} shouldn't match");
+    }
+    for raw in [
+        "super-s", "command-c", "pgup", "return", "kp5", "primary-s", "ctrl-0x41", "unknown",
+    ] {
+        assert!(regex.is_match(raw), "{raw} should match");
+        assert!(parse(raw).is_ok(), "{raw} should parse");
+    }
+    // every name accepted by the parser is valid in the schema
+    for (name, _) in KEY_NAMES {
+        assert!(parse(name).is_ok(), "{name} should parse");
+        assert!(regex.is_match(name), "{name} should match");
+    }
+    for modifier in modifier_names() {
+        let raw = format!("{modifier}-Shift-f1");
+        assert!(parse(&raw).is_ok(), "{raw} should parse");
+        assert!(regex.is_match(&raw), "{raw} should match");
+    }
+}
+
+#[test]
+fn check_binding_map_schema() {
+    let schema = schemars::schema_for!(KeyBindingMap<String>);
+    let patterns = schema.get("patternProperties").and_then(|p| p.as_object()).unwrap();
+    assert_eq!(patterns.len(), 1);
+    assert_eq!(schema.get("additionalProperties"), Some(&false.into()));
+}
@@ -0,0 +1,185 @@
+use {
+    crate::*,
+    std::ops::Deref,
+};
+
+/// Temporary bindings, removed from the map when the guard is dropped.
+///
+/// Made by [KeyBindingMap::scope], for transient states like a modal
+/// "press y/n" prompt. Bindings shadowed by temporary ones are restored,
+/// with their metadata and enabled state, and so are the pattern bindings.
+///
+/// ```
+/// use crokey::*;
+/// let mut map = KeyBindingMap::new();
+/// map.insert(key!(y), "yank").unwrap();
+/// {
+///     let mut prompt = map.scope();
+///     prompt.bind(key!(y), "yes").unwrap();
+///     prompt.bind(key!(n), "no").unwrap();
+///     assert_eq!(prompt.get(key!(y)), Some(&"yes"));
+/// }
+/// assert_eq!(map.get(key!(y)), Some(&"yank"));
+/// assert_eq!(map.get(key!(n)), None);
+/// ```
+#[derive(Debug)]
+pub struct ScopedBindings<'m, A> {
+    map: &'m mut KeyBindingMap<A>,
+    /// the temporary keys, with the bindings they shadow, in binding order
+    shadowed: Vec<(BindingKeys, Option<ShadowedBinding<A>>)>,
+}
+
+/// A binding hidden by a temporary one, restored with its metadata
+/// and its enabled state
+#[derive(Debug)]
+struct ShadowedBinding<A> {
+    action: A,
+    meta: Option<BindingMeta>,
+    enabled: bool,
+}
+
+impl<A> KeyBindingMap<A> {
+    /// Return a guard through which bindings may be added until it's dropped
+    pub fn scope(&mut self) -> ScopedBindings<'_, A> {
+        ScopedBindings {
+            map: self,
+            shadowed: Vec::new(),
+        }
+    }
+}
+
+impl<'m, A> ScopedBindings<'m, A> {
+    /// Bind an action to a key until the guard is dropped
+    pub fn bind<K>(&mut self, keys: K, action: A) -> Result<&mut Self, ParseKeyError>
+    where
+        K: TryInto<KeySequence>,
+        ParseKeyError: From<K::Error>,
+    {
+        let keys = keys.try_into()?.canonical();
+        let meta = self.map.meta(&keys).cloned();
+        let enabled = self.map.is_enabled(&keys);
+        let previous = self.map.insert::<KeySequence>(keys.clone(), action)?;
+        let previous = previous.map(|action| ShadowedBinding { action, meta, enabled });
+        self.shadowed.push((keys.into(), previous));
+        Ok(self)
+    }
+    /// Bind an action to a [KeyPattern] until the guard is dropped
+    pub fn bind_pattern<P>(&mut self, pattern: P, action: A) -> Result<&mut Self, ParseKeyError>
+    where
+        P: TryInto<KeyPattern>,
+        ParseKeyError: From<P::Error>,
+    {
+        let pattern = pattern.try_into()?;
+        let previous = self.map.insert_pattern::<KeyPattern>(pattern, action)?;
+        let previous = previous.map(|action| ShadowedBinding {
+            action,
+            meta: None,
+            enabled: true,
+        });
+        self.shadowed.push((pattern.into(), previous));
+        Ok(self)
+    }
+}
+
+impl<'m, A> Deref for ScopedBindings<'m, A> {
+    type Target = KeyBindingMap<A>;
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<'m, A> Drop for ScopedBindings<'m, A> {
+    fn drop(&mut self) {
+        // in reverse order, so that a key bound twice in the scope
+        // gets back its action from before the scope
+        while let Some((keys, previous)) = self.shadowed.pop() {
+            let keys = match keys {
+                BindingKeys::Sequence(keys) => keys,
+                BindingKeys::Pattern(pattern) => {
+                    match previous {
+                        Some(ShadowedBinding { action, .. }) => {
+                            let _ = self.map.insert_pattern::<KeyPattern>(pattern, action);
+                        }
+                        None => {
+                            self.map.remove_pattern(pattern);
+                        }
+                    }
+                    continue;
+                }
+            };
+            match previous {
+                Some(ShadowedBinding { action, meta, enabled }) => {
+                    let _ = match meta {
+                        Some(meta) => self.map.insert_with_meta(keys.clone(), action, meta),
+                        None => self.map.insert(keys.clone(), action),
+                    };
+                    if !enabled {
+                        self.map.set_enabled(&keys, false);
+                    }
+                }
+                None => {
+                    self.map.remove(&keys);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn check_scoped_bindings_restore_the_map() {
+    let mut map = KeyBindingMap::new();
+    map.insert("ctrl-x ctrl-c", "quit").unwrap();
+    {
+        let mut scope = map.scope();
+        scope.bind("y", "yes").unwrap().bind("shift-y", "YES").unwrap();
+        scope.bind("ctrl-x ctrl-c", "cancel").unwrap();
+        assert!(scope.bind("ctrl-nothing", "nothing").is_err());
+        assert_eq!(scope.len(), 3);
+        assert_eq!(scope.get(key!(shift-y)), Some(&"YES"));
+    }
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get_sequence(&[key!(ctrl-x), key!(ctrl-c)]), Some(&"quit"));
+}
+
+#[test]
+fn check_scoped_bindings_restore_meta_and_state() {
+    let mut map = KeyBindingMap::new();
+    let meta = BindingMeta {
+        category: Some("File".to_string()),
+        ..Default::default()
+    };
+    map.insert_with_meta("ctrl-s", "save", meta.clone()).unwrap();
+    map.insert("ctrl-q", "quit").unwrap();
+    map.set_enabled(&[key!(ctrl-q)], false);
+    {
+        let mut scope = map.scope();
+        scope.bind("ctrl-s", "confirm").unwrap();
+        scope.bind("ctrl-q", "cancel").unwrap();
+        assert_eq!(scope.meta(&[key!(ctrl-s)]), None);
+        assert_eq!(scope.get(key!(ctrl-q)), Some(&"cancel"));
+    }
+    assert_eq!(map.get(key!(ctrl-s)), Some(&"save"));
+    assert_eq!(map.meta(&[key!(ctrl-s)]), Some(&meta));
+    assert_eq!(map.get(key!(ctrl-q)), None);
+    assert_eq!(map.get_sequence_ignoring_state(&[key!(ctrl-q)]), Some(&"quit"));
+}
+
+#[test]
+fn check_scoped_pattern_bindings() {
+    let mut map = KeyBindingMap::new();
+    map.insert_pattern("*-enter", "confirm").unwrap();
+    map.insert_pattern("none-x", "cut").unwrap();
+    {
+        let mut scope = map.scope();
+        scope.bind_pattern("*-enter", "submit").unwrap();
+        scope.bind_pattern("*-esc", "cancel").unwrap();
+        assert_eq!(scope.len(), 3);
+        assert_eq!(scope.get_pattern(key!(alt-enter)), Some(&"submit"));
+        assert_eq!(scope.get_pattern(key!(esc)), Some(&"cancel"));
+    }
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get_pattern(key!(alt-enter)), Some(&"confirm"));
+    assert_eq!(map.get_pattern(key!(esc)), None);
+    let patterns: Vec<String> = map.patterns().map(|(pattern, _)| pattern.to_string()).collect();
+    assert_eq!(patterns, ["*-Enter", "none-x"]);
+}
@@ -0,0 +1,441 @@
+use {
+    crate::*,
+    crossterm::event::KeyCode,
+    std::collections::{
+        HashMap,
+        VecDeque,
+    },
+};
+
+/// How to resolve a binding whose keys are also the start
+/// of a longer bound sequence, e.g. `ctrl-k` when `ctrl-k ctrl-c` is bound too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResolutionPolicy {
+    /// Fire the shorter binding as soon as it's typed, which makes the
+    /// longer sequences unreachable
+    Immediate,
+    /// Wait for the next key: fire the longer sequence if it's typed,
+    /// otherwise fire the shorter binding when a key not continuing the
+    /// sequence is typed, or when the application calls
+    /// [SequenceMatcher::flush] (usually on a timeout)
+    #[default]
+    PreferLongest,
+}
+
+/// What happened on a key given to a [SequenceMatcher]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceMatch<A> {
+    /// The keys typed so far are the start of a bound sequence,
+    /// more keys are expected
+    Pending,
+    /// A binding was fully typed
+    Matched(A),
+    /// A binding was fully typed after a count (e.g. `3` then `ctrl-d`),
+    /// only when [SequenceMatcher::set_count_prefix] is enabled
+    MatchedWithCount(A, usize),
+    /// The typed keys don't match any binding
+    Unmatched(KeySequence),
+}
+
+/// A key which may follow the pending keys of a [SequenceMatcher],
+/// as displayed by which-key like hints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Continuation<'m, A> {
+    /// The next key to type
+    pub key: KeyCombination,
+    /// The action bound to the pending keys followed by this key, if any
+    pub action: Option<&'m A>,
+    /// The metadata of this binding, if any
+    pub meta: Option<&'m BindingMeta>,
+    /// Whether longer sequences start with the pending keys and this key
+    pub is_prefix: bool,
+}
+
+/// Recognize bound key sequences in a stream of key combinations.
+///
+/// ```
+/// use crokey::*;
+/// let mut bindings = KeyBindingMap::new();
+/// bindings.insert("ctrl-k", "kill").unwrap();
+/// bindings.insert("ctrl-k ctrl-c", "comment").unwrap();
+/// let mut matcher = SequenceMatcher::new(bindings);
+/// assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Pending]);
+/// assert_eq!(matcher.feed(key!(ctrl-c)), vec![SequenceMatch::Matched("comment")]);
+/// assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Pending]);
+/// assert_eq!(matcher.flush(), Some(SequenceMatch::Matched("kill"))); // timeout
+/// ```
+#[derive(Debug, Clone)]
+pub struct SequenceMatcher<A> {
+    bindings: KeyBindingMap<A>,
+    policy: ResolutionPolicy,
+    policy_overrides: HashMap<KeySequence, ResolutionPolicy>,
+    pending: Vec<KeyCombination>,
+    count_prefix: bool,
+    count_keys: Vec<KeyCombination>,
+}
+
+impl<A: Clone> SequenceMatcher<A> {
+    pub fn new(bindings: KeyBindingMap<A>) -> Self {
+        Self {
+            bindings,
+            policy: ResolutionPolicy::default(),
+            policy_overrides: HashMap::new(),
+            pending: Vec::new(),
+            count_prefix: false,
+            count_keys: Vec::new(),
+        }
+    }
+    pub fn bindings(&self) -> &KeyBindingMap<A> {
+        &self.bindings
+    }
+    /// Give access to the bindings. Changing them discards the pending keys.
+    pub fn bindings_mut(&mut self) -> &mut KeyBindingMap<A> {
+        self.pending.clear();
+        &mut self.bindings
+    }
+    /// Set the resolution policy applied to bindings without specific policy
+    pub fn set_policy(&mut self, policy: ResolutionPolicy) {
+        self.policy = policy;
+    }
+    /// Set the resolution policy of a specific binding, overriding the
+    /// general one
+    pub fn set_binding_policy<K>(
+        &mut self,
+        keys: K,
+        policy: ResolutionPolicy,
+    ) -> Result<(), ParseKeyError>
+    where
+        K: TryInto<KeySequence>,
+        ParseKeyError: From<K::Error>,
+    {
+        self.policy_overrides.insert(keys.try_into()?.canonical(), policy);
+        Ok(())
+    }
+    fn policy_of(&self, keys: &[KeyCombination]) -> ResolutionPolicy {
+        self.policy_overrides.get(keys).copied().unwrap_or(self.policy)
+    }
+    /// Enable or disable count prefixes, like in editors where `3` then
+    /// `ctrl-d` does `ctrl-d` 3 times: digits typed before a binding make
+    /// a count given in [SequenceMatch::MatchedWithCount].
+    ///
+    /// Don't enable it if plain digits are bound, as they'd be taken as
+    /// counts. As in vi, `0` only starts a count when following other digits.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut bindings = KeyBindingMap::new();
+    /// bindings.insert("ctrl-d", "down").unwrap();
+    /// bindings.insert("0", "start").unwrap();
+    /// let mut matcher = SequenceMatcher::new(bindings);
+    /// matcher.set_count_prefix(true);
+    /// assert_eq!(matcher.feed(key!(1)), vec![SequenceMatch::Pending]);
+    /// assert_eq!(matcher.feed(key!(0)), vec![SequenceMatch::Pending]);
+    /// assert_eq!(matcher.count(), Some(10));
+    /// assert_eq!(matcher.feed(key!(ctrl-d)), vec![SequenceMatch::MatchedWithCount("down", 10)]);
+    /// assert_eq!(matcher.feed(key!(0)), vec![SequenceMatch::Matched("start")]);
+    /// ```
+    pub fn set_count_prefix(&mut self, count_prefix: bool) {
+        self.count_prefix = count_prefix;
+        self.count_keys.clear();
+    }
+    /// Return the count typed so far, if any
+    pub fn count(&self) -> Option<usize> {
+        if self.count_keys.is_empty() {
+            return None;
+        }
+        let count = self
+            .count_keys
+            .iter()
+            .filter_map(|key| match key.codes.first() {
+                KeyCode::Char(c) => c.to_digit(10),
+                _ => None,
+            })
+            .fold(0usize, |count, digit| count.saturating_mul(10).saturating_add(digit as usize));
+        Some(count)
+    }
+    /// Return the keys typed so far in an incomplete sequence,
+    /// excluding the count
+    pub fn pending(&self) -> &[KeyCombination] {
+        &self.pending
+    }
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty() || !self.count_keys.is_empty()
+    }
+    /// Tell whether the key is a digit which should be taken as part of a count
+    fn is_count_key(&self, key: KeyCombination) -> bool {
+        if !self.count_prefix || !self.pending.is_empty() || !key.modifiers.is_empty() {
+            return false;
+        }
+        match key.codes {
+            OneToThree::One(KeyCode::Char('0')) => !self.count_keys.is_empty(),
+            OneToThree::One(KeyCode::Char(c)) => c.is_ascii_digit(),
+            _ => false,
+        }
+    }
+    /// Build the match of an action, with the count if one was typed
+    fn matched(&mut self, action: A) -> SequenceMatch<A> {
+        match self.count() {
+            Some(count) => {
+                self.count_keys.clear();
+                SequenceMatch::MatchedWithCount(action, count)
+            }
+            None => SequenceMatch::Matched(action),
+        }
+    }
+    /// Return the keys which may follow the pending ones, with the bindings
+    /// they'd complete, so that applications may show them as hints.
+    ///
+    /// When no key is pending, the first keys of all bindings are returned.
+    /// Continuations are sorted by key, as displayed.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let mut bindings = KeyBindingMap::new();
+    /// bindings.insert("space f f", "find file").unwrap();
+    /// bindings.insert("space f r", "recent files").unwrap();
+    /// bindings.insert("space f g x", "grep").unwrap();
+    /// bindings.insert("x", "delete").unwrap();
+    /// let mut matcher = SequenceMatcher::new(bindings);
+    /// matcher.feed(key!(space));
+    /// matcher.feed(key!(f));
+    /// let continuations = matcher.continuations();
+    /// assert_eq!(continuations.len(), 3);
+    /// assert_eq!(continuations[0].key, key!(f));
+    /// assert_eq!(continuations[0].action, Some(&"find file"));
+    /// assert_eq!(continuations[1].key, key!(g));
+    /// assert_eq!(continuations[1].action, None);
+    /// assert!(continuations[1].is_prefix);
+    /// ```
+    pub fn continuations(&self) -> Vec<Continuation<'_, A>> {
+        let depth = self.pending.len();
+        let mut continuations: Vec<Continuation<'_, A>> = Vec::new();
+        for (sequence, _) in self.bindings.iter() {
+            if
+                sequence.len() <= depth
+                || !sequence.starts_with(&self.pending)
+                || self.bindings.is_disabled_canonical(sequence)
+            {
+                continue;
+            }
+            let key = sequence[depth];
+            let continuation = match continuations.iter_mut().find(|c| c.key == key) {
+                Some(continuation) => continuation,
+                None => {
+                    let keys = &sequence[..=depth];
+                    continuations.push(Continuation {
+                        key,
+                        action: self.bindings.get_sequence(keys),
+                        meta: self.bindings.meta(keys),
+                        is_prefix: false,
+                    });
+                    continuations.last_mut().unwrap()
+                }
+            };
+            if sequence.len() > depth + 1 {
+                continuation.is_prefix = true;
+            }
+        }
+        continuations.sort_by_cached_key(|c| c.key.to_string());
+        continuations
+    }
+    /// Tell whether there's a binding longer than the given keys and starting with them
+    fn is_strict_prefix(&self, keys: &[KeyCombination]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(sequence, _)| {
+                sequence.len() > keys.len()
+                    && sequence.starts_with(keys)
+                    && !self.bindings.is_disabled_canonical(sequence)
+            })
+    }
+    /// Receive a key combination and return what happened.
+    ///
+    /// Most often, there's exactly one result, but there may be more than
+    /// one when a key interrupts a pending sequence: a shorter binding may
+    /// be matched, then the new key is handled on its own.
+    pub fn feed(&mut self, key: KeyCombination) -> Vec<SequenceMatch<A>> {
+        let mut results = Vec::new();
+        let key = key.canonical();
+        if self.is_count_key(key) {
+            self.count_keys.push(key);
+            debug_event!(count = ?self.count(), "count pending");
+            return vec![SequenceMatch::Pending];
+        }
+        let mut queue = VecDeque::from([key]);
+        while let Some(key) = queue.pop_front() {
+            self.pending.push(key);
+            let action = self.bindings.get_sequence(&self.pending);
+            let is_prefix = self.is_strict_prefix(&self.pending);
+            if let Some(action) = action {
+                if !is_prefix || self.policy_of(&self.pending) == ResolutionPolicy::Immediate {
+                    let action = action.clone();
+                    results.push(self.matched(action));
+                    self.pending.clear();
+                    continue;
+                }
+            }
+            if is_prefix {
+                continue;
+            }
+            // the key doesn't continue any sequence
+            if self.pending.len() == 1 {
+                results.push(SequenceMatch::Unmatched(self.take_pending()));
+                continue;
+            }
+            // We resolve what was pending before this key, then handle
+            // the remaining keys on their own
+            self.pending.pop();
+            queue.push_front(key);
+            results.push(self.resolve_pending(&mut queue));
+        }
+        if self.is_pending() {
+            debug_event!(pending = %KeySequence::from(self.pending.clone()), "sequence pending");
+            results.push(SequenceMatch::Pending);
+        }
+        results
+    }
+    /// Resolve the pending keys, matching the longest bound prefix and
+    /// putting back the remaining keys in the queue
+    fn resolve_pending(&mut self, queue: &mut VecDeque<KeyCombination>) -> SequenceMatch<A> {
+        let keys = std::mem::take(&mut self.pending);
+        for len in (1..=keys.len()).rev() {
+            if let Some(action) = self.bindings.get_sequence(&keys[..len]) {
+                for &key in keys[len..].iter().rev() {
+                    queue.push_front(key);
+                }
+                let action = action.clone();
+                return self.matched(action);
+            }
+        }
+        self.pending = keys;
+        SequenceMatch::Unmatched(self.take_pending())
+    }
+    /// Take the pending keys, as unmatched, with the count keys if any
+    fn take_pending(&mut self) -> KeySequence {
+        let mut keys = std::mem::take(&mut self.count_keys);
+        keys.append(&mut self.pending);
+        keys.into()
+    }
+    /// End the pending sequence, for example on a timeout, returning the
+    /// binding it matches, or the unmatched keys.
+    ///
+    /// Return None when no key is pending.
+    pub fn flush(&mut self) -> Option<SequenceMatch<A>> {
+        if !self.is_pending() {
+            return None;
+        }
+        match self.bindings.get_sequence(&self.pending) {
+            Some(action) => {
+                let action = action.clone();
+                self.pending.clear();
+                Some(self.matched(action))
+            }
+            None => Some(SequenceMatch::Unmatched(self.take_pending())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn matcher() -> SequenceMatcher<&'static str> {
+        let mut bindings = KeyBindingMap::new();
+        bindings.insert("ctrl-k", "kill").unwrap();
+        bindings.insert("ctrl-k ctrl-c", "comment").unwrap();
+        bindings.insert("g g", "top").unwrap();
+        bindings.insert("x", "delete").unwrap();
+        bindings.insert("a", "append").unwrap();
+        bindings.insert("a b c", "abc").unwrap();
+        SequenceMatcher::new(bindings)
+    }
+
+    #[test]
+    fn interrupted_sequence() {
+        let mut matcher = matcher();
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(x)),
+            vec![SequenceMatch::Matched("kill"), SequenceMatch::Matched("delete")],
+        );
+        assert_eq!(matcher.feed(key!(g)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(y)),
+            vec![
+                SequenceMatch::Unmatched(KeySequence::from(key!(g))),
+                SequenceMatch::Unmatched(KeySequence::from(key!(y))),
+            ],
+        );
+        assert_eq!(matcher.feed(key!(g)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(ctrl-k)),
+            vec![SequenceMatch::Unmatched(KeySequence::from(key!(g))), SequenceMatch::Pending],
+        );
+        assert_eq!(matcher.flush(), Some(SequenceMatch::Matched("kill")));
+        assert_eq!(matcher.flush(), None);
+        assert_eq!(matcher.feed(key!(a)), vec![SequenceMatch::Pending]);
+        assert_eq!(matcher.feed(key!(b)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(x)),
+            vec![
+                SequenceMatch::Matched("append"),
+                SequenceMatch::Unmatched(KeySequence::from(key!(b))),
+                SequenceMatch::Matched("delete"),
+            ],
+        );
+    }
+
+    #[test]
+    fn count_prefix() {
+        let mut matcher = matcher();
+        assert_eq!(matcher.feed(key!(3)), vec![SequenceMatch::Unmatched(KeySequence::from(key!(3)))]);
+        matcher.set_count_prefix(true);
+        assert_eq!(matcher.feed(key!(0)), vec![SequenceMatch::Unmatched(KeySequence::from(key!(0)))]);
+        assert_eq!(matcher.feed(key!(2)), vec![SequenceMatch::Pending]);
+        assert_eq!(matcher.feed(key!(g)), vec![SequenceMatch::Pending]);
+        assert_eq!(matcher.feed(key!(g)), vec![SequenceMatch::MatchedWithCount("top", 2)]);
+        assert_eq!(matcher.count(), None);
+        assert_eq!(matcher.feed(key!(5)), vec![SequenceMatch::Pending]);
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(x)),
+            vec![SequenceMatch::MatchedWithCount("kill", 5), SequenceMatch::Matched("delete")],
+        );
+        assert_eq!(matcher.feed(key!(4)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.feed(key!(y)),
+            vec![SequenceMatch::Unmatched(KeySequence::new(vec![key!(4), key!(y)]))],
+        );
+        assert_eq!(matcher.feed(key!(4)), vec![SequenceMatch::Pending]);
+        assert_eq!(
+            matcher.flush(),
+            Some(SequenceMatch::Unmatched(KeySequence::from(key!(4)))),
+        );
+        assert!(!matcher.is_pending());
+    }
+
+    #[test]
+    fn immediate_policy() {
+        let mut matcher = matcher();
+        matcher.set_binding_policy("ctrl-k", ResolutionPolicy::Immediate).unwrap();
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Matched("kill")]);
+        matcher.set_binding_policy("ctrl-k", ResolutionPolicy::PreferLongest).unwrap();
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Pending]);
+        assert_eq!(matcher.feed(key!(ctrl-c)), vec![SequenceMatch::Matched("comment")]);
+        matcher.set_policy(ResolutionPolicy::Immediate);
+        matcher.set_binding_policy("ctrl-k", ResolutionPolicy::Immediate).unwrap();
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Matched("kill")]);
+    }
+
+    #[test]
+    fn disabled_bindings_are_ignored() {
+        let mut matcher = matcher();
+        matcher.bindings_mut().set_enabled(&[key!(ctrl-k), key!(ctrl-c)], false);
+        assert_eq!(matcher.feed(key!(ctrl-k)), vec![SequenceMatch::Matched("kill")]);
+        matcher.bindings_mut().set_enabled(&[key!(x)], false);
+        assert_eq!(
+            matcher.feed(key!(x)),
+            vec![SequenceMatch::Unmatched(KeySequence::from(key!(x)))],
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Serde helpers encoding key combinations numerically in binary formats
+//! (e.g. bincode, postcard), and as strings in human readable ones
+//! (e.g. JSON, TOML), to be used with `#[serde(with = ...)]`:
+//!
+//! ```
+//! use {
+//!     crokey::*,
+//!     serde::{Deserialize, Serialize},
+//! };
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Session {
+//!     #[serde(with = "crokey::serde_binary")]
+//!     last_key: KeyCombination,
+//!     #[serde(with = "crokey::serde_binary::sequence")]
+//!     pending: KeySequence,
+//! }
+//! let session = Session {
+//!     last_key: key!(ctrl-x),
+//!     pending: "ctrl-k ctrl-c".parse().unwrap(),
+//! };
+//! let bytes = bincode::serialize(&session).unwrap();
+//! assert_eq!(bincode::deserialize::<Session>(&bytes).unwrap(), session);
+//! ```
+//!
+//! The binary encoding is defined by crokey and doesn't depend on the
+//! declaration order of crossterm's enums: it stays readable when
+//! crossterm is upgraded.
+//!
+//! This module needs the "serde-binary" feature.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+        MediaKeyCode,
+        ModifierKeyCode,
+    },
+    serde::{
+        de,
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    },
+};
+
+// The kinds of key codes in the binary form. Those values and the
+// positions in the lists below are part of the format: only append.
+const CHAR: u8 = 0;
+const FUNCTION: u8 = 1;
+const NAMED: u8 = 2;
+const MEDIA: u8 = 3;
+const MODIFIER: u8 = 4;
+
+const NAMED_KEYS: &[KeyCode] = &[
+    Backspace, Enter, Left, Right, Up, Down, Home, End, PageUp, PageDown, Tab, BackTab,
+    Delete, Insert, Null, Esc, CapsLock, ScrollLock, NumLock, PrintScreen, Pause, Menu,
+    KeypadBegin,
+];
+
+const MEDIA_KEYS: &[MediaKeyCode] = &[
+    MediaKeyCode::Play,
+    MediaKeyCode::Pause,
+    MediaKeyCode::PlayPause,
+    MediaKeyCode::Reverse,
+    MediaKeyCode::Stop,
+    MediaKeyCode::FastForward,
+    MediaKeyCode::Rewind,
+    MediaKeyCode::TrackNext,
+    MediaKeyCode::TrackPrevious,
+    MediaKeyCode::Record,
+    MediaKeyCode::LowerVolume,
+    MediaKeyCode::RaiseVolume,
+    MediaKeyCode::MuteVolume,
+];
+
+const MODIFIER_KEYS: &[ModifierKeyCode] = &[
+    ModifierKeyCode::LeftShift,
+    ModifierKeyCode::LeftControl,
+    ModifierKeyCode::LeftAlt,
+    ModifierKeyCode::LeftSuper,
+    ModifierKeyCode::LeftHyper,
+    ModifierKeyCode::LeftMeta,
+    ModifierKeyCode::RightShift,
+    ModifierKeyCode::RightControl,
+    ModifierKeyCode::RightAlt,
+    ModifierKeyCode::RightSuper,
+    ModifierKeyCode::RightHyper,
+    ModifierKeyCode::RightMeta,
+    ModifierKeyCode::IsoLevel3Shift,
+    ModifierKeyCode::IsoLevel5Shift,
+];
+
+fn position<T: PartialEq>(list: &[T], item: T) -> u32 {
+    // all the variants are listed
+    list.iter().position(|i| *i == item).unwrap_or_default() as u32
+}
+
+/// The binary form of a key code: its kind and a value in this kind
+#[derive(Serialize, Deserialize)]
+struct BinaryKeyCode(u8, u32);
+
+impl From<KeyCode> for BinaryKeyCode {
+    fn from(code: KeyCode) -> Self {
+        match code {
+            Char(c) => Self(CHAR, c as u32),
+            F(n) => Self(FUNCTION, n.into()),
+            Media(media) => Self(MEDIA, position(MEDIA_KEYS, media)),
+            Modifier(modifier) => Self(MODIFIER, position(MODIFIER_KEYS, modifier)),
+            code => Self(NAMED, position(NAMED_KEYS, code)),
+        }
+    }
+}
+
+impl BinaryKeyCode {
+    fn into_code<E: de::Error>(self) -> Result<KeyCode, E> {
+        let Self(kind, value) = self;
+        let index = value as usize;
+        let code = match kind {
+            CHAR => char::from_u32(value).map(Char),
+            FUNCTION => u8::try_from(value).ok().map(F),
+            NAMED => NAMED_KEYS.get(index).copied(),
+            MEDIA => MEDIA_KEYS.get(index).copied().map(Media),
+            MODIFIER => MODIFIER_KEYS.get(index).copied().map(Modifier),
+            _ => None,
+        };
+        code.ok_or_else(|| E::custom("invalid key code"))
+    }
+}
+
+/// The binary form of a key combination
+#[derive(Serialize, Deserialize)]
+struct BinaryKeyCombination {
+    modifiers: u8,
+    codes: Vec<BinaryKeyCode>,
+}
+
+impl From<KeyCombination> for BinaryKeyCombination {
+    fn from(key: KeyCombination) -> Self {
+        Self {
+            modifiers: key.modifiers.bits(),
+            codes: key.codes.iter().map(|&code| code.into()).collect(),
+        }
+    }
+}
+
+impl BinaryKeyCombination {
+    fn into_key<E: de::Error>(self) -> Result<KeyCombination, E> {
+        let modifiers = KeyModifiers::from_bits(self.modifiers)
+            .ok_or_else(|| E::custom("invalid key modifiers"))?;
+        let codes = self
+            .codes
+            .into_iter()
+            .map(BinaryKeyCode::into_code)
+            .collect::<Result<Vec<_>, E>>()?;
+        let codes: OneToThree<KeyCode> = codes
+            .try_into()
+            .map_err(|_| E::custom("a key combination has one to three codes"))?;
+        // the encoded combination may come from an older version or
+        // another writer, and keys are looked up normalized
+        Ok(KeyCombination::new(codes, modifiers).normalized())
+    }
+}
+
+pub fn serialize<S>(key: &KeyCombination, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        key.serialize(serializer)
+    } else {
+        BinaryKeyCombination::from(*key).serialize(serializer)
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<KeyCombination, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        KeyCombination::deserialize(deserializer)
+    } else {
+        BinaryKeyCombination::deserialize(deserializer)?.into_key()
+    }
+}
+
+/// Helpers for key sequences, encoded numerically in binary formats
+pub mod sequence {
+    use super::*;
+
+    pub fn serialize<S>(keys: &KeySequence, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            keys.serialize(serializer)
+        } else {
+            let keys: Vec<BinaryKeyCombination> = keys.iter().map(|&k| k.into()).collect();
+            keys.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<KeySequence, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            KeySequence::deserialize(deserializer)
+        } else {
+            Vec::<BinaryKeyCombination>::deserialize(deserializer)?
+                .into_iter()
+                .map(BinaryKeyCombination::into_key)
+                .collect::<Result<Vec<_>, _>>()
+                .map(KeySequence::new)
+        }
+    }
+}
+
+#[test]
+fn check_human_readable_form_is_kept() {
+    #[derive(Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "crate::serde_binary")]
+        key: KeyCombination,
+    }
+    let config: Config = deser_hjson::from_str("{\nkey: alt-shift-f5\n}").unwrap();
+    assert_eq!(config.key, key!(alt-shift-f5));
+    let bytes = bincode::serialize(&config).unwrap();
+    assert_eq!(bincode::deserialize::<Config>(&bytes).unwrap().key, key!(alt-shift-f5));
+    assert!(bincode::deserialize::<Config>(&[0xff, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn check_binary_encoding_is_stable() {
+    #[derive(Serialize, Deserialize)]
+    struct Binary(#[serde(with = "crate::serde_binary")] KeyCombination);
+    let key = KeyCombination::new((Char('a'), BackTab), KeyModifiers::CONTROL);
+    let bytes = bincode::serialize(&Binary(key)).unwrap();
+    // the modifiers, the number of codes, then the kind and value of each sorted code
+    assert_eq!(bytes, [2, 2, 0, 0, 0, 0, 0, 0, 0, 2, 11, 0, 0, 0, 0, 97, 0, 0, 0]);
+    let codes = NAMED_KEYS
+        .iter()
+        .copied()
+        .chain([Char('é'), F(12)])
+        .chain(MEDIA_KEYS.iter().map(|&media| Media(media)))
+        .chain(MODIFIER_KEYS.iter().map(|&modifier| Modifier(modifier)));
+    for code in codes {
+        let key = KeyCombination::new(code, KeyModifiers::ALT);
+        let bytes = bincode::serialize(&Binary(key)).unwrap();
+        assert_eq!(bincode::deserialize::<Binary>(&bytes).unwrap().0, key.normalized());
+    }
+    // shift-a written without normalization is read as shift-A
+    let bytes = [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 97, 0, 0, 0];
+    let key = bincode::deserialize::<Binary>(&bytes).unwrap().0;
+    key.assert_normalized();
+    assert_eq!(key, key!(shift-a));
+}
@@ -0,0 +1,123 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+};
+
+/// How shift is expressed in a key combination involving a char
+/// having a case, e.g. `shift-a`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ShiftPolicy {
+    /// The SHIFT modifier with the lowercase char, e.g. `Shift-a`
+    PreferModifier,
+    /// The uppercase char without modifier, e.g. `A`.
+    ///
+    /// SHIFT is kept when the combination involves a key without case,
+    /// e.g. `shift-up`.
+    PreferUppercaseChar,
+    /// The SHIFT modifier with the uppercase char, e.g. `Shift-A`,
+    /// which is what crossterm sends and what [KeyCombination::normalized]
+    /// produces
+    #[default]
+    Both,
+}
+
+/// Return the uppercase version of the char, when it's a single char
+pub(crate) fn uppercase_char(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(u), None) => u,
+        _ => c,
+    }
+}
+
+/// Return the lowercase version of the char, when it's a single char
+pub(crate) fn lowercase_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+/// Tell whether the char has distinct lowercase and uppercase versions
+pub(crate) fn has_case(c: char) -> bool {
+    uppercase_char(c) != lowercase_char(c)
+}
+
+impl KeyCombination {
+    /// Return the normalized combination, with shift expressed according
+    /// to the policy.
+    ///
+    /// ```
+    /// use crokey::*;
+    /// use crossterm::event::{KeyCode, KeyModifiers};
+    /// let key = key!(shift-a);
+    /// assert_eq!(
+    ///     key.with_shift_policy(ShiftPolicy::PreferModifier),
+    ///     KeyCombination::new(KeyCode::Char('a'), KeyModifiers::SHIFT),
+    /// );
+    /// assert_eq!(
+    ///     key.with_shift_policy(ShiftPolicy::PreferUppercaseChar),
+    ///     KeyCombination::new(KeyCode::Char('A'), KeyModifiers::NONE),
+    /// );
+    /// assert_eq!(key.with_shift_policy(ShiftPolicy::Both), key);
+    /// ```
+    pub fn with_shift_policy(self, policy: ShiftPolicy) -> Self {
+        let mut key = self.normalized();
+        if !key.modifiers.contains(KeyModifiers::SHIFT) {
+            return key;
+        }
+        match policy {
+            ShiftPolicy::Both => {}
+            ShiftPolicy::PreferModifier => {
+                for i in 0..3 {
+                    if let Some(KeyCode::Char(c)) = key.codes.get_mut(i) {
+                        *c = lowercase_char(*c);
+                    }
+                }
+                key.codes = key.codes.sorted();
+            }
+            ShiftPolicy::PreferUppercaseChar => {
+                let all_cased = key
+                    .codes
+                    .iter()
+                    .all(|code| matches!(code, KeyCode::Char(c) if has_case(*c)));
+                for i in 0..3 {
+                    if let Some(KeyCode::Char(c)) = key.codes.get_mut(i) {
+                        *c = uppercase_char(*c);
+                    }
+                }
+                if all_cased {
+                    key.modifiers.remove(KeyModifiers::SHIFT);
+                }
+                key.codes = key.codes.sorted();
+            }
+        }
+        key
+    }
+}
+
+#[test]
+fn check_shift_policies() {
+    use crossterm::event::KeyCode::*;
+    let key = parse("shift-a-up").unwrap();
+    assert_eq!(
+        key.with_shift_policy(ShiftPolicy::PreferUppercaseChar),
+        KeyCombination::new((Char('A'), Up), KeyModifiers::SHIFT),
+    );
+    assert_eq!(
+        key.with_shift_policy(ShiftPolicy::PreferModifier),
+        KeyCombination::new((Char('a'), Up), KeyModifiers::SHIFT),
+    );
+    for policy in [ShiftPolicy::PreferModifier, ShiftPolicy::PreferUppercaseChar, ShiftPolicy::Both] {
+        assert_eq!(key.with_shift_policy(policy).normalized(), key.normalized());
+        assert_eq!(key!(ctrl-x).with_shift_policy(policy), key!(ctrl-x));
+    }
+    // shift isn't limited to ASCII letters
+    assert_eq!(key!(shift-é), parse("shift-é").unwrap());
+    assert_eq!(key!(shift-é).codes, OneToThree::One(Char('É')));
+    assert_eq!(KeyCombination::from(Char('É')).normalized(), key!(shift-é));
+}
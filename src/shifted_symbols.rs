@@ -0,0 +1,150 @@
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode,
+        KeyModifiers,
+    },
+};
+
+/// The ASCII symbols typed with shift on a US keyboard, with the key
+/// typed without shift, used by [ShiftedSymbolPolicy::UsBaseKey]
+pub const US_SHIFTED_SYMBOLS: &[(char, char)] = &[
+    ('~', '`'),
+    ('!', '1'),
+    ('@', '2'),
+    ('#', '3'),
+    ('$', '4'),
+    ('%', '5'),
+    ('^', '6'),
+    ('&', '7'),
+    ('*', '8'),
+    ('(', '9'),
+    (')', '0'),
+    ('_', '-'),
+    ('+', '='),
+    ('{', '['),
+    ('}', ']'),
+    ('|', '\\'),
+    (':', ';'),
+    ('"', '\''),
+    ('<', ','),
+    ('>', '.'),
+    ('?', '/'),
+];
+
+/// How shift is handled with the ASCII punctuation chars (e.g. `!`).
+///
+/// Depending on the terminal, `!` typed with shift on a US keyboard comes
+/// with or without the SHIFT modifier, and it comes from different keys on
+/// other layouts. Without a common form, the combination would match
+/// neither a `!` binding nor a `shift-1` one consistently.
+///
+/// The policy is applied to the combinations produced by the [CombinerCore]
+/// and to the parsed ones (see [ParseOptions::shifted_symbol_policy]), so
+/// both should be given the same one. The [key!] macro, the conversion from
+/// a `KeyEvent` and [KeyCombination::canonical] keep the combinations as
+/// they are, like the default policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ShiftedSymbolPolicy {
+    /// Keep the combination as received or written, e.g. `shift-!`
+    #[default]
+    Keep,
+    /// A punctuation char is the symbol typed, whatever the modifier used
+    /// to type it: shift is removed, e.g. `shift-!` is `!`.
+    ///
+    /// This gives a form independent of the keyboard layout.
+    DropShift,
+    /// The symbols of the [US_SHIFTED_SYMBOLS] table are the key typed with
+    /// shift on a US keyboard, e.g. both `!` and `shift-!` are `shift-1`.
+    ///
+    /// This only makes sense when the users have a US keyboard.
+    UsBaseKey,
+}
+
+impl KeyCombination {
+    /// Return the combination with shift handled according to the policy
+    /// when all its keys are ASCII punctuation chars
+    ///
+    /// ```
+    /// use crokey::*;
+    /// let key = key!('!');
+    /// assert_eq!(key.with_shifted_symbol_policy(ShiftedSymbolPolicy::UsBaseKey), key!(shift-'1'));
+    /// let written = parse("shift-!").unwrap();
+    /// assert_eq!(written, key!(shift-'!'));
+    /// assert_eq!(written.with_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift), key);
+    /// let options = ParseOptions::default().with_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift);
+    /// assert_eq!(parse_with("shift-!", &options).unwrap(), key);
+    /// ```
+    pub fn with_shifted_symbol_policy(self, policy: ShiftedSymbolPolicy) -> Self {
+        let symbols_only = self
+            .codes
+            .iter()
+            .all(|code| matches!(code, KeyCode::Char(c) if c.is_ascii_punctuation()));
+        if !symbols_only {
+            return self;
+        }
+        match policy {
+            ShiftedSymbolPolicy::Keep => self,
+            ShiftedSymbolPolicy::DropShift => Self {
+                codes: self.codes,
+                modifiers: self.modifiers - KeyModifiers::SHIFT,
+            },
+            ShiftedSymbolPolicy::UsBaseKey => {
+                let base = |c: char| {
+                    US_SHIFTED_SYMBOLS
+                        .iter()
+                        .find(|(symbol, _)| *symbol == c)
+                        .map(|(_, base)| *base)
+                };
+                let mut modifiers = self.modifiers;
+                let shifted = self
+                    .codes
+                    .iter()
+                    .any(|code| matches!(code, KeyCode::Char(c) if base(*c).is_some()));
+                if shifted {
+                    modifiers |= KeyModifiers::SHIFT;
+                }
+                let codes = self.codes.map(|code| match code {
+                    KeyCode::Char(c) => KeyCode::Char(base(c).unwrap_or(c)),
+                    code => code,
+                });
+                Self::new(codes, modifiers)
+            }
+        }
+    }
+}
+
+#[test]
+fn check_shifted_symbol_policy() {
+    use crossterm::event::{KeyEvent, KeyEventKind};
+    let mut core = CombinerCore::default();
+    // the same key, from a legacy terminal and from a kitty one
+    let legacy = KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE);
+    let kitty = KeyEvent::new(KeyCode::Char('!'), KeyModifiers::SHIFT);
+    assert_eq!(core.transform(legacy), Some(KeyCombination::from(legacy)));
+    assert_eq!(core.transform(kitty), Some(KeyCombination::from(kitty)));
+    assert_eq!(core.transform(kitty), Some(key!(shift-'!')));
+    assert_eq!(parse("shift-!").unwrap(), key!(shift-'!'));
+    core.set_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift);
+    assert_eq!(core.transform(legacy), Some(key!('!')));
+    assert_eq!(core.transform(kitty), Some(key!('!')));
+    let drop = ParseOptions::default().with_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift);
+    assert_eq!(parse_with("shift-!", &drop).unwrap(), key!('!'));
+    core.set_shifted_symbol_policy(ShiftedSymbolPolicy::UsBaseKey);
+    assert_eq!(core.transform(legacy), Some(key!(shift-'1')));
+    assert_eq!(core.transform(kitty), Some(key!(shift-'1')));
+    let release = KeyEvent { kind: KeyEventKind::Release, ..kitty };
+    assert_eq!(core.transform(release), None);
+    let us = ParseOptions::default().with_shifted_symbol_policy(ShiftedSymbolPolicy::UsBaseKey);
+    assert_eq!(parse_with("!", &us).unwrap(), key!(shift-'1'));
+    assert_eq!(parse_with("shift-1", &us).unwrap(), key!(shift-'1'));
+    assert_eq!(
+        parse_with("ctrl-?", &us).unwrap(),
+        KeyCombination::new(KeyCode::Char('/'), KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+    );
+    // chars other than ASCII punctuation aren't changed
+    for key in [key!(shift-a), key!(shift-'1'), key!(shift-space), key!(shift-'!'-a)] {
+        assert_eq!(key.with_shifted_symbol_policy(ShiftedSymbolPolicy::DropShift), key);
+    }
+    assert_eq!(parse_with("shift-!-?", &drop).unwrap(), parse("!-?").unwrap());
+}
@@ -0,0 +1,89 @@
+//! Helpers for testing the code handling keys, without terminal.
+
+use {
+    crate::*,
+    std::{
+        sync::{
+            Arc,
+            Mutex,
+        },
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// A clock whose time only changes when told to, so that tests of
+/// time-based features are deterministic.
+///
+/// The clones of a mock clock share the same time.
+///
+/// ```
+/// use {
+///     crokey::{*, testing::MockClock},
+///     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+///     std::time::Duration,
+/// };
+/// let clock = MockClock::new();
+/// let mut detector = LongPressDetector::new(Duration::from_millis(400))
+///     .with_clock(clock.clone());
+/// let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+/// assert_eq!(detector.transform(&space), None);
+/// clock.advance(Duration::from_millis(399));
+/// assert_eq!(detector.poll(), None);
+/// clock.advance(Duration::from_millis(1));
+/// assert_eq!(detector.poll(), Some(LongPress(key!(space))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    /// Create a clock stopped at the current time
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+    /// Create a clock stopped at the given instant
+    pub fn starting_at(now: Instant) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+    /// Move the time forward
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+    /// Set the time
+    pub fn set(&self, now: Instant) {
+        *self.lock() = now;
+    }
+    fn lock(&self) -> std::sync::MutexGuard<'_, Instant> {
+        // the lock can't be poisoned as there's no panic while it's held
+        self.now.lock().unwrap()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.lock()
+    }
+}
+
+#[test]
+fn check_mock_clock() {
+    let clock = MockClock::new();
+    let start = clock.now();
+    let shared = clock.clone();
+    shared.advance(Duration::from_secs(2));
+    assert_eq!(clock.now(), start + Duration::from_secs(2));
+    clock.set(start);
+    assert_eq!(shared.now(), start);
+}
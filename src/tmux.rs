@@ -0,0 +1,166 @@
+//! Conversions from and to the key syntax of tmux,
+//! for example `C-b`, `M-x`, `S-Up` or `PPage`.
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+};
+
+/// Parse a key as written in tmux configuration or commands.
+///
+/// Key names are case insensitive but single chars aren't: as in tmux,
+/// `M-X` is alt and an uppercase x.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(parse_tmux("C-b").unwrap(), key!(ctrl-b));
+/// assert_eq!(parse_tmux("M-x").unwrap(), key!(alt-x));
+/// assert_eq!(parse_tmux("S-Up").unwrap(), key!(shift-up));
+/// assert_eq!(parse_tmux("PPage").unwrap(), key!(pageup));
+/// assert_eq!(parse_tmux("^a").unwrap(), key!(ctrl-a));
+/// ```
+pub fn parse_tmux(raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    let mut modifiers = KeyModifiers::empty();
+    let mut key = raw;
+    loop {
+        let prefix = if key.len() > 2 { key.get(..2) } else { None };
+        if matches!(prefix, Some("C-" | "c-")) {
+            modifiers.insert(KeyModifiers::CONTROL);
+        } else if matches!(prefix, Some("M-" | "m-")) {
+            modifiers.insert(KeyModifiers::ALT);
+        } else if matches!(prefix, Some("S-" | "s-")) {
+            modifiers.insert(KeyModifiers::SHIFT);
+        } else if key.len() > 1 && key.starts_with('^') {
+            modifiers.insert(KeyModifiers::CONTROL);
+            key = &key[1..];
+            continue;
+        } else {
+            break;
+        }
+        key = &key[2..];
+    }
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            if c.is_ascii_uppercase() {
+                modifiers.insert(KeyModifiers::SHIFT);
+            }
+            Char(c)
+        }
+        _ => tmux_key_code(&key.to_ascii_lowercase()).ok_or_else(|| ParseKeyError::new(raw))?,
+    };
+    if code == BackTab {
+        modifiers.insert(KeyModifiers::SHIFT);
+    }
+    let key = KeyCombination::new(code, modifiers);
+    Ok(key.normalized())
+}
+
+fn tmux_key_code(name: &str) -> Option<KeyCode> {
+    let code = match name {
+        "up" => Up,
+        "down" => Down,
+        "left" => Left,
+        "right" => Right,
+        "bspace" => Backspace,
+        "btab" => BackTab,
+        "dc" => Delete,
+        "end" => End,
+        "enter" => Enter,
+        "escape" => Esc,
+        "home" => Home,
+        "ic" => Insert,
+        "npage" | "pagedown" | "pgdn" => PageDown,
+        "ppage" | "pageup" | "pgup" => PageUp,
+        "space" => Char(' '),
+        "tab" => Tab,
+        _ => {
+            let n = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&n) {
+                return None;
+            }
+            F(n)
+        }
+    };
+    Some(code)
+}
+
+fn tmux_key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        Up => "Up",
+        Down => "Down",
+        Left => "Left",
+        Right => "Right",
+        Backspace => "BSpace",
+        BackTab => "BTab",
+        Delete => "DC",
+        End => "End",
+        Enter | Char('\r') | Char('\n') => "Enter",
+        Esc => "Escape",
+        Home => "Home",
+        Insert => "IC",
+        PageDown => "NPage",
+        PageUp => "PPage",
+        Char(' ') => "Space",
+        Tab => "Tab",
+        F(n) => {
+            return Some(format!("F{n}"));
+        }
+        Char(c) => {
+            return Some(c.to_string());
+        }
+        _ => {
+            return None;
+        }
+    };
+    Some(name.to_string())
+}
+
+/// Build the tmux description of a key combination.
+///
+/// With letters, shift is expressed with the case of the letter.
+/// Return `None` when the combination can't be expressed in tmux,
+/// for example when it involves several non-modifier keys.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(to_tmux_string(key!(ctrl-b)).unwrap(), "C-b");
+/// assert_eq!(to_tmux_string(key!(alt-shift-x)).unwrap(), "M-X");
+/// assert_eq!(to_tmux_string(key!(shift-up)).unwrap(), "S-Up");
+/// ```
+pub fn to_tmux_string(key: KeyCombination) -> Option<String> {
+    let key = key.normalized();
+    let OneToThree::One(code) = key.codes else {
+        return None;
+    };
+    let mut s = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        s.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        s.push_str("M-");
+    }
+    let shift_in_case = matches!(code, Char(c) if c.is_ascii_uppercase());
+    if key.modifiers.contains(KeyModifiers::SHIFT) && !shift_in_case && code != BackTab {
+        s.push_str("S-");
+    }
+    s.push_str(&tmux_key_name(code)?);
+    Some(s)
+}
+
+#[test]
+fn check_tmux_round_trip() {
+    for raw in ["C-b", "M-x", "M-X", "S-Up", "PPage", "NPage", "C-M-Space", "BTab", "F5", "M--"] {
+        let key = parse_tmux(raw).unwrap();
+        assert_eq!(to_tmux_string(key).unwrap(), raw);
+    }
+    assert_eq!(parse_tmux("X").unwrap(), key!(shift-x));
+    assert_eq!(parse_tmux("ඞ").unwrap(), key!('ඞ'));
+    assert_eq!(parse_tmux("C-M-bspace").unwrap(), key!(ctrl-alt-backspace));
+    assert!(parse_tmux("F13").is_err());
+    assert!(parse_tmux("Nothing").is_err());
+    assert!(to_tmux_string(key!(ctrl-a-b)).is_none());
+}
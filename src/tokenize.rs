@@ -0,0 +1,120 @@
+use {
+    crate::{
+        parse::modifier_prefix,
+        parse_key_code_with,
+        ParseOptions,
+    },
+    std::ops::Range,
+};
+
+/// The class of a [KeyToken]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyTokenKind {
+    /// A modifier, e.g. `ctrl` or `command`, without the following `-`
+    Modifier,
+    /// A key, e.g. `f1`, `a` or `-`
+    KeyName,
+    /// A `-` between modifiers and keys
+    Separator,
+    /// A part which makes the parsing fail, e.g. an unknown key. It's
+    /// empty where a key is missing (e.g. after `ctrl-`).
+    Invalid,
+}
+
+/// A span of a key combination string, as found by [tokenize]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyToken {
+    pub kind: KeyTokenKind,
+    /// the byte range of the token in the tokenized string
+    pub span: Range<usize>,
+}
+
+impl KeyToken {
+    fn new(kind: KeyTokenKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+    /// Return the text of the token in the tokenized string
+    pub fn text<'s>(&self, raw: &'s str) -> &'s str {
+        &raw[self.span.clone()]
+    }
+}
+
+/// Split a key combination string into classified spans, e.g. for the
+/// syntax highlighting of bindings in a configuration editor.
+///
+/// The tokens cover the whole string. There's no [KeyTokenKind::Invalid]
+/// token exactly when [parse](crate::parse) succeeds.
+///
+/// ```
+/// use crokey::*;
+/// let raw = "Ctrl-Alt-f13";
+/// let tokens: Vec<_> = tokenize(raw)
+///     .iter()
+///     .map(|token| (token.kind, token.text(raw)))
+///     .collect();
+/// assert_eq!(tokens, [
+///     (KeyTokenKind::Modifier, "Ctrl"),
+///     (KeyTokenKind::Separator, "-"),
+///     (KeyTokenKind::Modifier, "Alt"),
+///     (KeyTokenKind::Separator, "-"),
+///     (KeyTokenKind::Invalid, "f13"),
+/// ]);
+/// ```
+pub fn tokenize(raw: &str) -> Vec<KeyToken> {
+    tokenize_with(raw, &ParseOptions::default())
+}
+
+/// Split a key combination string into classified spans, according to
+/// the options of [parse_with](crate::parse_with)
+pub fn tokenize_with(raw: &str, options: &ParseOptions) -> Vec<KeyToken> {
+    let mut tokens = Vec::new();
+    // the ASCII lowercasing keeps the byte offsets
+    let lowercase = raw.to_ascii_lowercase();
+    let mut start = 0;
+    while let Some((_, len)) = modifier_prefix(&lowercase[start..], options) {
+        let separator = start + len - 1;
+        tokens.push(KeyToken::new(KeyTokenKind::Modifier, start..separator));
+        tokens.push(KeyToken::new(KeyTokenKind::Separator, separator..separator + 1));
+        start += len;
+    }
+    let rest = &lowercase[start..];
+    if rest == "-" {
+        tokens.push(KeyToken::new(KeyTokenKind::KeyName, start..raw.len()));
+        return tokens;
+    }
+    for (i, part) in rest.split('-').enumerate() {
+        if i > 0 {
+            tokens.push(KeyToken::new(KeyTokenKind::Separator, start - 1..start));
+        }
+        let kind = if parse_key_code_with(part, options).is_ok() {
+            KeyTokenKind::KeyName
+        } else {
+            KeyTokenKind::Invalid
+        };
+        tokens.push(KeyToken::new(kind, start..start + part.len()));
+        start += part.len() + 1;
+    }
+    tokens
+}
+
+#[test]
+fn check_tokenize_agrees_with_parse() {
+    use crate::*;
+    for raw in [
+        "", "a", "A", "-", "alt--", "ctrl-", "ctrl--a", "a-b-c", "a-b-c-d", "Shift-Tab",
+        "ctrl-hyper-a", "command-option-é", "0x3f", "0xnope", "ctrl-0x1d", "primary-s",
+        "f1-f2", "alt-f12-@", "ctrl-shift-alt--", "space-", "super-super-x",
+    ] {
+        let tokens = tokenize(raw);
+        let valid = tokens.iter().all(|token| token.kind != KeyTokenKind::Invalid);
+        assert_eq!(valid, parse(raw).is_ok(), "{:?}", raw);
+        let text: String = tokens.iter().map(|token| token.text(raw)).collect();
+        assert_eq!(text, raw);
+    }
+    let strict = ParseOptions::default().with_modifier_synonyms(false);
+    let kinds: Vec<_> = tokenize_with("command-s", &strict)
+        .into_iter()
+        .map(|token| token.kind)
+        .collect();
+    assert_eq!(kinds, [KeyTokenKind::Invalid, KeyTokenKind::Separator, KeyTokenKind::KeyName]);
+}
@@ -0,0 +1,166 @@
+//! Detection and optional disabling of the terminal line discipline
+//! features intercepting some keys: flow control (`ctrl-s`, `ctrl-q`)
+//! and signal keys (`ctrl-c`, `ctrl-z`, `ctrl-\`).
+//!
+//! crossterm's raw mode already disables both, so you only need this
+//! when your application reads keys without entering raw mode.
+
+use {
+    crate::*,
+    std::{
+        fmt,
+        io,
+    },
+};
+
+/// Tell whether the terminal on stdin handles flow control, i.e. whether
+/// `ctrl-s` and `ctrl-q` stop and resume the output instead of being
+/// received by the application.
+///
+/// Always return false on non unix platforms.
+pub fn is_flow_control_enabled() -> io::Result<bool> {
+    #[cfg(unix)]
+    {
+        Ok(sys::get_termios(libc::STDIN_FILENO)?.c_iflag & libc::IXON != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(false)
+    }
+}
+
+/// Tell whether the terminal on stdin turns `ctrl-c`, `ctrl-z` and `ctrl-\`
+/// into signals instead of passing them to the application.
+///
+/// Always return false on non unix platforms.
+pub fn are_signal_keys_enabled() -> io::Result<bool> {
+    #[cfg(unix)]
+    {
+        Ok(sys::get_termios(libc::STDIN_FILENO)?.c_lflag & libc::ISIG != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(false)
+    }
+}
+
+impl ReservedKeys {
+    /// Return the keys intercepted by the terminal on stdin in its current
+    /// state, which is empty when it's in raw mode.
+    pub fn from_terminal() -> io::Result<Self> {
+        let mut reserved = Self::new();
+        if is_flow_control_enabled()? {
+            reserved.insert(key!(ctrl-s), "flow control: XOFF stops the output");
+            reserved.insert(key!(ctrl-q), "flow control: XON resumes the output");
+        }
+        if are_signal_keys_enabled()? {
+            reserved.insert(key!(ctrl-c), "sends SIGINT");
+            reserved.insert(key!(ctrl-z), "job control: suspends the application");
+            reserved.insert(key!(ctrl-'\\'), "sends SIGQUIT");
+        }
+        Ok(reserved)
+    }
+}
+
+/// Disable flow control and/or signal keys on the terminal as long as
+/// it lives, so that `ctrl-s`, `ctrl-q`, `ctrl-z`, etc. can be bound.
+///
+/// The flags cleared by the guard are set back on drop, the other changes
+/// made to the terminal meanwhile (e.g. raw mode) being kept.
+///
+/// ```no_run
+/// let _guard = crokey::TtyKeysGuard::new(true, true)?;
+/// // ctrl-s and ctrl-z are now received as normal keys
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// This does nothing on non unix platforms.
+pub struct TtyKeysGuard {
+    /// the input flags cleared by the guard
+    #[cfg(unix)]
+    cleared_iflag: libc::tcflag_t,
+    /// the local flags cleared by the guard
+    #[cfg(unix)]
+    cleared_lflag: libc::tcflag_t,
+}
+
+// termios is only Debug in recent versions of libc
+impl fmt::Debug for TtyKeysGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut d = f.debug_struct("TtyKeysGuard");
+        #[cfg(unix)]
+        d.field("changed", &(self.cleared_iflag | self.cleared_lflag != 0));
+        d.finish()
+    }
+}
+
+impl TtyKeysGuard {
+    pub fn new(disable_flow_control: bool, disable_signal_keys: bool) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            let mut termios = sys::get_termios(libc::STDIN_FILENO)?;
+            let cleared_iflag = if disable_flow_control {
+                termios.c_iflag & libc::IXON
+            } else {
+                0
+            };
+            let cleared_lflag = if disable_signal_keys {
+                termios.c_lflag & libc::ISIG
+            } else {
+                0
+            };
+            if cleared_iflag | cleared_lflag != 0 {
+                termios.c_iflag &= !cleared_iflag;
+                termios.c_lflag &= !cleared_lflag;
+                sys::set_termios(libc::STDIN_FILENO, &termios)?;
+            }
+            Ok(Self { cleared_iflag, cleared_lflag })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (disable_flow_control, disable_signal_keys);
+            Ok(Self {})
+        }
+    }
+}
+
+impl Drop for TtyKeysGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if self.cleared_iflag | self.cleared_lflag != 0 {
+            if let Ok(mut termios) = sys::get_termios(libc::STDIN_FILENO) {
+                termios.c_iflag |= self.cleared_iflag;
+                termios.c_lflag |= self.cleared_lflag;
+                let _ = sys::set_termios(libc::STDIN_FILENO, &termios);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+pub(crate) mod sys {
+    use std::{
+        io,
+        mem::MaybeUninit,
+        os::unix::io::RawFd,
+    };
+
+    pub fn get_termios(fd: RawFd) -> io::Result<libc::termios> {
+        let mut termios = MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fully initializes the struct when it succeeds
+        unsafe {
+            if libc::tcgetattr(fd, termios.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(termios.assume_init())
+        }
+    }
+
+    pub fn set_termios(fd: RawFd, termios: &libc::termios) -> io::Result<()> {
+        // SAFETY: the pointer comes from a reference to a valid termios
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use {
+    crate::{
+        parse::{
+            KEY_NAMES,
+            modifier_prefix,
+            MODIFIER_NAMES,
+        },
+        *,
+    },
+    crossterm::event::KeyModifiers,
+    std::ops::Range,
+};
+
+/// How serious a [Diagnostic] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The string can't be parsed
+    Error,
+    /// The string is parsed, but probably not as intended
+    Warning,
+    /// The string is fine, but there's something to know about it
+    Hint,
+}
+
+/// A problem found in a binding string by [validate_binding_string]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// the byte range of the problem in the string (empty where
+    /// something is missing)
+    pub range: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+    /// a text which could replace the range
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    fn new<S: Into<String>>(range: Range<usize>, severity: Severity, message: S) -> Self {
+        Self {
+            range,
+            severity,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+}
+
+/// Check a binding string, e.g. while it's typed in a configuration editor,
+/// and return the problems found, with their positions.
+///
+/// There's an error exactly when [parse] fails.
+///
+/// ```
+/// use crokey::*;
+/// let diagnostics = validate_binding_string("ctlr-x");
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].range, 0..4);
+/// assert_eq!(diagnostics[0].severity, Severity::Error);
+/// assert_eq!(diagnostics[0].suggestion.as_deref(), Some("ctrl"));
+/// assert!(validate_binding_string("ctrl-x").is_empty());
+/// ```
+pub fn validate_binding_string(raw: &str) -> Vec<Diagnostic> {
+    validate_binding_string_with(raw, &ParseOptions::default())
+}
+
+/// Check a binding string according to the options of [parse_with]
+pub fn validate_binding_string_with(raw: &str, options: &ParseOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let tokens = tokenize_with(raw, options);
+    let lowercase = raw.to_ascii_lowercase();
+    let mut modifiers = KeyModifiers::NONE;
+    let mut key_count = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        let span = token.span.clone();
+        match token.kind {
+            KeyTokenKind::Modifier => {
+                let name = &lowercase[span.start..=span.end]; // with the `-`
+                if let Some((modifier, _)) = modifier_prefix(name, options) {
+                    if modifiers.intersects(modifier) {
+                        diagnostics.push(Diagnostic::new(
+                            span.start..span.end + 1,
+                            Severity::Warning,
+                            format!("{} is already given", token.text(raw)),
+                        ));
+                    }
+                    modifiers |= modifier;
+                }
+            }
+            KeyTokenKind::KeyName => {
+                key_count += 1;
+                if key_count > 3 {
+                    diagnostics.push(Diagnostic::new(
+                        span,
+                        Severity::Warning,
+                        "only 3 keys can be combined, this one is ignored",
+                    ));
+                }
+            }
+            KeyTokenKind::Separator => {}
+            KeyTokenKind::Invalid if span.is_empty() => {
+                diagnostics.push(Diagnostic::new(span, Severity::Error, "a key is missing"));
+            }
+            KeyTokenKind::Invalid => {
+                key_count += 1;
+                let is_last = i + 1 == tokens.len();
+                let mut diagnostic = Diagnostic::new(
+                    span.clone(),
+                    Severity::Error,
+                    format!("{:?} isn't a known key", token.text(raw)),
+                );
+                diagnostic.suggestion = suggestion(&lowercase[span], !is_last, options);
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    if diagnostics.is_empty() && key_count > 1 {
+        diagnostics.push(Diagnostic::new(
+            0..raw.len(),
+            Severity::Hint,
+            "a combination of several keys needs the kitty keyboard protocol",
+        ));
+    }
+    diagnostics
+}
+
+/// Return the known name closest to the unknown one, if it's
+/// close enough to be a typo
+fn suggestion(unknown: &str, with_modifiers: bool, options: &ParseOptions) -> Option<String> {
+    let modifiers: &[_] = if with_modifiers { MODIFIER_NAMES } else { &[] };
+    let synonyms: &[_] = if with_modifiers && options.modifier_synonyms {
+        MODIFIER_SYNONYMS
+    } else {
+        &[]
+    };
+    let modifier_names = modifiers
+        .iter()
+        .chain(synonyms)
+        .map(|(name, _)| name.trim_end_matches('-'));
+    let max_distance = (unknown.chars().count() / 4).max(1);
+    KEY_NAMES
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(modifier_names)
+        .chain(options.aliases.keys().map(String::as_str))
+        .map(|name| (edit_distance(unknown, name), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.to_string())
+}
+
+/// Compute the number of insertions, deletions, substitutions and
+/// transpositions of adjacent chars needed to go from a to b
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[test]
+fn check_binding_string_validation() {
+    let check = |raw: &str, expected: &[(Range<usize>, Severity, Option<&str>)]| {
+        let diagnostics: Vec<_> = validate_binding_string(raw)
+            .into_iter()
+            .map(|d| (d.range, d.severity, d.suggestion))
+            .collect();
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(range, severity, suggestion)| {
+                (range.clone(), *severity, suggestion.map(String::from))
+            })
+            .collect();
+        assert_eq!(diagnostics, expected, "{:?}", raw);
+    };
+    check("alt-enter", &[]);
+    check("", &[(0..0, Severity::Error, None)]);
+    check("ctrl-", &[(5..5, Severity::Error, None)]);
+    check("alt-entr", &[(4..8, Severity::Error, Some("enter"))]);
+    check("Ctrl-PgUpp", &[(5..10, Severity::Error, Some("pgup"))]);
+    check("shift-xyzzy", &[(6..11, Severity::Error, None)]);
+    check("ctrl-ctrl-a", &[(5..10, Severity::Warning, None)]);
+    check("ctrl-control-a", &[(5..13, Severity::Warning, None)]);
+    check("a-b-c-d", &[(6..7, Severity::Warning, None)]);
+    check("ctrl-a-b", &[(0..8, Severity::Hint, None)]);
+    for raw in ["", "a", "ctrl-", "x-ctrl", "ctlr-x", "a-b-c-d", "shift-f1-f2", "-"] {
+        let has_error = validate_binding_string(raw)
+            .iter()
+            .any(|d| d.severity == Severity::Error);
+        assert_eq!(has_error, parse(raw).is_err(), "{:?}", raw);
+    }
+    assert_eq!(edit_distance("ctlr", "ctrl"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
@@ -0,0 +1,178 @@
+//! Conversions from and to the syntax of VS Code keybindings,
+//! for example `"ctrl+shift+p"` or `"ctrl+k ctrl+s"` (a chord).
+
+use {
+    crate::*,
+    crossterm::event::{
+        KeyCode::{self, *},
+        KeyModifiers,
+    },
+};
+
+/// The name VS Code gives to the SUPER modifier on the current platform
+#[cfg(target_os = "macos")]
+const VSCODE_SUPER: &str = "cmd";
+#[cfg(windows)]
+const VSCODE_SUPER: &str = "win";
+#[cfg(not(any(target_os = "macos", windows)))]
+const VSCODE_SUPER: &str = "meta";
+
+/// Parse a VS Code keybinding, which may be a chord of several
+/// combinations separated by spaces.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(
+///     parse_vscode("ctrl+shift+p").unwrap(),
+///     KeySequence::from(key!(ctrl-shift-p)),
+/// );
+/// assert_eq!(
+///     parse_vscode("ctrl+k ctrl+s").unwrap(),
+///     KeySequence::new(vec![key!(ctrl-k), key!(ctrl-s)]),
+/// );
+/// ```
+pub fn parse_vscode(raw: &str) -> Result<KeySequence, ParseKeyError> {
+    let keys = raw
+        .split_whitespace()
+        .map(parse_vscode_combination)
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err(ParseKeyError::new(raw));
+    }
+    Ok(KeySequence::new(keys))
+}
+
+fn parse_vscode_combination(raw: &str) -> Result<KeyCombination, ParseKeyError> {
+    let lowercase = raw.to_ascii_lowercase();
+    let mut modifiers = KeyModifiers::empty();
+    let mut key: &str = &lowercase;
+    loop {
+        if let Some(end) = key.strip_prefix("ctrl+") {
+            key = end;
+            modifiers.insert(KeyModifiers::CONTROL);
+        } else if let Some(end) = key.strip_prefix("shift+") {
+            key = end;
+            modifiers.insert(KeyModifiers::SHIFT);
+        } else if let Some(end) = key.strip_prefix("alt+") {
+            key = end;
+            modifiers.insert(KeyModifiers::ALT);
+        } else if let Some(end) = key
+            .strip_prefix("cmd+")
+            .or_else(|| key.strip_prefix("meta+"))
+            .or_else(|| key.strip_prefix("win+"))
+        {
+            key = end;
+            modifiers.insert(KeyModifiers::SUPER);
+        } else {
+            break;
+        }
+    }
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+    let code = match key {
+        "escape" => Esc,
+        "" => return Err(ParseKeyError::new(raw)),
+        _ => parse_key_code(key, shift).map_err(|_| ParseKeyError::new(raw))?,
+    };
+    if code == BackTab {
+        modifiers.insert(KeyModifiers::SHIFT);
+    }
+    Ok(KeyCombination::new(code, modifiers))
+}
+
+fn vscode_key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        Esc => "escape".to_string(),
+        Enter | Char('\r') | Char('\n') => "enter".to_string(),
+        Tab | BackTab => "tab".to_string(),
+        Backspace => "backspace".to_string(),
+        Delete => "delete".to_string(),
+        Insert => "insert".to_string(),
+        Home => "home".to_string(),
+        End => "end".to_string(),
+        PageUp => "pageup".to_string(),
+        PageDown => "pagedown".to_string(),
+        Up => "up".to_string(),
+        Down => "down".to_string(),
+        Left => "left".to_string(),
+        Right => "right".to_string(),
+        F(n) => format!("f{n}"),
+        Char(' ') => "space".to_string(),
+        Char(c) => c.to_lowercase().collect(),
+        _ => {
+            return None;
+        }
+    };
+    Some(name)
+}
+
+fn to_vscode_combination(key: KeyCombination) -> Option<String> {
+    let OneToThree::One(code) = key.codes else {
+        return None; // VS Code has no simultaneous non-modifier keys
+    };
+    let mut s = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        s.push_str("ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) || code == BackTab {
+        s.push_str("shift+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        s.push_str("alt+");
+    }
+    if key.modifiers.contains(KeyModifiers::SUPER) {
+        s.push_str(VSCODE_SUPER);
+        s.push('+');
+    }
+    s.push_str(&vscode_key_name(code)?);
+    Some(s)
+}
+
+/// Build the VS Code description of a key combination or of a chord.
+///
+/// Return `None` when one of the combinations can't be expressed in VS Code,
+/// for example when it involves several non-modifier keys.
+///
+/// ```
+/// use crokey::*;
+/// assert_eq!(to_vscode_string(&[key!(ctrl-shift-p)]).unwrap(), "ctrl+shift+p");
+/// assert_eq!(
+///     to_vscode_string(&[key!(ctrl-k), key!(ctrl-s)]).unwrap(),
+///     "ctrl+k ctrl+s",
+/// );
+/// assert_eq!(to_vscode_string(&[key!(ctrl-a-b)]), None);
+/// ```
+pub fn to_vscode_string(keys: &[KeyCombination]) -> Option<String> {
+    let chords = keys
+        .iter()
+        .map(|&key| to_vscode_combination(key))
+        .collect::<Option<Vec<_>>>()?;
+    if chords.is_empty() {
+        return None;
+    }
+    Some(chords.join(" "))
+}
+
+#[test]
+fn check_vscode_round_trip() {
+    for raw in [
+        "ctrl+shift+p",
+        "ctrl+k ctrl+s",
+        "alt+enter",
+        "escape",
+        "shift+f6",
+        "ctrl+space",
+        "ctrl+alt+-",
+        "ctrl++",
+    ] {
+        let keys = parse_vscode(raw).unwrap();
+        assert_eq!(to_vscode_string(&keys).unwrap(), raw);
+    }
+    assert_eq!(parse_vscode("Ctrl+Escape").unwrap(), KeySequence::from(key!(ctrl-esc)));
+    assert_eq!(
+        parse_vscode("meta+s").unwrap(),
+        KeySequence::from(KeyCombination::new(Char('s'), KeyModifiers::SUPER)),
+    );
+    assert!(parse_vscode("").is_err());
+    assert!(parse_vscode("ctrl+").is_err());
+    assert!(parse_vscode("ctrl+nothing").is_err());
+}
@@ -0,0 +1,88 @@
+//! Check that the keys written by the public formats can be read back
+//! by the same formats
+
+use {
+    crokey::*,
+    crossterm::event::KeyCode,
+};
+
+fn keys() -> Vec<KeyCombination> {
+    vec![
+        key!(a),
+        key!(shift-a),
+        key!(ctrl-c),
+        key!(ctrl-alt-shift-x),
+        key!(alt-enter),
+        key!(backtab),
+        key!(space),
+        key!(ctrl-space),
+        key!(alt-'-'),
+        key!(ctrl-'['),
+        key!(f12),
+        key!(ctrl-pageup),
+        key!(ctrl-a-b),
+        key!(alt-up-down),
+        key!(unknown),
+        key!('é'),
+        key!(shift-'é'),
+        key!(super-s),
+        key!(ctrl-command-left),
+    ]
+}
+
+fn check_round_trip(format: &KeyCombinationFormat) {
+    for key in keys() {
+        let written = format.to_string(key);
+        let read = format.parse(&written);
+        assert!(read.is_ok(), "{:?} can't be read back by the format", written);
+        assert_eq!(read.unwrap().canonical(), key.canonical(), "round trip of {:?}", written);
+    }
+}
+
+#[test]
+fn default_format_round_trip() {
+    check_round_trip(&KeyCombinationFormat::default());
+}
+
+#[test]
+fn customized_formats_round_trip() {
+    check_round_trip(&KeyCombinationFormat::default().with_lowercase_modifiers());
+    check_round_trip(
+        &KeyCombinationFormat::default()
+            .with_control("^")
+            .with_alt("M-")
+            .with_enter("Return")
+            .with_key_name(KeyCode::PageUp, "PgUp"),
+    );
+    check_round_trip(&KeyCombinationFormat::default().with_implicit_shift());
+}
+
+#[test]
+fn platform_formats_round_trip() {
+    check_round_trip(&KeyCombinationFormat::mac());
+    check_round_trip(&KeyCombinationFormat::windows());
+    check_round_trip(&KeyCombinationFormat::platform_default());
+}
+
+#[test]
+fn display_round_trip() {
+    for key in keys() {
+        let written = key.to_string();
+        let read = KeyCombinationFormat::default().parse(&written).unwrap();
+        assert_eq!(read.canonical(), key.canonical(), "{:?}", written);
+    }
+}
+
+#[test]
+fn config_safe_format_round_trip() {
+    let format = KeyCombinationFormat::config_safe();
+    check_round_trip(&format);
+    for key in keys() {
+        let written = format.to_string(key);
+        assert!(
+            !written.contains(['"', '\'', '\\', '#']) && !written.contains(char::is_whitespace),
+            "{:?} would need escaping",
+            written,
+        );
+    }
+}
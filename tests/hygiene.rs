@@ -6,4 +6,9 @@ fn hygiene() {
     ::crokey::key!(M);
     ::crokey::key!(ctrl-c);
     ::crokey::key!(alt-shift-ctrl-']');
+    ::crokey::key!(super-shift-s);
+    ::crokey::keyseq!(ctrl-x ctrl-s);
+    ::crokey::key_event!(ctrl-alt-f4);
+    ::crokey::key_event!(release shift-'?');
+    ::crokey::keyseq!(g alt-g '?');
 }
@@ -0,0 +1,149 @@
+//! End-to-end tests: keys are sent to this test binary run as a child
+//! in a pseudo terminal, where crossterm reads them and a combiner
+//! builds the key combinations it prints.
+//!
+//! Run with `cargo test --features pty-test --test pty`
+
+// pseudo terminals, and so PtySession, only exist on unix
+#![cfg(unix)]
+
+use {
+    crokey::*,
+    crossterm::{
+        event::{self, Event},
+        terminal,
+    },
+    std::{
+        env,
+        io::{self, Write},
+        process::Command,
+        time::Duration,
+    },
+};
+
+const CHILD_ENV: &str = "CROKEY_PTY_CHILD";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The program run in the PTY: prints the combinations until ctrl-q
+#[test]
+fn pty_child() {
+    let combining = match env::var(CHILD_ENV) {
+        Ok(mode) => mode == "combining",
+        Err(_) => return, // we're not the child
+    };
+    let mut combiner = CombinerCore::default();
+    combiner.set_combining(combining);
+    combiner.set_keypad_distinction(true);
+    terminal::enable_raw_mode().unwrap();
+    let mut stdout = io::stdout();
+    write!(stdout, "ready\r\n").unwrap();
+    stdout.flush().unwrap();
+    loop {
+        let key_event = match event::read() {
+            Ok(Event::Key(key_event)) => key_event,
+            _ => continue,
+        };
+        if let Some(key) = combiner.transform(key_event) {
+            write!(stdout, "key: {}\r\n", key).unwrap();
+            stdout.flush().unwrap();
+            if key == key!(ctrl-q) {
+                break;
+            }
+        }
+    }
+    terminal::disable_raw_mode().unwrap();
+}
+
+/// The program run in the PTY to check that dropping a TtyKeysGuard
+/// keeps the raw mode enabled after its creation
+#[test]
+fn pty_guard_child() {
+    if env::var(CHILD_ENV).is_err() {
+        return; // we're not the child
+    }
+    let guard = TtyKeysGuard::new(true, true).unwrap();
+    terminal::enable_raw_mode().unwrap();
+    drop(guard);
+    let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+    let termios = unsafe {
+        assert_eq!(libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()), 0);
+        termios.assume_init()
+    };
+    let echo = termios.c_lflag & libc::ECHO != 0;
+    let signals = termios.c_lflag & libc::ISIG != 0;
+    terminal::disable_raw_mode().unwrap();
+    println!("echo: {}, signals: {}", echo, signals);
+    io::stdout().flush().unwrap();
+}
+
+fn spawn_child(mode: &str) -> PtySession {
+    let mut command = Command::new(env::current_exe().unwrap());
+    command
+        .args(["--exact", "pty_child", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, mode);
+    let mut session = PtySession::spawn(&mut command).unwrap();
+    session.expect("ready", TIMEOUT).unwrap();
+    session
+}
+
+fn check_keys(session: &mut PtySession, encoding: KeyEncoding, keys: &[KeyCombination]) {
+    for &key in keys {
+        session.write_key(key, encoding).unwrap();
+        session.expect(&format!("key: {}\r\n", key), TIMEOUT).unwrap();
+    }
+}
+
+#[test]
+fn legacy_keys() {
+    let mut session = spawn_child("legacy");
+    check_keys(
+        &mut session,
+        KeyEncoding::Legacy,
+        &[
+            key!(a),
+            key!(shift-b),
+            key!(ctrl-a),
+            key!(alt-x),
+            key!(enter),
+            key!(backtab), // legacy terminals send shift-tab as backtab
+            key!(ctrl-up),
+            key!(f5),
+            key!(alt-shift-f12),
+            key!(pagedown),
+            key!('é'),
+            key!(ctrl-q),
+        ],
+    );
+}
+
+#[test]
+fn kitty_keys() {
+    let mut session = spawn_child("combining");
+    check_keys(
+        &mut session,
+        KeyEncoding::Kitty,
+        &[
+            key!(a),
+            key!(shift-a),
+            key!(ctrl-enter),
+            key!(ctrl-a-b),
+            key!(alt-left-f5),
+            key!(ctrl-shift-f3),
+            key!(kp1),
+            key!(ctrl-kpenter),
+            key!(esc),
+            key!(ctrl-q),
+        ],
+    );
+}
+
+#[test]
+fn tty_keys_guard_keeps_raw_mode() {
+    let mut command = Command::new(env::current_exe().unwrap());
+    command
+        .args(["--exact", "pty_guard_child", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, "guard");
+    let mut session = PtySession::spawn(&mut command).unwrap();
+    // the flags cleared by the guard are set back, not the other ones
+    session.expect("echo: false, signals: true", TIMEOUT).unwrap();
+}
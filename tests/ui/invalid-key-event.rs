@@ -0,0 +1,5 @@
+fn main() {
+    crokey::key_event!(ctrl-a-b);
+    crokey::key_event!(ctrl-nothing);
+    crokey::key_event!(hold ctrl-a);
+}
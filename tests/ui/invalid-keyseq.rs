@@ -0,0 +1,5 @@
+fn main() {
+    crokey::keyseq!();
+    crokey::keyseq!(ctrl-x ctrl-nothing);
+    crokey::keyseq!(ctrl-x, ctrl-s);
+}
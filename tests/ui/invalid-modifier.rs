@@ -1,3 +1,3 @@
 fn main() {
-    crokey::key!(control-c);
+    crokey::key!(hyper-c);
 }
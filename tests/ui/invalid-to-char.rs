@@ -0,0 +1,6 @@
+fn main() {
+    crokey::to_char!();
+    crokey::to_char!(ab);
+    crokey::to_char!(a b);
+    crokey::to_char!(12);
+}